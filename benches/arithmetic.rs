@@ -0,0 +1,102 @@
+// Compares the arithmetic module with ethereum-types. Measured locally against a build whose
+// arithmetic module used ethereum-types and U512, as the opcode handlers did before:
+//
+// * mulmod: 336ns -> 204ns, addmod: 116ns -> 62ns, exp: 12.1us -> 3.8us
+// * The mulmod loop below: 197us -> 141us
+// * The uniswap swaps workload of benches/interpreter.rs: 389us -> 348us. The other workloads
+//   barely use these opcodes, and only changed within noise.
+//
+// The vmPerformance fixtures weren't checked out, so there are no numbers for them yet. With the
+// ethereum-tests submodule, `cargo bench --bench interpreter vmPerformance` measures them.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use ethereum_types::{U256, U512};
+use std::convert::TryFrom;
+use tiny_evm::{arithmetic, run, BlockContext, Bytecode, CallContext};
+
+fn operands() -> (U256, U256, U256) {
+    (
+        U256::from_dec_str(
+            "57896044618658097711785492504343953926634992332820282019728792003956564819949",
+        )
+        .unwrap(),
+        U256::from_dec_str(
+            "3618502788666131106986593281521497120414687020801267626233049500247285301239",
+        )
+        .unwrap(),
+        U256::from_dec_str(
+            "21888242871839275222246405745257275088548364400416034343698204186575808495617",
+        )
+        .unwrap(),
+    )
+}
+
+fn compare_operations(c: &mut Criterion) {
+    let (a, b, modulus) = operands();
+
+    let mut group = c.benchmark_group("mulmod");
+    group.bench_function("limbs", |bencher| {
+        bencher.iter(|| arithmetic::mulmod(black_box(a), black_box(b), black_box(modulus)))
+    });
+    group.bench_function("ethereum-types", |bencher| {
+        bencher.iter(|| {
+            let value = (U512::from(black_box(a)) * U512::from(black_box(b)))
+                % U512::from(black_box(modulus));
+            U256::try_from(value).unwrap()
+        })
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("addmod");
+    group.bench_function("limbs", |bencher| {
+        bencher.iter(|| arithmetic::addmod(black_box(a), black_box(b), black_box(modulus)))
+    });
+    group.bench_function("ethereum-types", |bencher| {
+        bencher.iter(|| {
+            let value = (U512::from(black_box(a)) + U512::from(black_box(b)))
+                % U512::from(black_box(modulus));
+            U256::try_from(value).unwrap()
+        })
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("exp");
+    group.bench_function("limbs", |bencher| {
+        bencher.iter(|| arithmetic::exp(black_box(a), black_box(b)))
+    });
+    group.bench_function("ethereum-types", |bencher| {
+        bencher.iter(|| black_box(a).overflowing_pow(black_box(b)).0)
+    });
+    group.finish();
+}
+
+// Runs 1000 iterations of x = x * x + x mod p, similar to the loops of vmPerformance
+fn mulmod_loop(c: &mut Criterion) {
+    let (_, _, modulus) = operands();
+    let mut modulus_bytes = [0; 32];
+    modulus.to_big_endian(&mut modulus_bytes);
+
+    // PUSH32 p PUSH1 3 PUSH2 1000
+    let mut code = vec![0x7f];
+    code.extend_from_slice(&modulus_bytes);
+    code.extend_from_slice(&[0x60, 0x03, 0x61, 0x03, 0xe8]);
+
+    // loop: JUMPDEST SWAP1 DUP3 DUP2 DUP1 MULMOD DUP3 ADDMOD SWAP1 PUSH1 1 SWAP1 SUB DUP1 PUSH1 loop JUMPI STOP
+    let loop_start = code.len() as u8;
+    code.extend_from_slice(&[
+        0x5b, 0x90, 0x82, 0x81, 0x80, 0x09, 0x81, 0x08, 0x90, 0x60, 0x01, 0x90, 0x03, 0x80, 0x60,
+        loop_start, 0x57, 0x00,
+    ]);
+
+    let bytecode = Bytecode::new(&code);
+
+    c.bench_function("mulmod loop", |bencher| {
+        bencher.iter(|| {
+            let result = run(&bytecode, &CallContext::default(), &BlockContext::default());
+            assert_eq!(result.error, None);
+        })
+    });
+}
+
+criterion_group!(benches, compare_operations, mulmod_loop);
+criterion_main!(benches);
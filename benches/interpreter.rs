@@ -0,0 +1,325 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use ethereum_types::U256;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tiny_evm::{
+    run, run_with_table, BlockContext, Bytecode, CallContext, ExecutionResult, InstructionTable,
+    Opcode, StepResult, VmState,
+};
+
+const VM_PERFORMANCE_TESTS_DIR: &str = "ethereum-tests/VMTests/vmPerformance";
+
+struct Workload {
+    name: String,
+    code: Vec<u8>,
+    calldata: Vec<u8>,
+}
+
+// ERC-20 style transfers: every iteration loads and updates the balances of the sender and of a
+// new recipient, whose storage slots are computed as keccak256(address . 0)
+const ERC20_TRANSFERS: &str = "
+    CALLER PUSH1 0 MSTORE
+    PUSH1 0 PUSH1 32 MSTORE
+    PUSH8 0x0de0b6b3a7640000 PUSH1 64 PUSH1 0 SHA3 SSTORE
+    PUSH1 100
+    loop:
+    CALLER PUSH1 0 MSTORE
+    PUSH1 64 PUSH1 0 SHA3
+    DUP1 SLOAD
+    PUSH1 7
+    DUP2 DUP2 GT @fail JUMPI
+    SWAP1 SUB
+    SWAP1 SSTORE
+    DUP1 PUSH1 0 MSTORE
+    PUSH1 64 PUSH1 0 SHA3
+    DUP1 SLOAD PUSH1 7 ADD
+    SWAP1 SSTORE
+    PUSH1 1 SWAP1 SUB
+    DUP1 @loop JUMPI
+    POP PUSH1 1 PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+    fail:
+    PUSH1 0 DUP1 REVERT
+";
+
+// Uniswap V2's getAmountOut, applied to a pair whose reserves are updated after every swap
+const UNISWAP_SWAPS: &str = "
+    PUSH10 0xd3c21bcecceda1000000 PUSH10 0xd3c21bcecceda1000000 PUSH2 1000
+    loop:
+    PUSH8 0x0de0b6b3a7640000 PUSH2 997 MUL
+    DUP1 DUP5 MUL
+    SWAP1 PUSH2 1000 DUP5 MUL ADD
+    SWAP1 DIV
+    DUP4 SUB
+    SWAP3 POP
+    SWAP1 PUSH8 0x0de0b6b3a7640000 ADD SWAP1
+    PUSH1 1 SWAP1 SUB
+    DUP1 @loop JUMPI
+    STOP
+";
+
+// Hashes a 64 bytes buffer 1000 times
+const KECCAK_LOOP: &str = "
+    PUSH2 1000
+    loop:
+    DUP1 PUSH1 0 MSTORE
+    PUSH1 64 PUSH1 0 SHA3 PUSH1 32 MSTORE
+    PUSH1 1 SWAP1 SUB
+    DUP1 @loop JUMPI
+    STOP
+";
+
+// Copies the whole calldata into memory 200 times, at increasing offsets
+const MEMORY_COPIES: &str = "
+    PUSH1 200
+    loop:
+    CALLDATASIZE PUSH1 0 DUP3 PUSH1 5 SHL CALLDATACOPY
+    DUP1 PUSH1 5 SHL MLOAD POP
+    PUSH1 1 SWAP1 SUB
+    DUP1 @loop JUMPI
+    STOP
+";
+
+// Assembles a program written with opcode names. PUSHes take their value as the next token,
+// `name:` defines a label with a JUMPDEST, and `@name` pushes a label's address.
+fn assemble(source: &str) -> Vec<u8> {
+    let opcodes: HashMap<String, u8> = (0..=255u8)
+        .map(|byte| (format!("{:?}", Opcode::try_from(byte).unwrap()), byte))
+        .collect();
+
+    let mut labels = HashMap::new();
+    let mut code = Vec::new();
+
+    // The first pass finds the labels' addresses, and the second one uses them
+    for _ in 0..2 {
+        code.clear();
+        let mut tokens = source.split_whitespace();
+
+        while let Some(token) = tokens.next() {
+            if let Some(label) = token.strip_suffix(':') {
+                labels.insert(label.to_string(), code.len());
+                code.push(Opcode::JUMPDEST as u8);
+            } else if let Some(label) = token.strip_prefix('@') {
+                let address = labels.get(label).copied().unwrap_or(0) as u16;
+                code.push(Opcode::PUSH2 as u8);
+                code.extend_from_slice(&address.to_be_bytes());
+            } else {
+                let opcode = opcodes[token];
+                code.push(opcode);
+
+                if opcode >= Opcode::PUSH1 as u8 && opcode <= Opcode::PUSH32 as u8 {
+                    let size = (opcode - Opcode::PUSH1 as u8 + 1) as usize;
+                    let value = parse_value(tokens.next().unwrap());
+
+                    let mut bytes = [0; 32];
+                    value.to_big_endian(&mut bytes);
+                    code.extend_from_slice(&bytes[32 - size..]);
+                }
+            }
+        }
+    }
+
+    code
+}
+
+fn parse_value(token: &str) -> U256 {
+    match token.strip_prefix("0x") {
+        Some(hex_str) => U256::from_big_endian(&hex::decode(hex_str).unwrap()),
+        None => U256::from_dec_str(token).unwrap(),
+    }
+}
+
+fn representative_workloads() -> Vec<Workload> {
+    vec![
+        Workload {
+            name: "erc20 transfers".to_string(),
+            code: assemble(ERC20_TRANSFERS),
+            calldata: Vec::new(),
+        },
+        Workload {
+            name: "uniswap swaps".to_string(),
+            code: assemble(UNISWAP_SWAPS),
+            calldata: Vec::new(),
+        },
+        Workload {
+            name: "keccak loop".to_string(),
+            code: assemble(KECCAK_LOOP),
+            calldata: Vec::new(),
+        },
+        Workload {
+            name: "memory copies".to_string(),
+            code: assemble(MEMORY_COPIES),
+            calldata: vec![0xab; 4096],
+        },
+    ]
+}
+
+// The vmPerformance tests are part of the ethereum-tests submodule, so they are only included if
+// it's checked out
+fn vm_performance_workloads() -> Vec<Workload> {
+    let dir = Path::new(VM_PERFORMANCE_TESTS_DIR);
+    if !dir.is_dir() {
+        eprintln!(
+            "{} not found, skipping the vmPerformance benchmarks",
+            VM_PERFORMANCE_TESTS_DIR
+        );
+        return Vec::new();
+    }
+
+    let mut files: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    files.sort();
+
+    let mut workloads = Vec::new();
+
+    for file in files {
+        let tests: HashMap<String, Value> =
+            serde_json::from_str(&fs::read_to_string(&file).unwrap()).unwrap();
+
+        for (name, test) in tests {
+            workloads.push(Workload {
+                name,
+                code: decode_hex(&test["exec"]["code"]),
+                calldata: decode_hex(&test["exec"]["data"]),
+            });
+        }
+    }
+
+    workloads
+}
+
+fn decode_hex(value: &Value) -> Vec<u8> {
+    let hex_str = value.as_str().unwrap_or("");
+    hex::decode(hex_str.trim_start_matches("0x")).unwrap()
+}
+
+static EXECUTED_INSTRUCTIONS: AtomicU64 = AtomicU64::new(0);
+static EXECUTED_GAS: AtomicU64 = AtomicU64::new(0);
+
+fn counting_handler(
+    opcode: Opcode,
+    vm_state: &mut VmState,
+    bytecode: &Bytecode,
+    call_context: &CallContext,
+    block_context: &BlockContext,
+) -> StepResult {
+    EXECUTED_INSTRUCTIONS.fetch_add(1, Ordering::Relaxed);
+    EXECUTED_GAS.fetch_add(opcode.static_gas(), Ordering::Relaxed);
+
+    InstructionTable::default_table().execute(
+        opcode,
+        vm_state,
+        bytecode,
+        call_context,
+        block_context,
+    )
+}
+
+// Runs the workload once with a table that counts every instruction, returning how many
+// instructions were executed and their static gas. There's no gas metering yet, so dynamic costs,
+// like memory expansion, aren't included.
+fn measure(workload: &Workload) -> (u64, u64) {
+    let mut table = InstructionTable::new();
+    for byte in 0..=255u8 {
+        table.set(Opcode::try_from(byte).unwrap(), counting_handler);
+    }
+
+    EXECUTED_INSTRUCTIONS.store(0, Ordering::Relaxed);
+    EXECUTED_GAS.store(0, Ordering::Relaxed);
+
+    let call_context = CallContext {
+        calldata: &workload.calldata,
+        ..CallContext::default()
+    };
+
+    run_with_table(
+        &Bytecode::new(&workload.code),
+        &table,
+        &call_context,
+        &BlockContext::default(),
+    );
+
+    (
+        EXECUTED_INSTRUCTIONS.load(Ordering::Relaxed),
+        EXECUTED_GAS.load(Ordering::Relaxed),
+    )
+}
+
+fn bench_workloads(c: &mut Criterion, group_name: &str, workloads: &[Workload]) {
+    for (unit, use_gas) in &[("instructions", false), ("gas", true)] {
+        let mut group = c.benchmark_group(format!("{} ({}/s)", group_name, unit));
+        group.sample_size(20);
+
+        for workload in workloads {
+            let (instructions, gas) = measure(workload);
+            let elements = if *use_gas { gas } else { instructions };
+
+            group.throughput(Throughput::Elements(elements));
+
+            let bytecode = Bytecode::new(&workload.code);
+            let call_context = CallContext {
+                calldata: &workload.calldata,
+                ..CallContext::default()
+            };
+            let block_context = BlockContext::default();
+
+            group.bench_function(BenchmarkId::from_parameter(&workload.name), |bencher| {
+                bencher.iter(|| run(&bytecode, &call_context, &block_context))
+            });
+        }
+
+        group.finish();
+    }
+}
+
+// Runs the workload once, so that workloads that fail, and would stop early, aren't benchmarked
+fn run_once(workload: &Workload) -> ExecutionResult {
+    let call_context = CallContext {
+        calldata: &workload.calldata,
+        ..CallContext::default()
+    };
+
+    run(
+        &Bytecode::new(&workload.code),
+        &call_context,
+        &BlockContext::default(),
+    )
+}
+
+fn representative(c: &mut Criterion) {
+    let workloads = representative_workloads();
+
+    for workload in &workloads {
+        assert_eq!(run_once(workload).error, None, "{} failed", workload.name);
+    }
+
+    bench_workloads(c, "workloads", &workloads);
+}
+
+// Some of the vmPerformance tests use instructions that aren't supported yet, like CALL, so those
+// are skipped
+fn vm_performance(c: &mut Criterion) {
+    let workloads: Vec<_> = vm_performance_workloads()
+        .into_iter()
+        .filter(|workload| match run_once(workload).error {
+            Some(error) => {
+                eprintln!("Skipping {}, which failed with {}", workload.name, error);
+                false
+            }
+            None => true,
+        })
+        .collect();
+
+    if !workloads.is_empty() {
+        bench_workloads(c, "vmPerformance", &workloads);
+    }
+}
+
+criterion_group!(benches, representative, vm_performance);
+criterion_main!(benches);
@@ -0,0 +1,52 @@
+/*
+ * Runs some code with a host that reads the storage from an array, and prints the result.
+ *
+ *   cargo build --release -p tiny-evm-c
+ *   cc -I bindings/c/include bindings/c/examples/run.c -L target/release -ltiny_evm_c -o run
+ *   LD_LIBRARY_PATH=target/release ./run
+ */
+#include <stdio.h>
+#include <string.h>
+
+#include "tiny_evm.h"
+
+static void get_storage(void *context, const tiny_evm_address *address,
+                        const tiny_evm_uint256be *key, tiny_evm_uint256be *result) {
+    const uint8_t *slots = context;
+    (void)address;
+
+    /* Only the first 16 slots have values */
+    if (memcmp(key->bytes, (uint8_t[31]){0}, 31) == 0 && key->bytes[31] < 16) {
+        result->bytes[31] = slots[key->bytes[31]];
+    }
+}
+
+int main(void) {
+    uint8_t slots[16] = {0, 41};
+
+    /* PUSH1 1 SLOAD PUSH1 1 ADD DUP1 PUSH1 2 SSTORE PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN */
+    const uint8_t code[] = {0x60, 0x01, 0x54, 0x60, 0x01, 0x01, 0x80, 0x60, 0x02, 0x55,
+                            0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3};
+
+    tiny_evm_call_context call_context = {0};
+    tiny_evm_block_context block_context = {0};
+    tiny_evm_host host = {0};
+    host.context = slots;
+    host.get_storage = get_storage;
+
+    tiny_evm_result result =
+        tiny_evm_run(code, sizeof(code), &call_context, &block_context, &host);
+
+    printf("tiny-evm %s\n", tiny_evm_version());
+    printf("status: %d\n", result.status);
+    printf("returned: %d\n", result.return_data[result.return_data_size - 1]);
+
+    for (size_t i = 0; i < result.storage_count; i++) {
+        printf("storage[%d] = %d\n", result.storage[i].key.bytes[31],
+               result.storage[i].value.bytes[31]);
+    }
+
+    tiny_evm_release_result(&result);
+
+    return 0;
+}
@@ -0,0 +1,146 @@
+/*
+ * The C API of tiny-evm.
+ *
+ * This is not an EVMC-compatible ABI. tiny-evm doesn't meter gas, execute calls or contract
+ * creations through the host, nor follow the rules of a revision, which EVMC VMs must do. The
+ * host callbacks mirror the read-only part of evmc_host_interface, so that an EVMC adapter can be
+ * built on top of this API later.
+ */
+
+#ifndef TINY_EVM_H
+#define TINY_EVM_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* A 256-bit unsigned integer, in big-endian order. */
+typedef struct tiny_evm_uint256be {
+    uint8_t bytes[32];
+} tiny_evm_uint256be;
+
+typedef struct tiny_evm_address {
+    uint8_t bytes[20];
+} tiny_evm_address;
+
+typedef struct tiny_evm_bytes32 {
+    uint8_t bytes[32];
+} tiny_evm_bytes32;
+
+typedef struct tiny_evm_call_context {
+    tiny_evm_uint256be value;
+    /* Can be NULL if calldata_size is zero. It's only read during tiny_evm_run. */
+    const uint8_t *calldata;
+    size_t calldata_size;
+    tiny_evm_address contract_address;
+    tiny_evm_address caller_address;
+    tiny_evm_address origin_address;
+    tiny_evm_uint256be gas_price;
+} tiny_evm_call_context;
+
+typedef struct tiny_evm_block_context {
+    tiny_evm_address coinbase_address;
+    uint32_t timestamp;
+    uint32_t number;
+    tiny_evm_uint256be gas_limit;
+    uint32_t difficulty;
+    uint32_t chain_id;
+} tiny_evm_block_context;
+
+/*
+ * Read access to the world state. Every callback receives the host's context as its first argument,
+ * and is called synchronously from tiny_evm_run. Any of them can be NULL, in which case the state
+ * it reads is considered empty.
+ */
+typedef struct tiny_evm_host {
+    void *context;
+
+    void (*get_balance)(void *context, const tiny_evm_address *address,
+                        tiny_evm_uint256be *result);
+
+    size_t (*get_code_size)(void *context, const tiny_evm_address *address);
+
+    /* Copies up to buffer_size bytes of code, starting at code_offset, and returns how many were
+     * copied. */
+    size_t (*copy_code)(void *context, const tiny_evm_address *address, size_t code_offset,
+                        uint8_t *buffer, size_t buffer_size);
+
+    /* Only called for the slots that weren't written during the execution. */
+    void (*get_storage)(void *context, const tiny_evm_address *address,
+                        const tiny_evm_uint256be *key, tiny_evm_uint256be *result);
+
+    void (*get_block_hash)(void *context, uint32_t number, tiny_evm_bytes32 *result);
+} tiny_evm_host;
+
+typedef enum tiny_evm_status {
+    TINY_EVM_SUCCESS = 0,
+    TINY_EVM_REVERT = 1,
+    TINY_EVM_STACK_OVERFLOW = 2,
+    TINY_EVM_STACK_UNDERFLOW = 3,
+    TINY_EVM_INVALID_JUMP = 4,
+    TINY_EVM_INVALID_OPCODE = 5,
+    TINY_EVM_OUT_OF_GAS = 6,
+    TINY_EVM_UNSUPPORTED_OPCODE = 7,
+    /* A NULL context, or a NULL buffer with a non-zero size. */
+    TINY_EVM_INVALID_ARGUMENT = 8,
+    /* A bug in tiny-evm. */
+    TINY_EVM_INTERNAL_ERROR = 9
+} tiny_evm_status;
+
+typedef struct tiny_evm_log {
+    tiny_evm_address address;
+    const tiny_evm_bytes32 *topics;
+    size_t topics_count;
+    const uint8_t *data;
+    size_t data_size;
+} tiny_evm_log;
+
+typedef struct tiny_evm_storage_entry {
+    tiny_evm_uint256be key;
+    tiny_evm_uint256be value;
+} tiny_evm_storage_entry;
+
+/*
+ * The result of an execution. Every buffer it points to is owned by tiny-evm, and stays valid until
+ * the result is passed to tiny_evm_release_result. Empty buffers may be NULL. The logs and the
+ * storage changes are only returned if the execution succeeded.
+ */
+typedef struct tiny_evm_result {
+    tiny_evm_status status;
+    /* The position and the opcode of the instruction that failed, if the execution did. */
+    size_t error_pc;
+    uint8_t error_opcode;
+    const uint8_t *return_data;
+    size_t return_data_size;
+    const tiny_evm_log *logs;
+    size_t logs_count;
+    const tiny_evm_storage_entry *storage;
+    size_t storage_count;
+    /* Private to tiny-evm. Must not be modified. */
+    void *internal;
+} tiny_evm_result;
+
+/* Returns the version of the library. The string is statically allocated. */
+const char *tiny_evm_version(void);
+
+/*
+ * Runs code. code can be NULL if code_size is zero, and host can be NULL, in which case every
+ * account is empty. The returned result must always be released with tiny_evm_release_result,
+ * whatever its status.
+ */
+tiny_evm_result tiny_evm_run(const uint8_t *code, size_t code_size,
+                             const tiny_evm_call_context *call_context,
+                             const tiny_evm_block_context *block_context,
+                             const tiny_evm_host *host);
+
+/* Frees the buffers of a result, and clears its pointers. Releasing a result twice is a no-op. */
+void tiny_evm_release_result(tiny_evm_result *result);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* TINY_EVM_H */
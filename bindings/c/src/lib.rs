@@ -0,0 +1,417 @@
+use ethereum_types::{Address, H256, U256};
+use std::os::raw::{c_char, c_void};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::OnceLock;
+use std::{ptr, slice};
+use tiny_evm::{BlockContext, CallContext, CodeCache, EmptyHost, ExecutionError, Host};
+
+// Keep these definitions in sync with include/tiny_evm.h
+
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct TinyEvmUint256be {
+    pub bytes: [u8; 32],
+}
+
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct TinyEvmAddress {
+    pub bytes: [u8; 20],
+}
+
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct TinyEvmBytes32 {
+    pub bytes: [u8; 32],
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct TinyEvmCallContext {
+    pub value: TinyEvmUint256be,
+    pub calldata: *const u8,
+    pub calldata_size: usize,
+    pub contract_address: TinyEvmAddress,
+    pub caller_address: TinyEvmAddress,
+    pub origin_address: TinyEvmAddress,
+    pub gas_price: TinyEvmUint256be,
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct TinyEvmBlockContext {
+    pub coinbase_address: TinyEvmAddress,
+    pub timestamp: u32,
+    pub number: u32,
+    pub gas_limit: TinyEvmUint256be,
+    pub difficulty: u32,
+    pub chain_id: u32,
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct TinyEvmHost {
+    pub context: *mut c_void,
+    pub get_balance:
+        Option<unsafe extern "C" fn(*mut c_void, *const TinyEvmAddress, *mut TinyEvmUint256be)>,
+    pub get_code_size: Option<unsafe extern "C" fn(*mut c_void, *const TinyEvmAddress) -> usize>,
+    pub copy_code: Option<
+        unsafe extern "C" fn(*mut c_void, *const TinyEvmAddress, usize, *mut u8, usize) -> usize,
+    >,
+    pub get_storage: Option<
+        unsafe extern "C" fn(
+            *mut c_void,
+            *const TinyEvmAddress,
+            *const TinyEvmUint256be,
+            *mut TinyEvmUint256be,
+        ),
+    >,
+    pub get_block_hash: Option<unsafe extern "C" fn(*mut c_void, u32, *mut TinyEvmBytes32)>,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TinyEvmStatus {
+    Success = 0,
+    Revert = 1,
+    StackOverflow = 2,
+    StackUnderflow = 3,
+    InvalidJump = 4,
+    InvalidOpcode = 5,
+    OutOfGas = 6,
+    UnsupportedOpcode = 7,
+    InvalidArgument = 8,
+    InternalError = 9,
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct TinyEvmLog {
+    pub address: TinyEvmAddress,
+    pub topics: *const TinyEvmBytes32,
+    pub topics_count: usize,
+    pub data: *const u8,
+    pub data_size: usize,
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct TinyEvmStorageEntry {
+    pub key: TinyEvmUint256be,
+    pub value: TinyEvmUint256be,
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct TinyEvmResult {
+    pub status: TinyEvmStatus,
+    pub error_pc: usize,
+    pub error_opcode: u8,
+    pub return_data: *const u8,
+    pub return_data_size: usize,
+    pub logs: *const TinyEvmLog,
+    pub logs_count: usize,
+    pub storage: *const TinyEvmStorageEntry,
+    pub storage_count: usize,
+    pub internal: *mut c_void,
+}
+
+// The buffers a TinyEvmResult points to. They are kept alive until the result is released.
+struct ResultBuffers {
+    return_data: Vec<u8>,
+    logs: Vec<TinyEvmLog>,
+    // These are only read through the logs' pointers
+    #[allow(dead_code)]
+    log_topics: Vec<Vec<TinyEvmBytes32>>,
+    #[allow(dead_code)]
+    log_data: Vec<Vec<u8>>,
+    storage: Vec<TinyEvmStorageEntry>,
+}
+
+impl TinyEvmResult {
+    fn without_buffers(status: TinyEvmStatus) -> TinyEvmResult {
+        TinyEvmResult {
+            status,
+            error_pc: 0,
+            error_opcode: 0,
+            return_data: ptr::null(),
+            return_data_size: 0,
+            logs: ptr::null(),
+            logs_count: 0,
+            storage: ptr::null(),
+            storage_count: 0,
+            internal: ptr::null_mut(),
+        }
+    }
+}
+
+struct CallbackHost<'host> {
+    host: &'host TinyEvmHost,
+}
+
+impl Host for CallbackHost<'_> {
+    fn balance(&self, address: &Address) -> U256 {
+        let mut result = TinyEvmUint256be::default();
+
+        if let Some(get_balance) = self.host.get_balance {
+            unsafe { get_balance(self.host.context, &address_to_c(address), &mut result) };
+        }
+
+        U256::from_big_endian(&result.bytes)
+    }
+
+    fn code(&self, address: &Address) -> Vec<u8> {
+        let (get_code_size, copy_code) = match (self.host.get_code_size, self.host.copy_code) {
+            (Some(get_code_size), Some(copy_code)) => (get_code_size, copy_code),
+            _ => return Vec::new(),
+        };
+
+        let address = address_to_c(address);
+        let size = unsafe { get_code_size(self.host.context, &address) };
+
+        let mut code = vec![0; size];
+        let copied = unsafe { copy_code(self.host.context, &address, 0, code.as_mut_ptr(), size) };
+        code.truncate(copied);
+
+        code
+    }
+
+    fn storage(&self, address: &Address, key: &U256) -> U256 {
+        let mut result = TinyEvmUint256be::default();
+
+        if let Some(get_storage) = self.host.get_storage {
+            unsafe {
+                get_storage(
+                    self.host.context,
+                    &address_to_c(address),
+                    &u256_to_c(key),
+                    &mut result,
+                )
+            };
+        }
+
+        U256::from_big_endian(&result.bytes)
+    }
+
+    fn block_hash(&self, number: u32) -> H256 {
+        let mut result = TinyEvmBytes32::default();
+
+        if let Some(get_block_hash) = self.host.get_block_hash {
+            unsafe { get_block_hash(self.host.context, number, &mut result) };
+        }
+
+        H256::from(result.bytes)
+    }
+}
+
+/// Returns the version of the library, as a NUL-terminated string.
+#[no_mangle]
+pub extern "C" fn tiny_evm_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char
+}
+
+/// Runs `code`. `host` can be NULL, in which case every account is empty.
+///
+/// # Safety
+///
+/// `code` must point to `code_size` readable bytes, or be NULL if `code_size` is zero. The
+/// contexts must be valid, and so must the calldata they point to. The returned result must be
+/// released with `tiny_evm_release_result`.
+#[no_mangle]
+pub unsafe extern "C" fn tiny_evm_run(
+    code: *const u8,
+    code_size: usize,
+    call_context: *const TinyEvmCallContext,
+    block_context: *const TinyEvmBlockContext,
+    host: *const TinyEvmHost,
+) -> TinyEvmResult {
+    if call_context.is_null() || block_context.is_null() {
+        return TinyEvmResult::without_buffers(TinyEvmStatus::InvalidArgument);
+    }
+
+    let call_context = &*call_context;
+    let block_context = &*block_context;
+
+    let (code, calldata) = match (
+        bytes_from_c(code, code_size),
+        bytes_from_c(call_context.calldata, call_context.calldata_size),
+    ) {
+        (Some(code), Some(calldata)) => (code, calldata),
+        _ => return TinyEvmResult::without_buffers(TinyEvmStatus::InvalidArgument),
+    };
+
+    // Unwinding into C is undefined behavior, so panics are reported as internal errors
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let callback_host = host.as_ref().map(|host| CallbackHost { host });
+        let host: &dyn Host = match &callback_host {
+            Some(callback_host) => callback_host,
+            None => &EmptyHost,
+        };
+
+        let call_context = CallContext {
+            value: u256_from_c(&call_context.value),
+            calldata,
+            contract_address: address_from_c(&call_context.contract_address),
+            caller_address: address_from_c(&call_context.caller_address),
+            origin_address: address_from_c(&call_context.origin_address),
+            gas_price: u256_from_c(&call_context.gas_price),
+            host,
+        };
+
+        let block_context = BlockContext {
+            coinbase_address: address_from_c(&block_context.coinbase_address),
+            timestamp: block_context.timestamp,
+            number: block_context.number,
+            gas_limit: u256_from_c(&block_context.gas_limit),
+            difficulty: block_context.difficulty,
+            chain_id: block_context.chain_id,
+        };
+
+        tiny_evm::run_analyzed(
+            &code_cache().get_or_analyze(code),
+            &call_context,
+            &block_context,
+        )
+    }));
+
+    let result = match result {
+        Ok(result) => result,
+        Err(_) => return TinyEvmResult::without_buffers(TinyEvmStatus::InternalError),
+    };
+
+    let mut c_result = TinyEvmResult::without_buffers(TinyEvmStatus::Success);
+
+    if let Some(error) = &result.error {
+        c_result.status = status_from_error(error);
+        c_result.error_pc = error.pc();
+        c_result.error_opcode = error.opcode() as u8;
+    }
+
+    let log_topics: Vec<Vec<TinyEvmBytes32>> = result
+        .logs
+        .iter()
+        .map(|log| {
+            log.topics
+                .iter()
+                .map(|topic| TinyEvmBytes32 { bytes: topic.0 })
+                .collect()
+        })
+        .collect();
+    let log_data: Vec<Vec<u8>> = result.logs.iter().map(|log| log.data.clone()).collect();
+
+    // The inner vectors' buffers don't move when they are moved into ResultBuffers, so the logs
+    // can point to them
+    let logs = result
+        .logs
+        .iter()
+        .zip(log_topics.iter().zip(log_data.iter()))
+        .map(|(log, (topics, data))| TinyEvmLog {
+            address: address_to_c(&log.address),
+            topics: topics.as_ptr(),
+            topics_count: topics.len(),
+            data: data.as_ptr(),
+            data_size: data.len(),
+        })
+        .collect();
+
+    let storage = result
+        .storage
+        .iter()
+        .map(|(key, value)| TinyEvmStorageEntry {
+            key: u256_to_c(key),
+            value: u256_to_c(value),
+        })
+        .collect();
+
+    let buffers = Box::new(ResultBuffers {
+        return_data: result.return_data,
+        logs,
+        log_topics,
+        log_data,
+        storage,
+    });
+
+    c_result.return_data = buffers.return_data.as_ptr();
+    c_result.return_data_size = buffers.return_data.len();
+    c_result.logs = buffers.logs.as_ptr();
+    c_result.logs_count = buffers.logs.len();
+    c_result.storage = buffers.storage.as_ptr();
+    c_result.storage_count = buffers.storage.len();
+    c_result.internal = Box::into_raw(buffers) as *mut c_void;
+
+    c_result
+}
+
+/// Frees the buffers of a result, and clears its pointers.
+///
+/// # Safety
+///
+/// `result` must be NULL or point to a result returned by `tiny_evm_run`. Releasing it more than
+/// once is fine, as long as its `internal` field isn't modified.
+#[no_mangle]
+pub unsafe extern "C" fn tiny_evm_release_result(result: *mut TinyEvmResult) {
+    let result = match result.as_mut() {
+        Some(result) => result,
+        None => return,
+    };
+
+    if !result.internal.is_null() {
+        drop(Box::from_raw(result.internal as *mut ResultBuffers));
+    }
+
+    *result = TinyEvmResult::without_buffers(result.status);
+}
+
+// Embedders usually run the same contracts many times, so their analysis is cached across calls
+fn code_cache() -> &'static CodeCache {
+    static CODE_CACHE: OnceLock<CodeCache> = OnceLock::new();
+
+    CODE_CACHE.get_or_init(CodeCache::default)
+}
+
+fn status_from_error(error: &ExecutionError) -> TinyEvmStatus {
+    match error {
+        ExecutionError::Revert { .. } => TinyEvmStatus::Revert,
+        ExecutionError::StackOverflow { .. } => TinyEvmStatus::StackOverflow,
+        ExecutionError::StackUnderflow { .. } => TinyEvmStatus::StackUnderflow,
+        ExecutionError::InvalidJump { .. } => TinyEvmStatus::InvalidJump,
+        ExecutionError::InvalidOpcode { .. } => TinyEvmStatus::InvalidOpcode,
+        ExecutionError::OutOfGas { .. } => TinyEvmStatus::OutOfGas,
+        ExecutionError::UnsupportedOpcode { .. } => TinyEvmStatus::UnsupportedOpcode,
+        // Executions can only be aborted by tracers, which the C API doesn't take
+        ExecutionError::Aborted { .. } => TinyEvmStatus::InternalError,
+        // Kinds of errors added to the core after this binding was updated
+        _ => TinyEvmStatus::InternalError,
+    }
+}
+
+unsafe fn bytes_from_c<'a>(data: *const u8, size: usize) -> Option<&'a [u8]> {
+    if size == 0 {
+        return Some(&[]);
+    }
+
+    if data.is_null() {
+        return None;
+    }
+
+    Some(slice::from_raw_parts(data, size))
+}
+
+fn u256_from_c(value: &TinyEvmUint256be) -> U256 {
+    U256::from_big_endian(&value.bytes)
+}
+
+fn u256_to_c(value: &U256) -> TinyEvmUint256be {
+    let mut result = TinyEvmUint256be::default();
+    value.to_big_endian(&mut result.bytes);
+
+    result
+}
+
+fn address_from_c(address: &TinyEvmAddress) -> Address {
+    Address::from(address.bytes)
+}
+
+fn address_to_c(address: &Address) -> TinyEvmAddress {
+    TinyEvmAddress { bytes: address.0 }
+}
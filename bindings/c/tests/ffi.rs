@@ -0,0 +1,96 @@
+extern crate tiny_evm_c;
+
+use std::ptr;
+use std::slice;
+use tiny_evm_c::*;
+
+fn contexts() -> (TinyEvmCallContext, TinyEvmBlockContext) {
+    let call_context = TinyEvmCallContext {
+        value: TinyEvmUint256be::default(),
+        calldata: ptr::null(),
+        calldata_size: 0,
+        contract_address: TinyEvmAddress { bytes: [0x11; 20] },
+        caller_address: TinyEvmAddress::default(),
+        origin_address: TinyEvmAddress::default(),
+        gas_price: TinyEvmUint256be::default(),
+    };
+
+    let block_context = TinyEvmBlockContext {
+        coinbase_address: TinyEvmAddress::default(),
+        timestamp: 0,
+        number: 0,
+        gas_limit: TinyEvmUint256be::default(),
+        difficulty: 0,
+        chain_id: 0,
+    };
+
+    (call_context, block_context)
+}
+
+#[test]
+fn returns_logs_and_releases_them() {
+    // PUSH1 0xaa PUSH1 0 MSTORE8 PUSH1 5 PUSH1 1 PUSH1 0 LOG1
+    let code = [
+        0x60, 0xaa, 0x60, 0x00, 0x53, 0x60, 0x05, 0x60, 0x01, 0x60, 0x00, 0xa1,
+    ];
+    let (call_context, block_context) = contexts();
+
+    unsafe {
+        let mut result = tiny_evm_run(
+            code.as_ptr(),
+            code.len(),
+            &call_context,
+            &block_context,
+            ptr::null(),
+        );
+
+        assert_eq!(result.status, TinyEvmStatus::Success);
+        assert_eq!(result.logs_count, 1);
+
+        let log = &*result.logs;
+        assert_eq!(log.address.bytes, [0x11; 20]);
+        assert_eq!(slice::from_raw_parts(log.data, log.data_size), &[0xaa]);
+        assert_eq!(log.topics_count, 1);
+        assert_eq!((*log.topics).bytes[31], 5);
+
+        tiny_evm_release_result(&mut result);
+        assert!(result.logs.is_null());
+        assert!(result.internal.is_null());
+
+        tiny_evm_release_result(&mut result);
+    }
+}
+
+#[test]
+fn reports_errors_as_statuses() {
+    // BALANCE without arguments, CALL, and a NULL code
+    let (call_context, block_context) = contexts();
+
+    unsafe {
+        let mut result = tiny_evm_run(
+            [0x31].as_ptr(),
+            1,
+            &call_context,
+            &block_context,
+            ptr::null(),
+        );
+        assert_eq!(result.status, TinyEvmStatus::StackUnderflow);
+        tiny_evm_release_result(&mut result);
+
+        let mut result = tiny_evm_run(
+            [0xf1].as_ptr(),
+            1,
+            &call_context,
+            &block_context,
+            ptr::null(),
+        );
+        assert_eq!(result.status, TinyEvmStatus::UnsupportedOpcode);
+        assert_eq!(result.error_pc, 0);
+        assert_eq!(result.error_opcode, 0xf1);
+        tiny_evm_release_result(&mut result);
+
+        let mut result = tiny_evm_run(ptr::null(), 1, &call_context, &block_context, ptr::null());
+        assert_eq!(result.status, TinyEvmStatus::InvalidArgument);
+        tiny_evm_release_result(&mut result);
+    }
+}
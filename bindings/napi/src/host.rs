@@ -0,0 +1,206 @@
+use crate::{address_to_js, bytes_from_js, u256_from_js, u256_to_js};
+use ethereum_types::{Address, H256, U256};
+use napi::threadsafe_function::{
+    ErrorStrategy, ThreadSafeCallContext, ThreadsafeFunction, ThreadsafeFunctionCallMode,
+};
+use napi::{
+    Env, Error, JsFunction, JsObject, JsUndefined, JsUnknown, Ref, Result, Status, ValueType,
+};
+use std::cell::RefCell;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex};
+use tiny_evm::Host;
+
+#[derive(Clone, Copy)]
+pub enum HostQuery {
+    Balance(Address),
+    Code(Address),
+    Storage(Address, U256),
+    BlockHash(u32),
+}
+
+pub enum HostValue {
+    Number(U256),
+    Bytes(Vec<u8>),
+}
+
+// Answers a query by calling the matching method of a JS host object. Missing methods are
+// treated as empty state.
+pub fn query_js_host(env: &Env, host: &JsObject, query: &HostQuery) -> Result<HostValue> {
+    let (method, args) = match query {
+        HostQuery::Balance(address) => ("balance", vec![address_to_js(env, address)?]),
+        HostQuery::Code(address) => ("code", vec![address_to_js(env, address)?]),
+        HostQuery::Storage(address, key) => (
+            "storage",
+            vec![address_to_js(env, address)?, u256_to_js(env, key)?],
+        ),
+        HostQuery::BlockHash(number) => (
+            "blockHash",
+            vec![env.create_uint32(*number)?.into_unknown()],
+        ),
+    };
+
+    let function: JsUnknown = host.get_named_property(method)?;
+    let returned = if function.get_type()? == ValueType::Function {
+        let function: JsFunction = unsafe { function.cast() };
+        Some(function.call(Some(host), &args)?)
+    } else {
+        None
+    };
+
+    match query {
+        HostQuery::Code(_) | HostQuery::BlockHash(_) => {
+            let bytes = match returned {
+                Some(value) => bytes_from_js(value, method)?,
+                None => Vec::new(),
+            };
+
+            Ok(HostValue::Bytes(bytes))
+        }
+        HostQuery::Balance(_) | HostQuery::Storage(..) => {
+            let number = match returned {
+                Some(value) => u256_from_js(value, method)?,
+                None => U256::zero(),
+            };
+
+            Ok(HostValue::Number(number))
+        }
+    }
+}
+
+// Adapts a function answering host queries to the Host trait. The trait can't fail, so the
+// first error is kept, and every query after it returns empty state.
+pub struct JsHost<F: Fn(&HostQuery) -> Result<HostValue>> {
+    query: F,
+    error: RefCell<Option<Error>>,
+}
+
+impl<F: Fn(&HostQuery) -> Result<HostValue>> JsHost<F> {
+    pub fn new(query: F) -> JsHost<F> {
+        JsHost {
+            query,
+            error: RefCell::new(None),
+        }
+    }
+
+    pub fn take_error(&self) -> Option<Error> {
+        self.error.borrow_mut().take()
+    }
+
+    fn ask(&self, query: HostQuery) -> Option<HostValue> {
+        if self.error.borrow().is_some() {
+            return None;
+        }
+
+        match (self.query)(&query) {
+            Ok(value) => Some(value),
+            Err(error) => {
+                *self.error.borrow_mut() = Some(error);
+                None
+            }
+        }
+    }
+
+    fn ask_number(&self, query: HostQuery) -> U256 {
+        match self.ask(query) {
+            Some(HostValue::Number(number)) => number,
+            _ => U256::zero(),
+        }
+    }
+
+    fn ask_bytes(&self, query: HostQuery) -> Vec<u8> {
+        match self.ask(query) {
+            Some(HostValue::Bytes(bytes)) => bytes,
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl<F: Fn(&HostQuery) -> Result<HostValue>> Host for JsHost<F> {
+    fn balance(&self, address: &Address) -> U256 {
+        self.ask_number(HostQuery::Balance(*address))
+    }
+
+    fn code(&self, address: &Address) -> Vec<u8> {
+        self.ask_bytes(HostQuery::Code(*address))
+    }
+
+    fn storage(&self, address: &Address, key: &U256) -> U256 {
+        self.ask_number(HostQuery::Storage(*address, *key))
+    }
+
+    fn block_hash(&self, number: u32) -> H256 {
+        let bytes = self.ask_bytes(HostQuery::BlockHash(number));
+
+        if bytes.len() != 32 {
+            return H256::zero();
+        }
+
+        H256::from_slice(&bytes)
+    }
+}
+
+type HostRequest = (HostQuery, Sender<Result<HostValue>>);
+
+// Lets code running in the thread pool query a JS host object. Each query is sent to the main
+// thread, which calls the host and sends back its answer.
+pub struct ThreadsafeHost {
+    host: Arc<Mutex<Ref<()>>>,
+    function: ThreadsafeFunction<HostRequest, ErrorStrategy::Fatal>,
+}
+
+impl ThreadsafeHost {
+    pub fn new(env: &Env, host: JsObject) -> Result<ThreadsafeHost> {
+        let host = Arc::new(Mutex::new(env.create_reference(host)?));
+        let host_in_callback = Arc::clone(&host);
+
+        // The queries are answered by the callback, which has access to the main thread's Env, so
+        // the function itself doesn't do anything
+        let noop = env.create_function_from_closure("queryHost", |_| Ok(()))?;
+
+        let function = noop.create_threadsafe_function(
+            0,
+            move |context: ThreadSafeCallContext<HostRequest>| {
+                let env = context.env;
+                let (query, sender) = context.value;
+                let reference = host_in_callback.lock().unwrap();
+                let result = env
+                    .get_reference_value::<JsObject>(&reference)
+                    .and_then(|host| query_js_host(&env, &host, &query));
+
+                // The receiver only goes away if the execution was aborted
+                let _ = sender.send(result);
+
+                Ok(Vec::<JsUndefined>::new())
+            },
+        )?;
+
+        Ok(ThreadsafeHost { host, function })
+    }
+
+    pub fn query(&self, query: &HostQuery) -> Result<HostValue> {
+        let (sender, receiver) = channel();
+
+        let status = self
+            .function
+            .call((*query, sender), ThreadsafeFunctionCallMode::Blocking);
+
+        if status != Status::Ok {
+            return Err(Error::new(status, "Couldn't call the host".to_string()));
+        }
+
+        receiver.recv().unwrap_or_else(|_| {
+            Err(Error::new(
+                Status::GenericFailure,
+                "The host didn't answer".to_string(),
+            ))
+        })
+    }
+
+    // Must be called from the main thread once the execution finished
+    pub fn release(&self, env: Env) -> Result<()> {
+        self.host.lock().unwrap().unref(env)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,367 @@
+mod host;
+
+use ethereum_types::{Address, U256};
+use host::{query_js_host, JsHost, ThreadsafeHost};
+use napi::bindgen_prelude::AsyncTask;
+use napi::{
+    Env, Error, JsBigInt, JsBuffer, JsFunction, JsObject, JsTypedArray, JsUnknown, Result, Status,
+    Task, TypedArrayType, ValueType,
+};
+use napi_derive::napi;
+use std::sync::OnceLock;
+use tiny_evm::{BlockContext, CallContext, CodeCache, ExecutionResult, Host, Log};
+
+// Everything needed to run some code, owned so that it can be sent to the thread pool
+struct RunInput {
+    code: Vec<u8>,
+    calldata: Vec<u8>,
+    value: U256,
+    contract_address: Address,
+    caller_address: Address,
+    origin_address: Address,
+    gas_price: U256,
+    block_context: BlockContext,
+}
+
+impl RunInput {
+    fn from_js(
+        code: JsUnknown,
+        call_context: Option<JsObject>,
+        block_context: Option<JsObject>,
+    ) -> Result<RunInput> {
+        let call_context = call_context.as_ref();
+        let block_context = block_context.as_ref();
+
+        Ok(RunInput {
+            code: bytes_from_js(code, "code")?,
+            calldata: optional_field(call_context, "calldata", bytes_from_js)?.unwrap_or_default(),
+            value: u256_field(call_context, "value")?,
+            contract_address: address_field(call_context, "contractAddress")?,
+            caller_address: address_field(call_context, "callerAddress")?,
+            origin_address: address_field(call_context, "originAddress")?,
+            gas_price: u256_field(call_context, "gasPrice")?,
+            block_context: BlockContext {
+                coinbase_address: address_field(block_context, "coinbaseAddress")?,
+                timestamp: u32_field(block_context, "timestamp")?,
+                number: u32_field(block_context, "number")?,
+                gas_limit: u256_field(block_context, "gasLimit")?,
+                difficulty: u32_field(block_context, "difficulty")?,
+                chain_id: u32_field(block_context, "chainId")?,
+            },
+        })
+    }
+
+    fn run(&self, host: &dyn Host) -> ExecutionResult {
+        let call_context = CallContext {
+            value: self.value,
+            calldata: &self.calldata,
+            contract_address: self.contract_address,
+            caller_address: self.caller_address,
+            origin_address: self.origin_address,
+            gas_price: self.gas_price,
+            host,
+        };
+
+        tiny_evm::run_analyzed(
+            &code_cache().get_or_analyze(&self.code),
+            &call_context,
+            &self.block_context,
+        )
+    }
+}
+
+// Runs `code` and returns an object with its return data, error, logs and storage. It takes the
+// same arguments as the wasm bindings, plus an optional host object with `balance`, `code`,
+// `storage` and `blockHash` methods, which are called to read the world state.
+#[napi(
+    ts_args_type = "code: string | Uint8Array, callContext?: object, blockContext?: object, host?: object",
+    ts_return_type = "object"
+)]
+pub fn run(
+    env: Env,
+    code: JsUnknown,
+    call_context: Option<JsObject>,
+    block_context: Option<JsObject>,
+    host: Option<JsObject>,
+) -> Result<JsObject> {
+    let input = RunInput::from_js(code, call_context, block_context)?;
+
+    let result = match host {
+        Some(host) => {
+            let js_host = JsHost::new(|query| query_js_host(&env, &host, query));
+            let result = input.run(&js_host);
+
+            if let Some(error) = js_host.take_error() {
+                return Err(error);
+            }
+
+            result
+        }
+        None => input.run(&tiny_evm::EmptyHost),
+    };
+
+    result_to_js(&env, &result)
+}
+
+pub struct RunTask {
+    input: RunInput,
+    host: Option<ThreadsafeHost>,
+}
+
+impl Task for RunTask {
+    type Output = ExecutionResult;
+    type JsValue = JsObject;
+
+    fn compute(&mut self) -> Result<ExecutionResult> {
+        match &self.host {
+            Some(threadsafe_host) => {
+                let js_host = JsHost::new(|query| threadsafe_host.query(query));
+                let result = self.input.run(&js_host);
+
+                match js_host.take_error() {
+                    Some(error) => Err(error),
+                    None => Ok(result),
+                }
+            }
+            None => Ok(self.input.run(&tiny_evm::EmptyHost)),
+        }
+    }
+
+    fn resolve(&mut self, env: Env, result: ExecutionResult) -> Result<JsObject> {
+        result_to_js(&env, &result)
+    }
+
+    fn finally(&mut self, env: Env) -> Result<()> {
+        match self.host.take() {
+            Some(threadsafe_host) => threadsafe_host.release(env),
+            None => Ok(()),
+        }
+    }
+}
+
+// Like `run`, but executes the code in the thread pool, returning a Promise. The host's methods
+// are still called in the main thread, and must not return Promises.
+#[napi(
+    ts_args_type = "code: string | Uint8Array, callContext?: object, blockContext?: object, host?: object",
+    ts_return_type = "Promise<object>"
+)]
+pub fn run_async(
+    env: Env,
+    code: JsUnknown,
+    call_context: Option<JsObject>,
+    block_context: Option<JsObject>,
+    host: Option<JsObject>,
+) -> Result<AsyncTask<RunTask>> {
+    let input = RunInput::from_js(code, call_context, block_context)?;
+
+    let host = match host {
+        Some(host) => Some(ThreadsafeHost::new(&env, host)?),
+        None => None,
+    };
+
+    Ok(AsyncTask::new(RunTask { input, host }))
+}
+
+fn invalid_arg(message: String) -> Error {
+    Error::new(Status::InvalidArg, message)
+}
+
+fn optional_field<T>(
+    object: Option<&JsObject>,
+    name: &str,
+    parse: fn(JsUnknown, &str) -> Result<T>,
+) -> Result<Option<T>> {
+    let object = match object {
+        Some(object) => object,
+        None => return Ok(None),
+    };
+
+    let value: JsUnknown = object.get_named_property(name)?;
+    match value.get_type()? {
+        ValueType::Undefined | ValueType::Null => Ok(None),
+        _ => parse(value, name).map(Some),
+    }
+}
+
+fn address_field(object: Option<&JsObject>, name: &str) -> Result<Address> {
+    Ok(optional_field(object, name, address_from_js)?.unwrap_or_else(Address::zero))
+}
+
+// Embedders usually run the same contracts many times, so their analysis is cached across calls
+fn code_cache() -> &'static CodeCache {
+    static CODE_CACHE: OnceLock<CodeCache> = OnceLock::new();
+
+    CODE_CACHE.get_or_init(CodeCache::default)
+}
+
+fn u256_field(object: Option<&JsObject>, name: &str) -> Result<U256> {
+    Ok(optional_field(object, name, u256_from_js)?.unwrap_or_else(U256::zero))
+}
+
+fn u32_field(object: Option<&JsObject>, name: &str) -> Result<u32> {
+    let number = optional_field(object, name, u256_from_js)?.unwrap_or_else(U256::zero);
+
+    if number > U256::from(u32::MAX) {
+        return Err(invalid_arg(format!(
+            "{} must be an integer that fits in 32 bits",
+            name
+        )));
+    }
+
+    Ok(number.as_u32())
+}
+
+fn bytes_from_js(value: JsUnknown, name: &str) -> Result<Vec<u8>> {
+    if value.get_type()? == ValueType::String {
+        let hex_str = value.coerce_to_string()?.into_utf8()?.into_owned()?;
+
+        return parse_hex(&hex_str)
+            .ok_or_else(|| invalid_arg(format!("{} is not a valid hex string", name)));
+    }
+
+    if value.is_buffer()? {
+        let buffer: JsBuffer = unsafe { value.cast() };
+        return Ok(buffer.into_value()?.to_vec());
+    }
+
+    if value.is_typedarray()? {
+        let array: JsTypedArray = unsafe { value.cast() };
+        let array = array.into_value()?;
+
+        if array.typedarray_type == TypedArrayType::Uint8 {
+            let bytes: &[u8] = array.as_ref();
+            return Ok(bytes.to_vec());
+        }
+    }
+
+    Err(invalid_arg(format!(
+        "{} must be a hex string or a Uint8Array",
+        name
+    )))
+}
+
+fn address_from_js(value: JsUnknown, name: &str) -> Result<Address> {
+    let bytes = if value.get_type()? == ValueType::String {
+        bytes_from_js(value, name).ok()
+    } else {
+        None
+    };
+
+    match bytes {
+        Some(bytes) if bytes.len() == 20 => Ok(Address::from_slice(&bytes)),
+        _ => Err(invalid_arg(format!(
+            "{} must be a 20 bytes hex string",
+            name
+        ))),
+    }
+}
+
+// Accepts BigInts and, for convenience, non-negative safe integers
+fn u256_from_js(value: JsUnknown, name: &str) -> Result<U256> {
+    let error = || {
+        invalid_arg(format!(
+            "{} must be a non-negative BigInt that fits in 256 bits",
+            name
+        ))
+    };
+
+    match value.get_type()? {
+        ValueType::Number => {
+            let number = value.coerce_to_number()?.get_double()?;
+            if number < 0.0 || number.fract() != 0.0 || number > 9007199254740991.0 {
+                return Err(error());
+            }
+
+            Ok(U256::from(number as u64))
+        }
+        ValueType::BigInt => {
+            let mut big_int: JsBigInt = unsafe { value.cast() };
+            let (negative, mut words) = big_int.get_words()?;
+
+            while words.last() == Some(&0) {
+                words.pop();
+            }
+
+            if negative || words.len() > 4 {
+                return Err(error());
+            }
+
+            words.resize(4, 0);
+
+            let mut limbs = [0; 4];
+            limbs.copy_from_slice(&words);
+
+            Ok(U256(limbs))
+        }
+        _ => Err(error()),
+    }
+}
+
+fn u256_to_js(env: &Env, value: &U256) -> Result<JsUnknown> {
+    env.create_bigint_from_words(false, value.0.to_vec())?
+        .into_unknown()
+}
+
+fn address_to_js(env: &Env, address: &Address) -> Result<JsUnknown> {
+    Ok(env.create_string(&format!("{:?}", address))?.into_unknown())
+}
+
+fn uint8_array(env: &Env, bytes: &[u8]) -> Result<JsTypedArray> {
+    env.create_arraybuffer_with_data(bytes.to_vec())?
+        .into_raw()
+        .into_typedarray(TypedArrayType::Uint8, bytes.len(), 0)
+}
+
+fn result_to_js(env: &Env, result: &ExecutionResult) -> Result<JsObject> {
+    let mut object = env.create_object()?;
+
+    object.set_named_property("returnData", uint8_array(env, &result.return_data)?)?;
+
+    match &result.error {
+        Some(error) => object.set_named_property("error", env.create_string(error.kind())?)?,
+        None => object.set_named_property("error", env.get_null()?)?,
+    }
+
+    let mut logs = env.create_array_with_length(result.logs.len())?;
+    for (i, log) in result.logs.iter().enumerate() {
+        logs.set_element(i as u32, log_to_js(env, log)?)?;
+    }
+    object.set_named_property("logs", logs)?;
+
+    // Plain objects can't have BigInt keys, so the storage is returned as a Map
+    let map_constructor: JsFunction = env.get_global()?.get_named_property("Map")?;
+    let storage = map_constructor.new_instance::<JsUnknown>(&[])?;
+    let set: JsFunction = storage.get_named_property("set")?;
+
+    for (key, value) in &result.storage {
+        set.call(
+            Some(&storage),
+            &[u256_to_js(env, key)?, u256_to_js(env, value)?],
+        )?;
+    }
+    object.set_named_property("storage", storage)?;
+
+    Ok(object)
+}
+
+fn log_to_js(env: &Env, log: &Log) -> Result<JsObject> {
+    let mut object = env.create_object()?;
+
+    object.set_named_property("address", address_to_js(env, &log.address)?)?;
+
+    let mut topics = env.create_array_with_length(log.topics.len())?;
+    for (i, topic) in log.topics.iter().enumerate() {
+        topics.set_element(i as u32, env.create_string(&format!("{:?}", topic))?)?;
+    }
+    object.set_named_property("topics", topics)?;
+
+    object.set_named_property("data", uint8_array(env, &log.data)?)?;
+
+    Ok(object)
+}
+
+fn parse_hex(hex_str: &str) -> Option<Vec<u8>> {
+    let hex_str = hex_str.strip_prefix("0x").unwrap_or(hex_str);
+
+    hex::decode(hex_str).ok()
+}
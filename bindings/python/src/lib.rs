@@ -0,0 +1,334 @@
+use ethereum_types::{Address, U256};
+use num_bigint::BigUint;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::collections::HashMap;
+
+#[pyclass(name = "Bytecode", module = "tiny_evm", frozen)]
+struct PyBytecode {
+    // Keeps the result of the jumpdest analysis across runs
+    code: tiny_evm::AnalyzedCode,
+}
+
+#[pymethods]
+impl PyBytecode {
+    #[new]
+    fn new(code: &[u8]) -> PyBytecode {
+        PyBytecode {
+            code: tiny_evm::AnalyzedCode::new(code),
+        }
+    }
+
+    fn __len__(&self) -> usize {
+        self.code.code().len()
+    }
+
+    fn __bytes__(&self) -> &[u8] {
+        self.code.code()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Bytecode({} bytes)", self.code.code().len())
+    }
+}
+
+#[pyclass(name = "CallContext", module = "tiny_evm", get_all, set_all)]
+#[derive(Clone, Default)]
+struct PyCallContext {
+    value: BigUint,
+    calldata: Vec<u8>,
+    contract_address: [u8; 20],
+    caller_address: [u8; 20],
+    origin_address: [u8; 20],
+    gas_price: BigUint,
+}
+
+#[pymethods]
+impl PyCallContext {
+    #[new]
+    #[pyo3(signature = (
+        value = BigUint::default(),
+        calldata = Vec::new(),
+        contract_address = [0; 20],
+        caller_address = [0; 20],
+        origin_address = [0; 20],
+        gas_price = BigUint::default(),
+    ))]
+    fn new(
+        value: BigUint,
+        calldata: Vec<u8>,
+        contract_address: [u8; 20],
+        caller_address: [u8; 20],
+        origin_address: [u8; 20],
+        gas_price: BigUint,
+    ) -> PyCallContext {
+        PyCallContext {
+            value,
+            calldata,
+            contract_address,
+            caller_address,
+            origin_address,
+            gas_price,
+        }
+    }
+}
+
+#[pyclass(name = "BlockContext", module = "tiny_evm", get_all, set_all)]
+#[derive(Clone, Default)]
+struct PyBlockContext {
+    coinbase_address: [u8; 20],
+    timestamp: u32,
+    number: u32,
+    gas_limit: BigUint,
+    difficulty: u32,
+    chain_id: u32,
+}
+
+#[pymethods]
+impl PyBlockContext {
+    #[new]
+    #[pyo3(signature = (
+        coinbase_address = [0; 20],
+        timestamp = 0,
+        number = 0,
+        gas_limit = BigUint::default(),
+        difficulty = 0,
+        chain_id = 0,
+    ))]
+    fn new(
+        coinbase_address: [u8; 20],
+        timestamp: u32,
+        number: u32,
+        gas_limit: BigUint,
+        difficulty: u32,
+        chain_id: u32,
+    ) -> PyBlockContext {
+        PyBlockContext {
+            coinbase_address,
+            timestamp,
+            number,
+            gas_limit,
+            difficulty,
+            chain_id,
+        }
+    }
+}
+
+#[pyclass(name = "Log", module = "tiny_evm", get_all, frozen)]
+#[derive(Clone)]
+struct PyLog {
+    address: [u8; 20],
+    topics: Vec<[u8; 32]>,
+    data: Vec<u8>,
+}
+
+#[pyclass(name = "ExecutionResult", module = "tiny_evm", get_all, frozen)]
+struct PyExecutionResult {
+    return_data: Vec<u8>,
+    // The kind of error, like "Revert", or None if the execution succeeded
+    error: Option<String>,
+    logs: Vec<PyLog>,
+    storage: HashMap<BigUint, BigUint>,
+}
+
+// A snapshot of the VM, passed to the tracer callbacks. In on_step it's taken before executing the
+// instruction at pc, and in on_step_end after executing it.
+#[pyclass(name = "Step", module = "tiny_evm", get_all, frozen)]
+struct PyStep {
+    pc: usize,
+    opcode: u8,
+    opcode_name: String,
+    // From the bottom to the top
+    stack: Vec<BigUint>,
+    memory: Vec<u8>,
+    // Only set in on_step_end, if the instruction failed
+    error: Option<String>,
+}
+
+// Calls the Python callbacks. The first exception aborts the execution, and is raised once it
+// stops.
+struct PyTracer<'py> {
+    py: Python<'py>,
+    on_step: Option<PyObject>,
+    on_step_end: Option<PyObject>,
+    pc: usize,
+    opcode_name: String,
+    error: Option<PyErr>,
+}
+
+impl PyTracer<'_> {
+    fn call(&mut self, callback: Option<&PyObject>, step: PyStep) {
+        let callback = match callback {
+            Some(callback) if self.error.is_none() => callback,
+            _ => return,
+        };
+
+        if let Err(error) = callback.call1(self.py, (step,)) {
+            self.error = Some(error);
+        }
+    }
+}
+
+impl tiny_evm::Tracer for PyTracer<'_> {
+    fn step(
+        &mut self,
+        opcode: tiny_evm::Opcode,
+        vm_state: &tiny_evm::VmState,
+        instruction_table: &tiny_evm::InstructionTable,
+    ) -> tiny_evm::TracerAction {
+        self.pc = vm_state.pc;
+        self.opcode_name = instruction_table.opcode_name(opcode);
+
+        let step = snapshot(self.pc, opcode, &self.opcode_name, vm_state, None);
+        let on_step = self.on_step.take();
+        self.call(on_step.as_ref(), step);
+        self.on_step = on_step;
+
+        // on_step_end may have failed in the previous instruction too
+        if self.error.is_some() {
+            tiny_evm::TracerAction::Abort
+        } else {
+            tiny_evm::TracerAction::Continue
+        }
+    }
+
+    fn step_end(
+        &mut self,
+        opcode: tiny_evm::Opcode,
+        vm_state: &tiny_evm::VmState,
+        result: &tiny_evm::StepResult,
+    ) {
+        let error = result.as_ref().err().map(|error| error.to_string());
+
+        let step = snapshot(self.pc, opcode, &self.opcode_name, vm_state, error);
+        let on_step_end = self.on_step_end.take();
+        self.call(on_step_end.as_ref(), step);
+        self.on_step_end = on_step_end;
+    }
+}
+
+fn snapshot(
+    pc: usize,
+    opcode: tiny_evm::Opcode,
+    opcode_name: &str,
+    vm_state: &tiny_evm::VmState,
+    error: Option<String>,
+) -> PyStep {
+    PyStep {
+        pc,
+        opcode: opcode as u8,
+        opcode_name: opcode_name.to_string(),
+        stack: vm_state.stack.as_slice().iter().map(u256_to_py).collect(),
+        memory: vm_state.memory.as_bytes().to_vec(),
+        error,
+    }
+}
+
+// Runs the bytecode, calling on_step before each instruction and on_step_end after it
+#[pyfunction]
+#[pyo3(signature = (bytecode, call_context = None, block_context = None, on_step = None, on_step_end = None))]
+fn run(
+    py: Python<'_>,
+    bytecode: &PyBytecode,
+    call_context: Option<PyCallContext>,
+    block_context: Option<PyBlockContext>,
+    on_step: Option<PyObject>,
+    on_step_end: Option<PyObject>,
+) -> PyResult<PyExecutionResult> {
+    let call_context = call_context.unwrap_or_default();
+    let block_context = block_context.unwrap_or_default();
+
+    let call_context = tiny_evm::CallContext {
+        value: u256_from_py(&call_context.value, "value")?,
+        calldata: &call_context.calldata,
+        contract_address: Address::from(call_context.contract_address),
+        caller_address: Address::from(call_context.caller_address),
+        origin_address: Address::from(call_context.origin_address),
+        gas_price: u256_from_py(&call_context.gas_price, "gas_price")?,
+        ..tiny_evm::CallContext::default()
+    };
+
+    let block_context = tiny_evm::BlockContext {
+        coinbase_address: Address::from(block_context.coinbase_address),
+        timestamp: block_context.timestamp,
+        number: block_context.number,
+        gas_limit: u256_from_py(&block_context.gas_limit, "gas_limit")?,
+        difficulty: block_context.difficulty,
+        chain_id: block_context.chain_id,
+    };
+
+    let bytecode = bytecode.code.bytecode();
+
+    let result = if on_step.is_none() && on_step_end.is_none() {
+        tiny_evm::run(&bytecode, &call_context, &block_context)
+    } else {
+        let mut tracer = PyTracer {
+            py,
+            on_step,
+            on_step_end,
+            pc: 0,
+            opcode_name: String::new(),
+            error: None,
+        };
+
+        let result =
+            tiny_evm::run_with_tracer(&bytecode, &mut tracer, &call_context, &block_context);
+
+        if let Some(error) = tracer.error {
+            return Err(error);
+        }
+
+        result
+    };
+
+    Ok(PyExecutionResult {
+        return_data: result.return_data,
+        error: result.error.map(|error| error.kind().to_string()),
+        logs: result
+            .logs
+            .into_iter()
+            .map(|log| PyLog {
+                address: log.address.0,
+                topics: log.topics.iter().map(|topic| topic.0).collect(),
+                data: log.data,
+            })
+            .collect(),
+        storage: result
+            .storage
+            .iter()
+            .map(|(key, value)| (u256_to_py(key), u256_to_py(value)))
+            .collect(),
+    })
+}
+
+fn u256_from_py(value: &BigUint, name: &str) -> PyResult<U256> {
+    if value.bits() > 256 {
+        return Err(PyValueError::new_err(format!(
+            "{} doesn't fit in 256 bits",
+            name
+        )));
+    }
+
+    Ok(U256::from_big_endian(&value.to_bytes_be()))
+}
+
+fn u256_to_py(value: &U256) -> BigUint {
+    let mut bytes = [0; 32];
+    value.to_big_endian(&mut bytes);
+
+    BigUint::from_bytes_be(&bytes)
+}
+
+#[pymodule]
+#[pyo3(name = "tiny_evm")]
+fn init(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyBytecode>()?;
+    module.add_class::<PyCallContext>()?;
+    module.add_class::<PyBlockContext>()?;
+    module.add_class::<PyLog>()?;
+    module.add_class::<PyExecutionResult>()?;
+    module.add_class::<PyStep>()?;
+    module.add_function(wrap_pyfunction!(run, module)?)?;
+
+    Ok(())
+}
@@ -0,0 +1,218 @@
+use ethereum_types::{Address, H256, U256};
+use js_sys::{Array, BigInt, Map, Object, Reflect, Uint8Array};
+use std::collections::HashMap;
+use tiny_evm::{BlockContext, Bytecode, CallContext, ExecutionResult, Log};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+// Runs `code` and returns an object with its return data, error, logs and storage. Code and
+// calldata can be hex strings or Uint8Arrays, and both contexts are plain objects whose missing
+// fields take their default values.
+#[wasm_bindgen]
+pub fn run(
+    code: JsValue,
+    call_context: JsValue,
+    block_context: JsValue,
+) -> Result<JsValue, JsError> {
+    let code = bytes_from_js(&code, "code")?;
+    let calldata = bytes_from_js(&get_field(&call_context, "calldata")?, "calldata")?;
+
+    let call_context = CallContext {
+        value: u256_field(&call_context, "value")?,
+        calldata: &calldata,
+        contract_address: address_field(&call_context, "contractAddress")?,
+        caller_address: address_field(&call_context, "callerAddress")?,
+        origin_address: address_field(&call_context, "originAddress")?,
+        gas_price: u256_field(&call_context, "gasPrice")?,
+        ..CallContext::default()
+    };
+
+    let block_context = BlockContext {
+        coinbase_address: address_field(&block_context, "coinbaseAddress")?,
+        timestamp: u32_field(&block_context, "timestamp")?,
+        number: u32_field(&block_context, "number")?,
+        gas_limit: u256_field(&block_context, "gasLimit")?,
+        difficulty: u32_field(&block_context, "difficulty")?,
+        chain_id: u32_field(&block_context, "chainId")?,
+    };
+
+    let result = tiny_evm::run(&Bytecode::new(&code), &call_context, &block_context);
+
+    result_to_js(&result)
+}
+
+fn get_field(object: &JsValue, name: &str) -> Result<JsValue, JsError> {
+    if object.is_undefined() || object.is_null() {
+        return Ok(JsValue::UNDEFINED);
+    }
+
+    Reflect::get(object, &JsValue::from_str(name))
+        .map_err(|_| JsError::new(&format!("Couldn't read {}", name)))
+}
+
+fn bytes_from_js(value: &JsValue, name: &str) -> Result<Vec<u8>, JsError> {
+    if value.is_undefined() {
+        return Ok(Vec::new());
+    }
+
+    if let Some(array) = value.dyn_ref::<Uint8Array>() {
+        return Ok(array.to_vec());
+    }
+
+    match value.as_string() {
+        Some(hex_str) => parse_hex(&hex_str)
+            .ok_or_else(|| JsError::new(&format!("{} is not a valid hex string", name))),
+        None => Err(JsError::new(&format!(
+            "{} must be a hex string or a Uint8Array",
+            name
+        ))),
+    }
+}
+
+fn address_field(object: &JsValue, name: &str) -> Result<Address, JsError> {
+    let value = get_field(object, name)?;
+    if value.is_undefined() {
+        return Ok(Address::zero());
+    }
+
+    value
+        .as_string()
+        .and_then(|hex_str| parse_hex(&hex_str))
+        .filter(|bytes| bytes.len() == 20)
+        .map(|bytes| Address::from_slice(&bytes))
+        .ok_or_else(|| JsError::new(&format!("{} must be a 20 bytes hex string", name)))
+}
+
+fn u256_field(object: &JsValue, name: &str) -> Result<U256, JsError> {
+    let value = get_field(object, name)?;
+    if value.is_undefined() {
+        return Ok(U256::zero());
+    }
+
+    u256_from_js(&value).ok_or_else(|| {
+        JsError::new(&format!(
+            "{} must be a non-negative BigInt that fits in 256 bits",
+            name
+        ))
+    })
+}
+
+fn u32_field(object: &JsValue, name: &str) -> Result<u32, JsError> {
+    let value = get_field(object, name)?;
+    if value.is_undefined() {
+        return Ok(0);
+    }
+
+    u256_from_js(&value)
+        .filter(|number| *number <= U256::from(u32::MAX))
+        .map(|number| number.as_u32())
+        .ok_or_else(|| JsError::new(&format!("{} must be an integer that fits in 32 bits", name)))
+}
+
+// Accepts BigInts and, for convenience, non-negative safe integers
+fn u256_from_js(value: &JsValue) -> Option<U256> {
+    if let Some(number) = value.as_f64() {
+        if number < 0.0 || number.fract() != 0.0 || number > 9007199254740991.0 {
+            return None;
+        }
+
+        return Some(U256::from(number as u64));
+    }
+
+    let big_int = value.dyn_ref::<BigInt>()?;
+    let hex_str = String::from(big_int.to_string(16).ok()?);
+
+    parse_u256_hex(&hex_str)
+}
+
+fn u256_to_js(value: &U256) -> JsValue {
+    BigInt::new(&JsValue::from_str(&format!("{:#x}", value)))
+        .expect("A hex string is always a valid BigInt")
+        .into()
+}
+
+fn result_to_js(result: &ExecutionResult) -> Result<JsValue, JsError> {
+    let object = Object::new();
+
+    set_field(
+        &object,
+        "returnData",
+        &Uint8Array::from(result.return_data.as_slice()).into(),
+    )?;
+
+    let error = match &result.error {
+        Some(error) => JsValue::from_str(error.kind()),
+        None => JsValue::NULL,
+    };
+    set_field(&object, "error", &error)?;
+
+    let logs: Array = result
+        .logs
+        .iter()
+        .map(log_to_js)
+        .collect::<Result<_, _>>()?;
+    set_field(&object, "logs", &logs.into())?;
+
+    set_field(&object, "storage", &storage_to_js(&result.storage).into())?;
+
+    Ok(object.into())
+}
+
+fn log_to_js(log: &Log) -> Result<JsValue, JsError> {
+    let object = Object::new();
+
+    set_field(
+        &object,
+        "address",
+        &JsValue::from_str(&format!("{:?}", log.address)),
+    )?;
+
+    let topics: Array = log
+        .topics
+        .iter()
+        .map(|topic: &H256| JsValue::from_str(&format!("{:?}", topic)))
+        .collect();
+    set_field(&object, "topics", &topics.into())?;
+
+    set_field(
+        &object,
+        "data",
+        &Uint8Array::from(log.data.as_slice()).into(),
+    )?;
+
+    Ok(object.into())
+}
+
+fn storage_to_js(storage: &HashMap<U256, U256>) -> Map {
+    let map = Map::new();
+
+    for (key, value) in storage {
+        map.set(&u256_to_js(key), &u256_to_js(value));
+    }
+
+    map
+}
+
+fn set_field(object: &Object, name: &str, value: &JsValue) -> Result<(), JsError> {
+    Reflect::set(object, &JsValue::from_str(name), value)
+        .map(|_| ())
+        .map_err(|_| JsError::new(&format!("Couldn't set {}", name)))
+}
+
+fn parse_hex(hex_str: &str) -> Option<Vec<u8>> {
+    let hex_str = hex_str.strip_prefix("0x").unwrap_or(hex_str);
+
+    hex::decode(hex_str).ok()
+}
+
+fn parse_u256_hex(hex_str: &str) -> Option<U256> {
+    if hex_str.is_empty() || hex_str.len() > 64 {
+        return None;
+    }
+
+    // Odd-length strings are padded, as BigInt.prototype.toString doesn't add leading zeros
+    let padded = format!("{:0>64}", hex_str);
+    let bytes = hex::decode(padded).ok()?;
+
+    Some(U256::from_big_endian(&bytes))
+}
@@ -0,0 +1,1002 @@
+use crate::keccak::keccak256;
+use crate::log::Log;
+use ethereum_types::{Address, H256, U256};
+use serde_json::Value;
+use std::fmt::{Display, Formatter};
+
+// Solidity ABI encoding and decoding, as specified in
+// https://docs.soliditylang.org/en/latest/abi-spec.html
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum AbiError {
+    InvalidType,
+    InvalidSignature,
+    InvalidJson,
+    // The values don't match the types they are encoded as
+    InvalidArguments,
+    InputTooShort,
+    InvalidOffset,
+    // A value isn't padded as its type requires, like a bool that isn't 0 or 1
+    InvalidValue,
+    // The topics of a log don't match the event
+    InvalidLog,
+}
+
+impl Display for AbiError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for AbiError {}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ParamType {
+    Address,
+    Bool,
+    // The size in bits
+    Uint(usize),
+    Int(usize),
+    // The size in bytes
+    FixedBytes(usize),
+    Bytes,
+    String,
+    Array(Box<ParamType>),
+    FixedArray(Box<ParamType>, usize),
+    Tuple(Vec<ParamType>),
+}
+
+impl ParamType {
+    // Parses a Solidity type, like "uint256", "bytes32[]" or "(address,uint256)[2]"
+    pub fn parse(type_name: &str) -> Result<ParamType, AbiError> {
+        let type_name = type_name.trim();
+
+        if let Some(element) = type_name.strip_suffix(']') {
+            let open = element.rfind('[').ok_or(AbiError::InvalidType)?;
+            let inner = ParamType::parse(&element[..open])?;
+            let size = &element[open + 1..];
+
+            if size.is_empty() {
+                return Ok(ParamType::Array(Box::new(inner)));
+            }
+
+            let size = size.parse().map_err(|_| AbiError::InvalidType)?;
+            return Ok(ParamType::FixedArray(Box::new(inner), size));
+        }
+
+        let tuple = type_name.strip_prefix("tuple").unwrap_or(type_name);
+        if let Some(components) = tuple.strip_prefix('(') {
+            let components = components.strip_suffix(')').ok_or(AbiError::InvalidType)?;
+
+            return split_top_level(components)?
+                .into_iter()
+                .map(ParamType::parse)
+                .collect::<Result<_, _>>()
+                .map(ParamType::Tuple);
+        }
+
+        let param_type = match type_name {
+            "address" => ParamType::Address,
+            "bool" => ParamType::Bool,
+            "bytes" => ParamType::Bytes,
+            "string" => ParamType::String,
+            "uint" => ParamType::Uint(256),
+            "int" => ParamType::Int(256),
+            _ => {
+                if let Some(bits) = type_name.strip_prefix("uint") {
+                    ParamType::Uint(parse_bits(bits)?)
+                } else if let Some(bits) = type_name.strip_prefix("int") {
+                    ParamType::Int(parse_bits(bits)?)
+                } else if let Some(size) = type_name.strip_prefix("bytes") {
+                    match size.parse() {
+                        Ok(size) if (1..=32).contains(&size) => ParamType::FixedBytes(size),
+                        _ => return Err(AbiError::InvalidType),
+                    }
+                } else {
+                    return Err(AbiError::InvalidType);
+                }
+            }
+        };
+
+        Ok(param_type)
+    }
+
+    // Whether its encoding is stored out of place, after an offset
+    pub fn is_dynamic(&self) -> bool {
+        match self {
+            ParamType::Bytes | ParamType::String | ParamType::Array(_) => true,
+            ParamType::FixedArray(inner, _) => inner.is_dynamic(),
+            ParamType::Tuple(components) => components.iter().any(ParamType::is_dynamic),
+            _ => false,
+        }
+    }
+
+    // The size of its encoding in the head of a tuple
+    fn head_size(&self) -> usize {
+        match self {
+            _ if self.is_dynamic() => 32,
+            ParamType::FixedArray(inner, size) => inner.head_size() * size,
+            ParamType::Tuple(components) => components.iter().map(ParamType::head_size).sum(),
+            _ => 32,
+        }
+    }
+
+    fn matches(&self, value: &AbiValue) -> bool {
+        match (self, value) {
+            (ParamType::Address, AbiValue::Address(_))
+            | (ParamType::Bool, AbiValue::Bool(_))
+            | (ParamType::Bytes, AbiValue::Bytes(_))
+            | (ParamType::String, AbiValue::String(_)) => true,
+            (ParamType::Uint(bits), AbiValue::Uint(value)) => value.bits() <= *bits,
+            (ParamType::Int(bits), AbiValue::Int(value)) => fits_int(*value, *bits),
+            (ParamType::FixedBytes(size), AbiValue::FixedBytes(bytes)) => bytes.len() == *size,
+            (ParamType::Array(inner), AbiValue::Array(values)) => {
+                values.iter().all(|value| inner.matches(value))
+            }
+            (ParamType::FixedArray(inner, size), AbiValue::FixedArray(values)) => {
+                values.len() == *size && values.iter().all(|value| inner.matches(value))
+            }
+            (ParamType::Tuple(components), AbiValue::Tuple(values)) => {
+                components_match(components, values)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Display for ParamType {
+    // Writes its canonical name, as used in signatures
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParamType::Address => write!(f, "address"),
+            ParamType::Bool => write!(f, "bool"),
+            ParamType::Uint(bits) => write!(f, "uint{}", bits),
+            ParamType::Int(bits) => write!(f, "int{}", bits),
+            ParamType::FixedBytes(size) => write!(f, "bytes{}", size),
+            ParamType::Bytes => write!(f, "bytes"),
+            ParamType::String => write!(f, "string"),
+            ParamType::Array(inner) => write!(f, "{}[]", inner),
+            ParamType::FixedArray(inner, size) => write!(f, "{}[{}]", inner, size),
+            ParamType::Tuple(components) => write!(f, "({})", join(components)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum AbiValue {
+    Address(Address),
+    Bool(bool),
+    Uint(U256),
+    // In two's complement
+    Int(U256),
+    FixedBytes(Vec<u8>),
+    Bytes(Vec<u8>),
+    String(String),
+    Array(Vec<AbiValue>),
+    FixedArray(Vec<AbiValue>),
+    Tuple(Vec<AbiValue>),
+}
+
+impl AbiValue {
+    pub fn int(value: i128) -> AbiValue {
+        let magnitude = U256::from(value.unsigned_abs());
+
+        if value < 0 {
+            AbiValue::Int(negate(magnitude))
+        } else {
+            AbiValue::Int(magnitude)
+        }
+    }
+
+    pub fn as_address(&self) -> Option<Address> {
+        match self {
+            AbiValue::Address(address) => Some(*address),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            AbiValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    // Returns the value of a Uint or an Int, in two's complement
+    pub fn as_u256(&self) -> Option<U256> {
+        match self {
+            AbiValue::Uint(value) | AbiValue::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            AbiValue::FixedBytes(bytes) | AbiValue::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            AbiValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    // Returns the elements of an array or a tuple
+    pub fn as_slice(&self) -> Option<&[AbiValue]> {
+        match self {
+            AbiValue::Array(values) | AbiValue::FixedArray(values) | AbiValue::Tuple(values) => {
+                Some(values)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl From<Address> for AbiValue {
+    fn from(address: Address) -> Self {
+        AbiValue::Address(address)
+    }
+}
+
+impl From<bool> for AbiValue {
+    fn from(value: bool) -> Self {
+        AbiValue::Bool(value)
+    }
+}
+
+impl From<U256> for AbiValue {
+    fn from(value: U256) -> Self {
+        AbiValue::Uint(value)
+    }
+}
+
+impl From<u64> for AbiValue {
+    fn from(value: u64) -> Self {
+        AbiValue::Uint(U256::from(value))
+    }
+}
+
+impl From<&str> for AbiValue {
+    fn from(value: &str) -> Self {
+        AbiValue::String(value.to_string())
+    }
+}
+
+impl From<Vec<u8>> for AbiValue {
+    fn from(bytes: Vec<u8>) -> Self {
+        AbiValue::Bytes(bytes)
+    }
+}
+
+impl Display for AbiValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AbiValue::Address(address) => write!(f, "{:?}", address),
+            AbiValue::Bool(value) => write!(f, "{}", value),
+            AbiValue::Uint(value) => write!(f, "{}", value),
+            AbiValue::Int(value) if value.bit(255) => write!(f, "-{}", negate(*value)),
+            AbiValue::Int(value) => write!(f, "{}", value),
+            AbiValue::FixedBytes(bytes) | AbiValue::Bytes(bytes) => {
+                write!(f, "0x{}", hex::encode(bytes))
+            }
+            AbiValue::String(value) => write!(f, "{:?}", value),
+            AbiValue::Array(values) | AbiValue::FixedArray(values) => {
+                write!(f, "[{}]", join(values))
+            }
+            AbiValue::Tuple(values) => write!(f, "({})", join(values)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Param {
+    pub name: String,
+    pub kind: ParamType,
+    // Only used by event parameters
+    pub indexed: bool,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Function {
+    pub name: String,
+    pub inputs: Vec<Param>,
+    pub outputs: Vec<Param>,
+}
+
+impl Function {
+    // Parses a human-readable signature, like "transfer(address,uint256)" or
+    // "function balanceOf(address owner) view returns (uint256)"
+    pub fn parse(signature: &str) -> Result<Function, AbiError> {
+        let declaration = Declaration::parse(signature, "function")?;
+
+        Ok(Function {
+            name: declaration.name,
+            inputs: declaration.inputs,
+            outputs: declaration.outputs,
+        })
+    }
+
+    // The canonical signature, like "transfer(address,uint256)"
+    pub fn signature(&self) -> String {
+        signature(&self.name, &self.inputs)
+    }
+
+    pub fn selector(&self) -> [u8; 4] {
+        selector(&self.signature())
+    }
+
+    pub fn encode_input(&self, arguments: &[AbiValue]) -> Result<Vec<u8>, AbiError> {
+        let mut data = self.selector().to_vec();
+        data.extend(encode_params(&self.inputs, arguments)?);
+
+        Ok(data)
+    }
+
+    // Decodes calldata, which must start with the function's selector
+    pub fn decode_input(&self, data: &[u8]) -> Result<Vec<AbiValue>, AbiError> {
+        if data.len() < 4 {
+            return Err(AbiError::InputTooShort);
+        }
+
+        if data[..4] != self.selector() {
+            return Err(AbiError::InvalidSignature);
+        }
+
+        decode(&param_types(&self.inputs), &data[4..])
+    }
+
+    pub fn decode_output(&self, data: &[u8]) -> Result<Vec<AbiValue>, AbiError> {
+        decode(&param_types(&self.outputs), data)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Event {
+    pub name: String,
+    pub inputs: Vec<Param>,
+    pub anonymous: bool,
+}
+
+impl Event {
+    // Parses a human-readable signature, like
+    // "event Transfer(address indexed from, address indexed to, uint256 value)"
+    pub fn parse(signature: &str) -> Result<Event, AbiError> {
+        let declaration = Declaration::parse(signature, "event")?;
+
+        Ok(Event {
+            name: declaration.name,
+            inputs: declaration.inputs,
+            anonymous: declaration.anonymous,
+        })
+    }
+
+    pub fn signature(&self) -> String {
+        signature(&self.name, &self.inputs)
+    }
+
+    // The first topic of its logs, unless it's anonymous
+    pub fn topic(&self) -> H256 {
+        keccak256(self.signature().as_bytes())
+    }
+
+    // Decodes the inputs of the event, in order. Indexed inputs whose type isn't a value type are
+    // hashed in the topics, so they are returned as their bytes32 hash.
+    pub fn decode_log(&self, log: &Log) -> Result<Vec<AbiValue>, AbiError> {
+        let mut topics = log.topics.iter();
+
+        if !self.anonymous && topics.next() != Some(&self.topic()) {
+            return Err(AbiError::InvalidLog);
+        }
+
+        let data_types: Vec<ParamType> = self
+            .inputs
+            .iter()
+            .filter(|input| !input.indexed)
+            .map(|input| input.kind.clone())
+            .collect();
+        let mut data_values = decode(&data_types, &log.data)?.into_iter();
+
+        let mut values = Vec::with_capacity(self.inputs.len());
+
+        for input in &self.inputs {
+            let value = if input.indexed {
+                let topic = topics.next().ok_or(AbiError::InvalidLog)?;
+
+                match input.kind {
+                    ParamType::Bytes
+                    | ParamType::String
+                    | ParamType::Array(_)
+                    | ParamType::FixedArray(_, _)
+                    | ParamType::Tuple(_) => AbiValue::FixedBytes(topic.as_bytes().to_vec()),
+                    _ => decode_value(&input.kind, topic.as_bytes(), 0)?,
+                }
+            } else {
+                data_values.next().ok_or(AbiError::InvalidLog)?
+            };
+
+            values.push(value);
+        }
+
+        if topics.next().is_some() {
+            return Err(AbiError::InvalidLog);
+        }
+
+        Ok(values)
+    }
+}
+
+// A custom error, like "error Unauthorized(address caller)"
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CustomError {
+    pub name: String,
+    pub inputs: Vec<Param>,
+}
+
+impl CustomError {
+    pub fn parse(signature: &str) -> Result<CustomError, AbiError> {
+        let declaration = Declaration::parse(signature, "error")?;
+
+        Ok(CustomError {
+            name: declaration.name,
+            inputs: declaration.inputs,
+        })
+    }
+
+    pub fn signature(&self) -> String {
+        signature(&self.name, &self.inputs)
+    }
+
+    pub fn selector(&self) -> [u8; 4] {
+        selector(&self.signature())
+    }
+
+    // Decodes the arguments of the error, without its selector
+    pub fn decode_arguments(&self, data: &[u8]) -> Result<Vec<AbiValue>, AbiError> {
+        decode(&param_types(&self.inputs), data)
+    }
+}
+
+// The interface of a contract
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Abi {
+    pub functions: Vec<Function>,
+    pub events: Vec<Event>,
+    pub errors: Vec<CustomError>,
+}
+
+impl Abi {
+    // Parses a JSON ABI, as generated by solc. Constructors, fallbacks and receive functions are
+    // ignored.
+    pub fn from_json(json: &str) -> Result<Abi, AbiError> {
+        let items: Vec<Value> = serde_json::from_str(json).map_err(|_| AbiError::InvalidJson)?;
+        let mut abi = Abi::default();
+
+        for item in &items {
+            let name = || {
+                item["name"]
+                    .as_str()
+                    .map(str::to_string)
+                    .ok_or(AbiError::InvalidJson)
+            };
+
+            match item["type"].as_str().unwrap_or("function") {
+                "function" => abi.functions.push(Function {
+                    name: name()?,
+                    inputs: params_from_json(&item["inputs"])?,
+                    outputs: params_from_json(&item["outputs"])?,
+                }),
+                "event" => abi.events.push(Event {
+                    name: name()?,
+                    inputs: params_from_json(&item["inputs"])?,
+                    anonymous: item["anonymous"].as_bool().unwrap_or(false),
+                }),
+                "error" => abi.errors.push(CustomError {
+                    name: name()?,
+                    inputs: params_from_json(&item["inputs"])?,
+                }),
+                _ => {}
+            }
+        }
+
+        Ok(abi)
+    }
+
+    // Parses human-readable declarations, like "function transfer(address to, uint256 amount)",
+    // "event Transfer(address indexed from, address indexed to, uint256 value)" or
+    // "error Unauthorized()". Declarations without a keyword are parsed as functions.
+    pub fn parse(declarations: &[&str]) -> Result<Abi, AbiError> {
+        let mut abi = Abi::default();
+
+        for declaration in declarations {
+            let declaration = declaration.trim();
+
+            if declaration.starts_with("event ") {
+                abi.events.push(Event::parse(declaration)?);
+            } else if declaration.starts_with("error ") {
+                abi.errors.push(CustomError::parse(declaration)?);
+            } else {
+                abi.functions.push(Function::parse(declaration)?);
+            }
+        }
+
+        Ok(abi)
+    }
+
+    // Returns the first function with that name. Overloads can be found by their signature.
+    pub fn function(&self, name_or_signature: &str) -> Option<&Function> {
+        self.functions.iter().find(|function| {
+            function.name == name_or_signature || function.signature() == name_or_signature
+        })
+    }
+
+    pub fn event(&self, name_or_signature: &str) -> Option<&Event> {
+        self.events
+            .iter()
+            .find(|event| event.name == name_or_signature || event.signature() == name_or_signature)
+    }
+
+    pub fn error(&self, name_or_signature: &str) -> Option<&CustomError> {
+        self.errors
+            .iter()
+            .find(|error| error.name == name_or_signature || error.signature() == name_or_signature)
+    }
+}
+
+// Encodes the values as the components of a tuple, which is how arguments and return values are
+// encoded
+pub fn encode(values: &[AbiValue]) -> Vec<u8> {
+    let head_size: usize = values.iter().map(value_head_size).sum();
+
+    let mut head = Vec::with_capacity(head_size);
+    let mut tail = Vec::new();
+
+    for value in values {
+        if value_is_dynamic(value) {
+            head.extend_from_slice(&word(U256::from(head_size + tail.len())));
+            tail.extend(encode_value(value));
+        } else {
+            head.extend(encode_value(value));
+        }
+    }
+
+    head.extend(tail);
+    head
+}
+
+// Decodes a tuple with the given component types
+pub fn decode(types: &[ParamType], data: &[u8]) -> Result<Vec<AbiValue>, AbiError> {
+    decode_tuple(types, data, 0)
+}
+
+pub fn selector(signature: &str) -> [u8; 4] {
+    let mut selector = [0; 4];
+    selector.copy_from_slice(&keccak256(signature.as_bytes())[..4]);
+
+    selector
+}
+
+fn encode_params(params: &[Param], values: &[AbiValue]) -> Result<Vec<u8>, AbiError> {
+    if !components_match(&param_types(params), values) {
+        return Err(AbiError::InvalidArguments);
+    }
+
+    Ok(encode(values))
+}
+
+fn encode_value(value: &AbiValue) -> Vec<u8> {
+    match value {
+        AbiValue::Address(address) => {
+            let mut word = [0; 32];
+            word[12..].copy_from_slice(address.as_bytes());
+            word.to_vec()
+        }
+        AbiValue::Bool(value) => word(U256::from(*value as u8)).to_vec(),
+        AbiValue::Uint(value) | AbiValue::Int(value) => word(*value).to_vec(),
+        AbiValue::FixedBytes(bytes) => padded(bytes),
+        AbiValue::Bytes(bytes) => encode_bytes(bytes),
+        AbiValue::String(value) => encode_bytes(value.as_bytes()),
+        AbiValue::Array(values) => {
+            let mut data = word(U256::from(values.len())).to_vec();
+            data.extend(encode(values));
+            data
+        }
+        AbiValue::FixedArray(values) | AbiValue::Tuple(values) => encode(values),
+    }
+}
+
+fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+    let mut data = word(U256::from(bytes.len())).to_vec();
+    data.extend(padded(bytes));
+    data
+}
+
+// Values are encoded without checking their types, so their dynamism is taken from the values
+fn value_is_dynamic(value: &AbiValue) -> bool {
+    match value {
+        AbiValue::Bytes(_) | AbiValue::String(_) | AbiValue::Array(_) => true,
+        AbiValue::FixedArray(values) | AbiValue::Tuple(values) => {
+            values.iter().any(value_is_dynamic)
+        }
+        _ => false,
+    }
+}
+
+fn value_head_size(value: &AbiValue) -> usize {
+    match value {
+        _ if value_is_dynamic(value) => 32,
+        AbiValue::FixedArray(values) | AbiValue::Tuple(values) => {
+            values.iter().map(value_head_size).sum()
+        }
+        _ => 32,
+    }
+}
+
+// Decodes a tuple encoded at data[base..]. Offsets are relative to base.
+fn decode_tuple(types: &[ParamType], data: &[u8], base: usize) -> Result<Vec<AbiValue>, AbiError> {
+    let mut values = Vec::with_capacity(types.len());
+    let mut position = base;
+
+    for param_type in types {
+        let value = if param_type.is_dynamic() {
+            let offset = read_usize(data, position)?;
+            let start = base.checked_add(offset).ok_or(AbiError::InvalidOffset)?;
+
+            if start > data.len() {
+                return Err(AbiError::InvalidOffset);
+            }
+
+            decode_value(param_type, data, start)?
+        } else {
+            decode_value(param_type, data, position)?
+        };
+
+        values.push(value);
+        position += param_type.head_size();
+    }
+
+    Ok(values)
+}
+
+fn decode_value(
+    param_type: &ParamType,
+    data: &[u8],
+    position: usize,
+) -> Result<AbiValue, AbiError> {
+    let value = match param_type {
+        ParamType::Address => {
+            let word = read_word(data, position)?;
+            if word[..12].iter().any(|byte| *byte != 0) {
+                return Err(AbiError::InvalidValue);
+            }
+
+            AbiValue::Address(Address::from_slice(&word[12..]))
+        }
+        ParamType::Bool => match U256::from_big_endian(read_word(data, position)?) {
+            value if value.is_zero() => AbiValue::Bool(false),
+            value if value == U256::one() => AbiValue::Bool(true),
+            _ => return Err(AbiError::InvalidValue),
+        },
+        ParamType::Uint(bits) => {
+            let value = U256::from_big_endian(read_word(data, position)?);
+            if value.bits() > *bits {
+                return Err(AbiError::InvalidValue);
+            }
+
+            AbiValue::Uint(value)
+        }
+        ParamType::Int(bits) => {
+            let value = U256::from_big_endian(read_word(data, position)?);
+            if !fits_int(value, *bits) {
+                return Err(AbiError::InvalidValue);
+            }
+
+            AbiValue::Int(value)
+        }
+        ParamType::FixedBytes(size) => {
+            let word = read_word(data, position)?;
+            if word[*size..].iter().any(|byte| *byte != 0) {
+                return Err(AbiError::InvalidValue);
+            }
+
+            AbiValue::FixedBytes(word[..*size].to_vec())
+        }
+        ParamType::Bytes => AbiValue::Bytes(read_bytes(data, position)?.to_vec()),
+        ParamType::String => {
+            let bytes = read_bytes(data, position)?;
+            let value = String::from_utf8(bytes.to_vec()).map_err(|_| AbiError::InvalidValue)?;
+
+            AbiValue::String(value)
+        }
+        ParamType::Array(inner) => {
+            let length = read_usize(data, position)?;
+            let start = position + 32;
+
+            // Checking that the heads fit in the data avoids allocating huge vectors
+            let heads_size = length
+                .checked_mul(inner.head_size().max(32))
+                .ok_or(AbiError::InputTooShort)?;
+            if start.saturating_add(heads_size) > data.len() {
+                return Err(AbiError::InputTooShort);
+            }
+
+            let types = vec![(**inner).clone(); length];
+            AbiValue::Array(decode_tuple(&types, data, start)?)
+        }
+        ParamType::FixedArray(inner, size) => {
+            let types = vec![(**inner).clone(); *size];
+            AbiValue::FixedArray(decode_tuple(&types, data, position)?)
+        }
+        ParamType::Tuple(components) => AbiValue::Tuple(decode_tuple(components, data, position)?),
+    };
+
+    Ok(value)
+}
+
+fn read_word(data: &[u8], position: usize) -> Result<&[u8], AbiError> {
+    let end = position.checked_add(32).ok_or(AbiError::InputTooShort)?;
+    data.get(position..end).ok_or(AbiError::InputTooShort)
+}
+
+fn read_usize(data: &[u8], position: usize) -> Result<usize, AbiError> {
+    let value = U256::from_big_endian(read_word(data, position)?);
+    if value > U256::from(usize::MAX) {
+        return Err(AbiError::InvalidOffset);
+    }
+
+    Ok(value.as_usize())
+}
+
+// Reads a length-prefixed byte array
+fn read_bytes(data: &[u8], position: usize) -> Result<&[u8], AbiError> {
+    let length = read_usize(data, position)?;
+    let start = position + 32;
+    let end = start.checked_add(length).ok_or(AbiError::InputTooShort)?;
+
+    data.get(start..end).ok_or(AbiError::InputTooShort)
+}
+
+fn word(value: U256) -> [u8; 32] {
+    let mut word = [0; 32];
+    value.to_big_endian(&mut word);
+    word
+}
+
+// Right-pads the bytes with zeros to a multiple of 32 bytes
+fn padded(bytes: &[u8]) -> Vec<u8> {
+    let mut data = bytes.to_vec();
+    data.resize(bytes.len().div_ceil(32) * 32, 0);
+    data
+}
+
+fn negate(value: U256) -> U256 {
+    (!value).overflowing_add(U256::one()).0
+}
+
+// Whether a two's complement value is sign-extended from the given size
+fn fits_int(value: U256, bits: usize) -> bool {
+    if bits == 256 {
+        return true;
+    }
+
+    let high_bits = value >> (bits - 1);
+    high_bits.is_zero() || high_bits == U256::MAX >> (bits - 1)
+}
+
+fn components_match(types: &[ParamType], values: &[AbiValue]) -> bool {
+    types.len() == values.len()
+        && types
+            .iter()
+            .zip(values)
+            .all(|(param_type, value)| param_type.matches(value))
+}
+
+fn param_types(params: &[Param]) -> Vec<ParamType> {
+    params.iter().map(|param| param.kind.clone()).collect()
+}
+
+fn signature(name: &str, params: &[Param]) -> String {
+    format!("{}({})", name, join(&param_types(params)))
+}
+
+fn join<T: Display>(items: &[T]) -> String {
+    items
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn parse_bits(bits: &str) -> Result<usize, AbiError> {
+    match bits.parse() {
+        Ok(bits) if bits > 0 && bits <= 256 && bits % 8 == 0 => Ok(bits),
+        _ => Err(AbiError::InvalidType),
+    }
+}
+
+// Splits a comma-separated list, ignoring the commas inside parentheses
+fn split_top_level(list: &str) -> Result<Vec<&str>, AbiError> {
+    if list.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut items = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+
+    for (i, c) in list.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.checked_sub(1).ok_or(AbiError::InvalidSignature)?,
+            ',' if depth == 0 => {
+                items.push(list[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+
+    if depth != 0 {
+        return Err(AbiError::InvalidSignature);
+    }
+
+    items.push(list[start..].trim());
+    Ok(items)
+}
+
+// Returns the content of the parenthesized list at the start of text, and what follows it
+fn parenthesized(text: &str) -> Result<(&str, &str), AbiError> {
+    let text = text.trim_start();
+    if !text.starts_with('(') {
+        return Err(AbiError::InvalidSignature);
+    }
+
+    let mut depth = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok((&text[1..i], &text[i + 1..]));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Err(AbiError::InvalidSignature)
+}
+
+// A parsed human-readable function, event or error
+struct Declaration {
+    name: String,
+    inputs: Vec<Param>,
+    outputs: Vec<Param>,
+    anonymous: bool,
+}
+
+impl Declaration {
+    fn parse(text: &str, keyword: &str) -> Result<Declaration, AbiError> {
+        let text = text.trim();
+        let text = match text.strip_prefix(keyword) {
+            Some(rest) if rest.starts_with(char::is_whitespace) => rest.trim_start(),
+            _ => text,
+        };
+
+        let open = text.find('(').ok_or(AbiError::InvalidSignature)?;
+        let name = text[..open].trim();
+
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+        {
+            return Err(AbiError::InvalidSignature);
+        }
+
+        let (inputs, mut rest) = parenthesized(&text[open..])?;
+        let inputs = parse_params(inputs)?;
+
+        let mut outputs = Vec::new();
+        let mut anonymous = false;
+
+        // Modifiers, like view or payable, are ignored
+        loop {
+            rest = rest.trim_start();
+
+            if rest.is_empty() {
+                break;
+            }
+
+            if let Some(returns) = rest.strip_prefix("returns") {
+                let (list, after) = parenthesized(returns)?;
+                outputs = parse_params(list)?;
+                rest = after;
+                continue;
+            }
+
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            if &rest[..end] == "anonymous" {
+                anonymous = true;
+            }
+
+            rest = &rest[end..];
+        }
+
+        Ok(Declaration {
+            name: name.to_string(),
+            inputs,
+            outputs,
+            anonymous,
+        })
+    }
+}
+
+// Parses parameters like "address indexed from" or "(uint256,bytes) memory data"
+fn parse_params(list: &str) -> Result<Vec<Param>, AbiError> {
+    split_top_level(list)?
+        .into_iter()
+        .map(|param| {
+            // The type may be a tuple, so the name is whatever follows its last parenthesis
+            let type_start = param.rfind(')').unwrap_or(0);
+            let type_end = param[type_start..]
+                .find(char::is_whitespace)
+                .map_or(param.len(), |i| type_start + i);
+
+            let kind = ParamType::parse(&param[..type_end])?;
+            let mut name = String::new();
+            let mut indexed = false;
+
+            for word in param[type_end..].split_whitespace() {
+                match word {
+                    "indexed" => indexed = true,
+                    "memory" | "calldata" | "storage" => {}
+                    _ => name = word.to_string(),
+                }
+            }
+
+            Ok(Param {
+                name,
+                kind,
+                indexed,
+            })
+        })
+        .collect()
+}
+
+fn params_from_json(params: &Value) -> Result<Vec<Param>, AbiError> {
+    let params = match params {
+        Value::Null => return Ok(Vec::new()),
+        Value::Array(params) => params,
+        _ => return Err(AbiError::InvalidJson),
+    };
+
+    params
+        .iter()
+        .map(|param| {
+            Ok(Param {
+                name: param["name"].as_str().unwrap_or("").to_string(),
+                kind: param_type_from_json(param)?,
+                indexed: param["indexed"].as_bool().unwrap_or(false),
+            })
+        })
+        .collect()
+}
+
+// Tuples are declared as "tuple", "tuple[]", etc, with their types in the components field
+fn param_type_from_json(param: &Value) -> Result<ParamType, AbiError> {
+    let type_name = param["type"].as_str().ok_or(AbiError::InvalidJson)?;
+
+    match type_name.strip_prefix("tuple") {
+        Some(suffix) => {
+            let components = params_from_json(&param["components"])?;
+            let tuple = ParamType::Tuple(param_types(&components));
+
+            ParamType::parse(&format!("{}{}", tuple, suffix))
+        }
+        None => ParamType::parse(type_name),
+    }
+}
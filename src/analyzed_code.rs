@@ -0,0 +1,117 @@
+use crate::basic_blocks::{analyze_basic_blocks, BasicBlock};
+use crate::bytecode::Bytecode;
+use crate::jumpdests::JumpdestMap;
+use crate::keccak::keccak256;
+use ethereum_types::H256;
+use lru::LruCache;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex, OnceLock};
+
+// Enough for the contracts used by a typical session
+const DEFAULT_CACHE_CAPACITY: usize = 1024;
+
+// The result of analyzing some code once, so that it can be executed many times without redoing
+// the work.
+#[derive(Debug)]
+pub struct AnalyzedCode {
+    code: Vec<u8>,
+    code_hash: H256,
+    jumpdests: Arc<JumpdestMap>,
+    // Only run_analyzed uses the basic blocks, so they are analyzed when they are first requested
+    basic_blocks: OnceLock<Vec<BasicBlock>>,
+}
+
+impl AnalyzedCode {
+    pub fn new(code: &[u8]) -> AnalyzedCode {
+        AnalyzedCode {
+            code: code.to_vec(),
+            code_hash: keccak256(code),
+            jumpdests: Arc::new(JumpdestMap::analyze(code)),
+            basic_blocks: OnceLock::new(),
+        }
+    }
+
+    pub fn code(&self) -> &[u8] {
+        &self.code
+    }
+
+    pub fn code_hash(&self) -> H256 {
+        self.code_hash
+    }
+
+    pub fn jumpdests(&self) -> &Arc<JumpdestMap> {
+        &self.jumpdests
+    }
+
+    pub fn basic_blocks(&self) -> &[BasicBlock] {
+        self.basic_blocks
+            .get_or_init(|| analyze_basic_blocks(self.code()))
+    }
+
+    // Returns the block that starts at pc. Blocks start at every JUMPDEST, and right after every
+    // jump, so this finds the block that is entered after a jump or after falling through.
+    pub fn basic_block_at(&self, pc: usize) -> Option<&BasicBlock> {
+        let blocks = self.basic_blocks();
+
+        blocks
+            .binary_search_by_key(&pc, |block| block.start)
+            .ok()
+            .map(|index| &blocks[index])
+    }
+
+    pub fn bytecode(&self) -> Bytecode<'_> {
+        Bytecode::with_jumpdests(self.code(), Arc::clone(&self.jumpdests))
+    }
+}
+
+// A thread-safe LRU cache of analyzed code, keyed by code hash.
+#[derive(Debug)]
+pub struct CodeCache {
+    entries: Mutex<LruCache<H256, Arc<AnalyzedCode>>>,
+}
+
+impl CodeCache {
+    pub fn new(capacity: NonZeroUsize) -> CodeCache {
+        CodeCache {
+            entries: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    pub fn get(&self, code_hash: &H256) -> Option<Arc<AnalyzedCode>> {
+        self.entries.lock().unwrap().get(code_hash).cloned()
+    }
+
+    // Returns the cached analysis of the code, analyzing and caching it if it isn't there
+    pub fn get_or_analyze(&self, code: &[u8]) -> Arc<AnalyzedCode> {
+        let code_hash = keccak256(code);
+
+        if let Some(analyzed) = self.get(&code_hash) {
+            return analyzed;
+        }
+
+        // The lock isn't held while analyzing, so other threads may analyze the same code
+        // concurrently. Both results are equivalent, so it doesn't matter which one is kept.
+        let analyzed = Arc::new(AnalyzedCode::new(code));
+
+        self.entries
+            .lock()
+            .unwrap()
+            .put(code_hash, Arc::clone(&analyzed));
+
+        analyzed
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for CodeCache {
+    fn default() -> Self {
+        CodeCache::new(NonZeroUsize::new(DEFAULT_CACHE_CAPACITY).unwrap())
+    }
+}
@@ -0,0 +1,267 @@
+use ethereum_types::U256;
+
+// 256-bit modular arithmetic implemented directly over the little-endian u64 limbs of U256. These
+// avoid the generic code paths of ethereum_types, and the widening to U512 of ADDMOD and MULMOD,
+// which are the bottleneck of arithmetic-heavy code.
+
+type Limbs = [u64; 4];
+
+pub fn add(a: U256, b: U256) -> U256 {
+    let mut result = [0; 4];
+    let mut carry = false;
+
+    for (i, limb) in result.iter_mut().enumerate() {
+        let (sum, overflow1) = a.0[i].overflowing_add(b.0[i]);
+        let (sum, overflow2) = sum.overflowing_add(carry as u64);
+
+        *limb = sum;
+        carry = overflow1 || overflow2;
+    }
+
+    U256(result)
+}
+
+pub fn sub(a: U256, b: U256) -> U256 {
+    let mut result = [0; 4];
+    let mut borrow = false;
+
+    for (i, limb) in result.iter_mut().enumerate() {
+        let (difference, underflow1) = a.0[i].overflowing_sub(b.0[i]);
+        let (difference, underflow2) = difference.overflowing_sub(borrow as u64);
+
+        *limb = difference;
+        borrow = underflow1 || underflow2;
+    }
+
+    U256(result)
+}
+
+// Returns a % modulus, or zero if the modulus is zero, as MOD does
+pub fn modulo(a: U256, modulus: U256) -> U256 {
+    if modulus.is_zero() {
+        return U256::zero();
+    }
+
+    U256(rem(&a.0, &modulus.0))
+}
+
+pub fn addmod(a: U256, b: U256, modulus: U256) -> U256 {
+    if modulus.is_zero() {
+        return U256::zero();
+    }
+
+    let mut sum = [0; 5];
+    let mut carry = false;
+
+    for (i, limb) in sum.iter_mut().take(4).enumerate() {
+        let (value, overflow1) = a.0[i].overflowing_add(b.0[i]);
+        let (value, overflow2) = value.overflowing_add(carry as u64);
+
+        *limb = value;
+        carry = overflow1 || overflow2;
+    }
+
+    sum[4] = carry as u64;
+
+    U256(rem(&sum, &modulus.0))
+}
+
+pub fn mulmod(a: U256, b: U256, modulus: U256) -> U256 {
+    if modulus.is_zero() {
+        return U256::zero();
+    }
+
+    U256(rem(&full_mul(&a.0, &b.0), &modulus.0))
+}
+
+// Returns base ** exponent mod 2**256, using square-and-multiply
+pub fn exp(base: U256, exponent: U256) -> U256 {
+    let mut result = [1, 0, 0, 0];
+    let mut base = base.0;
+
+    let bits = exponent.bits();
+
+    for bit in 0..bits {
+        if exponent.bit(bit) {
+            result = wrapping_mul(&result, &base);
+        }
+
+        // The last square isn't used, so we skip it
+        if bit + 1 < bits {
+            base = wrapping_mul(&base, &base);
+        }
+    }
+
+    U256(result)
+}
+
+fn wrapping_mul(a: &Limbs, b: &Limbs) -> Limbs {
+    let mut result = [0; 4];
+
+    for i in 0..4 {
+        if a[i] == 0 {
+            continue;
+        }
+
+        let mut carry = 0u128;
+
+        for j in 0..4 - i {
+            let product = a[i] as u128 * b[j] as u128 + result[i + j] as u128 + carry;
+
+            result[i + j] = product as u64;
+            carry = product >> 64;
+        }
+    }
+
+    result
+}
+
+fn full_mul(a: &Limbs, b: &Limbs) -> [u64; 8] {
+    let mut result = [0; 8];
+
+    for i in 0..4 {
+        if a[i] == 0 {
+            continue;
+        }
+
+        let mut carry = 0u128;
+
+        for j in 0..4 {
+            let product = a[i] as u128 * b[j] as u128 + result[i + j] as u128 + carry;
+
+            result[i + j] = product as u64;
+            carry = product >> 64;
+        }
+
+        result[i + 4] = carry as u64;
+    }
+
+    result
+}
+
+fn significant_limbs(limbs: &[u64]) -> usize {
+    limbs
+        .iter()
+        .rposition(|limb| *limb != 0)
+        .map_or(0, |i| i + 1)
+}
+
+// Returns numerator % divisor, using Knuth's algorithm D (TAOCP vol. 2, 4.3.1). The divisor must
+// not be zero.
+fn rem(numerator: &[u64], divisor: &Limbs) -> Limbs {
+    let n = significant_limbs(divisor);
+    let m = significant_limbs(numerator);
+
+    if m < n {
+        let mut result = [0; 4];
+        result[..m].copy_from_slice(&numerator[..m]);
+        return result;
+    }
+
+    if n == 1 {
+        let divisor = divisor[0] as u128;
+        let remainder = numerator[..m].iter().rev().fold(0u128, |remainder, limb| {
+            ((remainder << 64) | *limb as u128) % divisor
+        });
+
+        return [remainder as u64, 0, 0, 0];
+    }
+
+    // Normalize so that the divisor's most significant bit is set, which makes the quotient
+    // estimates off by at most 2
+    let shift = divisor[n - 1].leading_zeros();
+
+    let mut v = [0u64; 4];
+    shift_left(&divisor[..n], shift, &mut v[..n]);
+
+    let mut u = [0u64; 9];
+    u[m] = shift_left(&numerator[..m], shift, &mut u[..m]);
+
+    let base = 1u128 << 64;
+    let top = v[n - 1] as u128;
+    let second = v[n - 2] as u128;
+
+    for j in (0..=m - n).rev() {
+        let dividend = ((u[j + n] as u128) << 64) | u[j + n - 1] as u128;
+
+        let mut quotient = dividend / top;
+        let mut remainder = dividend % top;
+
+        while quotient >= base || quotient * second > ((remainder << 64) | u[j + n - 2] as u128) {
+            quotient -= 1;
+            remainder += top;
+
+            if remainder >= base {
+                break;
+            }
+        }
+
+        // Subtract quotient * v from the current window of u
+        let mut carry = 0u128;
+        let mut borrow = false;
+
+        for i in 0..n {
+            let product = quotient * v[i] as u128 + carry;
+            carry = product >> 64;
+
+            let (difference, underflow1) = u[i + j].overflowing_sub(product as u64);
+            let (difference, underflow2) = difference.overflowing_sub(borrow as u64);
+
+            u[i + j] = difference;
+            borrow = underflow1 || underflow2;
+        }
+
+        let (difference, underflow1) = u[j + n].overflowing_sub(carry as u64);
+        let (difference, underflow2) = difference.overflowing_sub(borrow as u64);
+        u[j + n] = difference;
+
+        // The estimate was one too big, so we add the divisor back
+        if underflow1 || underflow2 {
+            let mut carry = false;
+
+            for i in 0..n {
+                let (sum, overflow1) = u[i + j].overflowing_add(v[i]);
+                let (sum, overflow2) = sum.overflowing_add(carry as u64);
+
+                u[i + j] = sum;
+                carry = overflow1 || overflow2;
+            }
+
+            u[j + n] = u[j + n].wrapping_add(carry as u64);
+        }
+    }
+
+    let mut result = [0; 4];
+    shift_right(&u[..n], shift, &mut result[..n]);
+
+    result
+}
+
+// Writes limbs << shift into out, and returns the bits shifted out of the most significant limb
+fn shift_left(limbs: &[u64], shift: u32, out: &mut [u64]) -> u64 {
+    if shift == 0 {
+        out.copy_from_slice(limbs);
+        return 0;
+    }
+
+    let mut carry = 0;
+
+    for (i, limb) in limbs.iter().enumerate() {
+        out[i] = (limb << shift) | carry;
+        carry = limb >> (64 - shift);
+    }
+
+    carry
+}
+
+fn shift_right(limbs: &[u64], shift: u32, out: &mut [u64]) {
+    if shift == 0 {
+        out.copy_from_slice(limbs);
+        return;
+    }
+
+    for i in 0..limbs.len() {
+        let next = limbs.get(i + 1).copied().unwrap_or(0);
+        out[i] = (limbs[i] >> shift) | (next << (64 - shift));
+    }
+}
@@ -0,0 +1,114 @@
+use crate::bytecode::BytecodeIterator;
+use crate::opcodes::Opcode;
+
+// A sequence of instructions that is always executed from its start to its end, unless an error
+// occurs in the middle of it.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BasicBlock {
+    pub start: usize,
+    // The pc right after the block's last instruction
+    pub end: usize,
+    pub static_gas: u64,
+    // The stack height needed to execute the whole block without underflowing
+    pub stack_required: usize,
+    // How much the stack grows over its initial height while executing the block
+    pub stack_max_growth: usize,
+}
+
+impl BasicBlock {
+    // Returns true if the block can be run with this initial stack height without underflowing or
+    // overflowing. If it can't, the instructions should be run one by one, so that the error is
+    // raised by the right instruction.
+    pub fn fits_stack(&self, stack_height: usize, stack_limit: usize) -> bool {
+        stack_height >= self.stack_required && stack_height + self.stack_max_growth <= stack_limit
+    }
+}
+
+// Splits the code into basic blocks. A block starts at every JUMPDEST and after every instruction
+// that jumps or halts.
+pub fn analyze_basic_blocks(code: &[u8]) -> Vec<BasicBlock> {
+    let mut blocks = Vec::new();
+    let mut current: Option<BasicBlockBuilder> = None;
+
+    for instruction in BytecodeIterator::new(code) {
+        if instruction.opcode == Opcode::JUMPDEST {
+            if let Some(builder) = current.take() {
+                blocks.push(builder.build(instruction.pc));
+            }
+        }
+
+        let builder = current.get_or_insert_with(|| BasicBlockBuilder::new(instruction.pc));
+        builder.add(instruction.opcode);
+
+        if ends_block(instruction.opcode) {
+            blocks.push(current.take().unwrap().build(instruction.pc + 1));
+        }
+    }
+
+    if let Some(builder) = current {
+        blocks.push(builder.build(code.len()));
+    }
+
+    blocks
+}
+
+struct BasicBlockBuilder {
+    start: usize,
+    static_gas: u64,
+    stack_height: isize,
+    stack_required: usize,
+    stack_max_growth: usize,
+}
+
+impl BasicBlockBuilder {
+    fn new(start: usize) -> BasicBlockBuilder {
+        BasicBlockBuilder {
+            start,
+            static_gas: 0,
+            stack_height: 0,
+            stack_required: 0,
+            stack_max_growth: 0,
+        }
+    }
+
+    fn add(&mut self, opcode: Opcode) {
+        let (inputs, outputs) = opcode.stack_io();
+
+        self.static_gas += opcode.static_gas();
+
+        let required = inputs as isize - self.stack_height;
+        if required > 0 {
+            self.stack_required = std::cmp::max(self.stack_required, required as usize);
+        }
+
+        self.stack_height += outputs as isize - inputs as isize;
+
+        if self.stack_height > 0 {
+            self.stack_max_growth =
+                std::cmp::max(self.stack_max_growth, self.stack_height as usize);
+        }
+    }
+
+    fn build(self, end: usize) -> BasicBlock {
+        BasicBlock {
+            start: self.start,
+            end,
+            static_gas: self.static_gas,
+            stack_required: self.stack_required,
+            stack_max_growth: self.stack_max_growth,
+        }
+    }
+}
+
+fn ends_block(opcode: Opcode) -> bool {
+    matches!(
+        opcode,
+        Opcode::JUMP
+            | Opcode::JUMPI
+            | Opcode::STOP
+            | Opcode::RETURN
+            | Opcode::REVERT
+            | Opcode::INVALID
+            | Opcode::SELFDESTRUCT
+    )
+}
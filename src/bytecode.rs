@@ -1,26 +1,40 @@
 use std::convert::TryFrom;
 use std::iter;
+use std::sync::Arc;
 
+use crate::jumpdests::JumpdestMap;
+use crate::keccak::keccak256;
 use crate::opcodes::Opcode;
-use ethereum_types::U256;
+use ethereum_types::{H256, U256};
 
 #[derive(Debug)]
 pub struct Bytecode<'data> {
     data: &'data [u8],
-    jumpdests: Vec<usize>,
+    jumpdests: Arc<JumpdestMap>,
 }
 
 impl<'data> Bytecode<'data> {
     pub fn new(data: &'data [u8]) -> Bytecode<'data> {
         Bytecode {
             data,
-            jumpdests: BytecodeIterator::new(data)
-                .filter(|inst| inst.opcode == Opcode::JUMPDEST)
-                .map(|inst| inst.pc)
-                .collect(),
+            jumpdests: Arc::new(JumpdestMap::analyze(data)),
         }
     }
 
+    // Creates a Bytecode reusing a previous analysis of the same code, which can be cached by its
+    // code hash.
+    pub fn with_jumpdests(data: &'data [u8], jumpdests: Arc<JumpdestMap>) -> Bytecode<'data> {
+        Bytecode { data, jumpdests }
+    }
+
+    pub fn jumpdests(&self) -> &Arc<JumpdestMap> {
+        &self.jumpdests
+    }
+
+    pub fn code_hash(&self) -> H256 {
+        keccak256(self.data)
+    }
+
     pub fn iter(&self) -> BytecodeIterator<'data> {
         BytecodeIterator::new(self.data)
     }
@@ -50,11 +64,7 @@ impl<'data> Bytecode<'data> {
     }
 
     pub fn is_jumpdest(&self, pc: usize) -> bool {
-        if pc >= self.size() {
-            return false;
-        }
-
-        self.jumpdests.contains(&pc)
+        self.jumpdests.contains(pc)
     }
 }
 
@@ -70,7 +80,7 @@ pub struct BytecodeIterator<'data> {
 }
 
 impl<'data> BytecodeIterator<'data> {
-    fn new(data: &'data [u8]) -> BytecodeIterator<'data> {
+    pub fn new(data: &'data [u8]) -> BytecodeIterator<'data> {
         BytecodeIterator { data, next_byte: 0 }
     }
 }
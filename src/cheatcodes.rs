@@ -0,0 +1,154 @@
+use crate::abi::{AbiValue, Function};
+use crate::evm::ExecutionResult;
+use crate::log::Log;
+use crate::revert_reason::RevertReason;
+use crate::session::EvmError;
+use ethereum_types::{Address, H160, U256};
+
+// The address of Foundry's cheatcode contract, the last 20 bytes of keccak256("hevm cheat code")
+pub const CHEATCODE_ADDRESS: Address = H160([
+    0x71, 0x09, 0x70, 0x9e, 0xcf, 0xa9, 0x1a, 0x80, 0x62, 0x6f, 0xf3, 0x98, 0x9d, 0x68, 0xf6, 0x7f,
+    0x5b, 0x1d, 0xd1, 0x2d,
+]);
+
+const SIGNATURES: [&str; 11] = [
+    "prank(address)",
+    "startPrank(address)",
+    "stopPrank()",
+    "warp(uint256)",
+    "roll(uint256)",
+    "deal(address,uint256)",
+    "store(address,bytes32,bytes32)",
+    "load(address,bytes32)",
+    "expectRevert()",
+    "expectRevert(bytes)",
+    "expectRevert(bytes4)",
+];
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) enum Cheatcode {
+    Prank(Address),
+    StartPrank(Address),
+    StopPrank,
+    Warp(U256),
+    Roll(U256),
+    Deal(Address, U256),
+    Store(Address, U256, U256),
+    Load(Address, U256),
+    ExpectRevert(Option<Vec<u8>>),
+}
+
+impl Cheatcode {
+    // Returns None if the calldata isn't a supported cheatcode call
+    pub(crate) fn decode(calldata: &[u8]) -> Option<Cheatcode> {
+        let function = SIGNATURES
+            .iter()
+            .map(|signature| Function::parse(signature).expect("Invalid cheatcode signature"))
+            .find(|function| calldata.starts_with(&function.selector()))?;
+
+        let arguments = function.decode_input(calldata).ok()?;
+
+        let cheatcode = match (function.name.as_str(), arguments.as_slice()) {
+            ("prank", [AbiValue::Address(sender)]) => Cheatcode::Prank(*sender),
+            ("startPrank", [AbiValue::Address(sender)]) => Cheatcode::StartPrank(*sender),
+            ("stopPrank", []) => Cheatcode::StopPrank,
+            ("warp", [AbiValue::Uint(timestamp)]) => Cheatcode::Warp(*timestamp),
+            ("roll", [AbiValue::Uint(number)]) => Cheatcode::Roll(*number),
+            ("deal", [AbiValue::Address(address), AbiValue::Uint(balance)]) => {
+                Cheatcode::Deal(*address, *balance)
+            }
+            (
+                "store",
+                [AbiValue::Address(address), AbiValue::FixedBytes(key), AbiValue::FixedBytes(value)],
+            ) => Cheatcode::Store(
+                *address,
+                U256::from_big_endian(key),
+                U256::from_big_endian(value),
+            ),
+            ("load", [AbiValue::Address(address), AbiValue::FixedBytes(key)]) => {
+                Cheatcode::Load(*address, U256::from_big_endian(key))
+            }
+            ("expectRevert", []) => Cheatcode::ExpectRevert(None),
+            ("expectRevert", [AbiValue::Bytes(data)])
+            | ("expectRevert", [AbiValue::FixedBytes(data)]) => {
+                Cheatcode::ExpectRevert(Some(data.clone()))
+            }
+            _ => return None,
+        };
+
+        Some(cheatcode)
+    }
+}
+
+// What the cheatcodes expect from the next transaction or deployment of an Evm
+#[derive(Debug, Default)]
+pub(crate) struct Cheatcodes {
+    pub(crate) prank: Option<Prank>,
+    // The revert data, or None to accept any revert
+    pub(crate) expected_revert: Option<Option<Vec<u8>>>,
+    pub(crate) expected_logs: Vec<Log>,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Prank {
+    pub(crate) sender: Address,
+    // Whether it lasts until stopPrank, instead of a single transaction
+    pub(crate) persistent: bool,
+}
+
+impl Cheatcodes {
+    // Returns the sender of the next transaction, if it's being pranked
+    pub(crate) fn pranked_sender(&self) -> Option<Address> {
+        self.prank.map(|prank| prank.sender)
+    }
+
+    // Called once the pranked transaction passes its checks, so that rejected transactions can be
+    // retried with the same prank
+    pub(crate) fn use_prank(&mut self) {
+        if self.prank.is_some_and(|prank| !prank.persistent) {
+            self.prank = None;
+        }
+    }
+
+    // Checks the result against the expectations, which only apply to a single transaction
+    pub(crate) fn check_expectations(&mut self, result: &ExecutionResult) -> Result<(), EvmError> {
+        if let Some(expected) = self.expected_revert.take() {
+            if !reverted_with(result, expected.as_deref()) {
+                self.expected_logs.clear();
+                return Err(EvmError::MissingRevert);
+            }
+        }
+
+        let expected_logs = std::mem::take(&mut self.expected_logs);
+        let mut logs = result.logs.iter();
+
+        // The expected logs must be emitted in order, but other logs can be emitted between them
+        if expected_logs
+            .iter()
+            .all(|expected| logs.any(|log| log == expected))
+        {
+            Ok(())
+        } else {
+            Err(EvmError::MissingEmit)
+        }
+    }
+}
+
+// Any failure matches if no revert data is expected. Otherwise, the revert data must be the same,
+// or an Error(string) with that message, like in Foundry.
+fn reverted_with(result: &ExecutionResult, expected: Option<&[u8]>) -> bool {
+    let expected = match (&result.error, expected) {
+        (None, _) => return false,
+        (Some(_), None) => return true,
+        (Some(_), Some(expected)) => expected,
+    };
+
+    if result.return_data == expected {
+        return true;
+    }
+
+    match result.revert_reason() {
+        Some(RevertReason::Error(message)) => message.as_bytes() == expected,
+        _ => false,
+    }
+}
@@ -1,3 +1,4 @@
+use crate::host::{EmptyHost, Host};
 use ethereum_types::Address;
 use ethereum_types::U256;
 
@@ -9,6 +10,7 @@ pub struct CallContext<'context> {
     pub caller_address: Address,
     pub origin_address: Address,
     pub gas_price: U256,
+    pub host: &'context dyn Host,
 }
 
 impl Default for CallContext<'_> {
@@ -20,11 +22,12 @@ impl Default for CallContext<'_> {
             caller_address: Address::zero(),
             origin_address: Address::zero(),
             gas_price: U256::zero(),
+            host: &EmptyHost,
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BlockContext {
     pub coinbase_address: Address,
     pub timestamp: u32,
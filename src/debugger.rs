@@ -0,0 +1,372 @@
+use crate::analyzed_code::AnalyzedCode;
+use crate::bytecode::Bytecode;
+use crate::context::{BlockContext, CallContext};
+use crate::evm::ExecutionResult;
+use crate::execution_error::ExecutionError;
+use crate::instruction_table::InstructionTable;
+use crate::opcode_handlers::{ExecutionStatus, HaltReason};
+use crate::opcodes::Opcode;
+use crate::vm::VmState;
+use ethereum_types::U256;
+use std::collections::VecDeque;
+use std::num::NonZeroUsize;
+
+// By default, up to 64 states are kept, one every 1024 instructions, so that running backwards
+// doesn't need a copy of the memory and storage per instruction
+const DEFAULT_CHECKPOINT_INTERVAL: usize = 1024;
+const DEFAULT_MAX_CHECKPOINTS: usize = 64;
+
+// Breakpoints pause the execution before running the instruction they match
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Breakpoint {
+    Pc(usize),
+    Opcode(Opcode),
+    // An SSTORE to the slot
+    StorageWrite(U256),
+}
+
+// Runs code one instruction at a time. It can also run backwards, by replaying the execution from
+// states recorded every few instructions, as executions are deterministic.
+pub struct Debugger<'a> {
+    bytecode: Bytecode<'a>,
+    instruction_table: &'a InstructionTable,
+    call_context: &'a CallContext<'a>,
+    block_context: &'a BlockContext,
+    vm_state: VmState,
+    steps: usize,
+    // The oldest ones are dropped when there are too many, so the execution can't be run
+    // backwards past the first one
+    checkpoints: VecDeque<Checkpoint>,
+    checkpoint_interval: usize,
+    max_checkpoints: usize,
+    breakpoints: Vec<Breakpoint>,
+    outcome: Option<Result<HaltReason, ExecutionError>>,
+}
+
+// The state after running a number of instructions
+struct Checkpoint {
+    steps: usize,
+    vm_state: VmState,
+}
+
+impl<'a> Debugger<'a> {
+    pub fn new(
+        code: &'a [u8],
+        call_context: &'a CallContext<'a>,
+        block_context: &'a BlockContext,
+    ) -> Debugger<'a> {
+        Debugger::with_table(
+            code,
+            InstructionTable::default_table(),
+            call_context,
+            block_context,
+        )
+    }
+
+    pub fn with_table(
+        code: &'a [u8],
+        instruction_table: &'a InstructionTable,
+        call_context: &'a CallContext<'a>,
+        block_context: &'a BlockContext,
+    ) -> Debugger<'a> {
+        Debugger::with_bytecode(
+            Bytecode::new(code),
+            instruction_table,
+            call_context,
+            block_context,
+        )
+    }
+
+    // Like with_table, but reusing an analysis of the code, like one from a CodeCache
+    pub fn with_analyzed_code(
+        code: &'a AnalyzedCode,
+        instruction_table: &'a InstructionTable,
+        call_context: &'a CallContext<'a>,
+        block_context: &'a BlockContext,
+    ) -> Debugger<'a> {
+        Debugger::with_bytecode(
+            code.bytecode(),
+            instruction_table,
+            call_context,
+            block_context,
+        )
+    }
+
+    fn with_bytecode(
+        bytecode: Bytecode<'a>,
+        instruction_table: &'a InstructionTable,
+        call_context: &'a CallContext<'a>,
+        block_context: &'a BlockContext,
+    ) -> Debugger<'a> {
+        let mut checkpoints = VecDeque::new();
+        checkpoints.push_back(Checkpoint {
+            steps: 0,
+            vm_state: VmState::new(),
+        });
+
+        Debugger {
+            bytecode,
+            instruction_table,
+            call_context,
+            block_context,
+            vm_state: VmState::new(),
+            steps: 0,
+            checkpoints,
+            checkpoint_interval: DEFAULT_CHECKPOINT_INTERVAL,
+            max_checkpoints: DEFAULT_MAX_CHECKPOINTS,
+            breakpoints: Vec::new(),
+            outcome: None,
+        }
+    }
+
+    // Records a state every checkpoint_interval instructions, keeping up to max_checkpoints of
+    // them. Larger intervals use less memory, but make running backwards slower.
+    pub fn set_history(
+        &mut self,
+        checkpoint_interval: NonZeroUsize,
+        max_checkpoints: NonZeroUsize,
+    ) {
+        self.checkpoint_interval = checkpoint_interval.get();
+        self.max_checkpoints = max_checkpoints.get();
+        self.drop_old_checkpoints();
+    }
+
+    pub fn vm_state(&self) -> &VmState {
+        &self.vm_state
+    }
+
+    pub fn pc(&self) -> usize {
+        self.vm_state.pc
+    }
+
+    // The opcode of the next instruction, or None if the execution is over
+    pub fn opcode(&self) -> Option<Opcode> {
+        if self.is_finished() || self.vm_state.pc >= self.bytecode.size() {
+            return None;
+        }
+
+        Some(self.bytecode.get_opcode_at(self.vm_state.pc))
+    }
+
+    // How many instructions have been run
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.outcome.is_some()
+    }
+
+    // How the execution ended, or None if it hasn't yet
+    pub fn outcome(&self) -> Option<Result<HaltReason, ExecutionError>> {
+        self.outcome
+    }
+
+    pub fn result(&self) -> Option<ExecutionResult> {
+        let result = match self.outcome? {
+            Ok(halt_reason) => ExecutionResult::success(self.vm_state.clone(), halt_reason),
+            Err(error) => ExecutionResult::failure(self.vm_state.clone(), error),
+        };
+
+        Some(result)
+    }
+
+    // A line per 32-byte word of memory, with its offset
+    pub fn memory_dump(&self) -> String {
+        self.vm_state
+            .memory
+            .as_bytes()
+            .chunks(32)
+            .enumerate()
+            .map(|(index, word)| format!("{:04x}: {}", index * 32, hex::encode(word)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        if !self.breakpoints.contains(&breakpoint) {
+            self.breakpoints.push(breakpoint);
+        }
+    }
+
+    // Returns false if there was no such breakpoint
+    pub fn remove_breakpoint(&mut self, breakpoint: Breakpoint) -> bool {
+        let length = self.breakpoints.len();
+        self.breakpoints.retain(|existing| *existing != breakpoint);
+
+        self.breakpoints.len() != length
+    }
+
+    // Runs the next instruction. Returns false if the execution was already over.
+    pub fn step(&mut self) -> bool {
+        if self.is_finished() {
+            return false;
+        }
+
+        self.steps += 1;
+
+        let pc = self.vm_state.pc;
+
+        if pc >= self.bytecode.size() {
+            self.outcome = Some(Ok(HaltReason::Stop));
+            return true;
+        }
+
+        let opcode = self.bytecode.get_opcode_at(pc);
+        self.vm_state.pc += 1;
+
+        let step_result = self.instruction_table.execute(
+            opcode,
+            &mut self.vm_state,
+            &self.bytecode,
+            self.call_context,
+            self.block_context,
+        );
+
+        match step_result {
+            Err(error) => self.outcome = Some(Err(ExecutionError::new(error, pc, opcode))),
+            Ok(ExecutionStatus::Halted(halt_reason)) => self.outcome = Some(Ok(halt_reason)),
+            Ok(ExecutionStatus::Running) => self.record_checkpoint(),
+        }
+
+        true
+    }
+
+    // Inter-account calls aren't supported yet, so every call instruction completes in a single
+    // step, and this is the same as step
+    pub fn step_over(&mut self) -> bool {
+        self.step()
+    }
+
+    // Runs until a breakpoint is hit, which is returned, or until the execution is over. The
+    // current instruction is always run, so that resuming from a breakpoint moves past it.
+    pub fn resume(&mut self) -> Option<Breakpoint> {
+        while self.step() {
+            if let Some(breakpoint) = self.hit_breakpoint() {
+                return Some(breakpoint);
+            }
+        }
+
+        None
+    }
+
+    // Restores the state before the last instruction. Returns false if nothing has been run, or
+    // if that state is older than the recorded ones.
+    pub fn step_back(&mut self) -> bool {
+        self.steps > 0 && self.go_to(self.steps - 1)
+    }
+
+    // Like resume, but running backwards until a breakpoint or the oldest recorded state
+    pub fn resume_backwards(&mut self) -> Option<Breakpoint> {
+        let mut end = self.steps;
+
+        // Replays the execution from each checkpoint, newest first, looking for the last
+        // breakpoint hit before the end of its range
+        for index in (0..self.checkpoints.len()).rev() {
+            let start = self.checkpoints[index].steps;
+
+            if start >= end {
+                continue;
+            }
+
+            self.restore(index);
+            let mut last_hit = None;
+
+            loop {
+                if let Some(breakpoint) = self.hit_breakpoint() {
+                    last_hit = Some((self.steps, breakpoint));
+                }
+
+                if self.steps + 1 >= end {
+                    break;
+                }
+
+                self.step();
+            }
+
+            if let Some((steps, breakpoint)) = last_hit {
+                self.go_to(steps);
+                return Some(breakpoint);
+            }
+
+            end = start;
+        }
+
+        self.restore(0);
+
+        None
+    }
+
+    // Moves to the state after running the given number of instructions, which must not be after
+    // the current one. Returns false if it's older than the recorded ones.
+    fn go_to(&mut self, steps: usize) -> bool {
+        let index = match self
+            .checkpoints
+            .iter()
+            .rposition(|checkpoint| checkpoint.steps <= steps)
+        {
+            Some(index) => index,
+            None => return false,
+        };
+
+        if self.steps > steps || self.steps < self.checkpoints[index].steps {
+            self.restore(index);
+        }
+
+        while self.steps < steps {
+            self.step();
+        }
+
+        true
+    }
+
+    fn restore(&mut self, index: usize) {
+        let checkpoint = &self.checkpoints[index];
+
+        self.vm_state = checkpoint.vm_state.clone();
+        self.steps = checkpoint.steps;
+        self.outcome = None;
+    }
+
+    // Replaying an execution goes through already recorded steps, which aren't recorded again
+    fn record_checkpoint(&mut self) {
+        let is_new = self
+            .checkpoints
+            .back()
+            .is_none_or(|checkpoint| checkpoint.steps < self.steps);
+
+        if is_new && self.steps.is_multiple_of(self.checkpoint_interval) {
+            self.checkpoints.push_back(Checkpoint {
+                steps: self.steps,
+                vm_state: self.vm_state.clone(),
+            });
+
+            self.drop_old_checkpoints();
+        }
+    }
+
+    fn drop_old_checkpoints(&mut self) {
+        while self.checkpoints.len() > self.max_checkpoints {
+            self.checkpoints.pop_front();
+        }
+    }
+
+    fn hit_breakpoint(&self) -> Option<Breakpoint> {
+        let opcode = self.opcode()?;
+
+        self.breakpoints
+            .iter()
+            .copied()
+            .find(|breakpoint| match *breakpoint {
+                Breakpoint::Pc(pc) => pc == self.vm_state.pc,
+                Breakpoint::Opcode(expected) => expected == opcode,
+                Breakpoint::StorageWrite(key) => {
+                    opcode == Opcode::SSTORE && self.vm_state.stack.read(0) == Ok(key)
+                }
+            })
+    }
+}
@@ -0,0 +1,97 @@
+use crate::state::Account;
+use crate::transaction::Transaction;
+use ethereum_types::{Address, U256};
+use std::collections::HashMap;
+
+// EIP-7702 delegated accounts have `0xef0100 || address` as their code
+const DELEGATION_PREFIX: [u8; 3] = [0xef, 0x01, 0x00];
+const DELEGATION_DESIGNATOR_LENGTH: usize = 23;
+
+pub fn delegation_designator(address: &Address) -> Vec<u8> {
+    let mut code = Vec::with_capacity(DELEGATION_DESIGNATOR_LENGTH);
+    code.extend_from_slice(&DELEGATION_PREFIX);
+    code.extend_from_slice(address.as_bytes());
+
+    code
+}
+
+// Returns the address an account delegates to, if its code is a delegation designator.
+pub fn delegated_address(code: &[u8]) -> Option<Address> {
+    if code.len() != DELEGATION_DESIGNATOR_LENGTH || !code.starts_with(&DELEGATION_PREFIX) {
+        return None;
+    }
+
+    Some(Address::from_slice(&code[DELEGATION_PREFIX.len()..]))
+}
+
+// Returns the code that has to be executed when calling `address`. If the account delegates to
+// another one, that account's code is returned instead. Delegations are only followed once, so a
+// chain of them ends up executing a designator, which is invalid code.
+pub fn resolve_code<'state>(
+    accounts: &'state HashMap<Address, Account>,
+    address: &Address,
+) -> &'state [u8] {
+    let code = accounts
+        .get(address)
+        .map(|account| account.code.as_slice())
+        .unwrap_or(&[]);
+
+    match delegated_address(code) {
+        Some(delegate) => accounts
+            .get(&delegate)
+            .map(|account| account.code.as_slice())
+            .unwrap_or(&[]),
+        None => code,
+    }
+}
+
+// Processes the authorization list of a transaction, as defined in EIP-7702, and returns the
+// authorities whose delegation was applied. Invalid authorizations are skipped.
+//
+// This has to be run after incrementing the sender's nonce, and before executing the
+// transaction.
+pub fn apply_authorizations(
+    accounts: &mut HashMap<Address, Account>,
+    transaction: &Transaction,
+    chain_id: u64,
+) -> Vec<Address> {
+    let mut authorities = Vec::new();
+
+    for authorization in &transaction.authorization_list {
+        if !authorization.chain_id.is_zero() && authorization.chain_id != U256::from(chain_id) {
+            continue;
+        }
+
+        if authorization.nonce == u64::MAX {
+            continue;
+        }
+
+        let authority = match authorization.authority() {
+            Some(authority) => authority,
+            None => continue,
+        };
+
+        let account = accounts.entry(authority).or_default();
+
+        if !account.code.is_empty() && delegated_address(&account.code).is_none() {
+            continue;
+        }
+
+        if account.nonce != authorization.nonce {
+            continue;
+        }
+
+        // Delegating to the zero address clears the delegation
+        account.code = if authorization.address.is_zero() {
+            Vec::new()
+        } else {
+            delegation_designator(&authorization.address)
+        };
+
+        account.nonce += 1;
+
+        authorities.push(authority);
+    }
+
+    authorities
+}
@@ -0,0 +1,33 @@
+use crate::bytecode::BytecodeIterator;
+use crate::instruction_table::InstructionTable;
+
+// Returns a line per instruction, with its pc, its name, and its immediate data if it has any.
+// Custom opcodes are shown with the name they were registered with.
+pub fn disassemble(code: &[u8], instruction_table: &InstructionTable) -> String {
+    let mut lines = Vec::new();
+    let mut instructions = BytecodeIterator::new(code).peekable();
+
+    while let Some(instruction) = instructions.next() {
+        let name = instruction_table.opcode_name(instruction.opcode);
+
+        let immediate_end = instructions
+            .peek()
+            .map(|next| next.pc)
+            .unwrap_or_else(|| code.len());
+
+        let immediate = &code[instruction.pc + 1..immediate_end];
+
+        if immediate.is_empty() {
+            lines.push(format!("{:04x}: {}", instruction.pc, name));
+        } else {
+            lines.push(format!(
+                "{:04x}: {} 0x{}",
+                instruction.pc,
+                name,
+                hex::encode(immediate)
+            ));
+        }
+    }
+
+    lines.join("\n")
+}
@@ -0,0 +1,564 @@
+use crate::opcodes::Opcode;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+
+// This module implements the EOF container format (EIP-3540), and its validation rules (EIP-3670,
+// EIP-4200, EIP-4750, EIP-5450, EIP-6206 and EIP-7480).
+
+const MAGIC: [u8; 2] = [0xef, 0x00];
+const VERSION: u8 = 0x01;
+
+const KIND_TYPES: u8 = 0x01;
+const KIND_CODE: u8 = 0x02;
+const KIND_DATA: u8 = 0xff;
+const TERMINATOR: u8 = 0x00;
+
+const TYPE_ENTRY_SIZE: usize = 4;
+const MAX_CODE_SECTIONS: usize = 1024;
+const MAX_SECTION_IO: u8 = 0x7f;
+const NON_RETURNING: u8 = 0x80;
+const MAX_STACK_HEIGHT: usize = 1023;
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum EofError {
+    InvalidMagic,
+    InvalidVersion,
+    IncompleteHeader,
+    InvalidHeader,
+    // The size of the container doesn't match the one declared in its header
+    InvalidContainerSize,
+    InvalidTypeSection,
+    UndefinedInstruction { section: usize, pc: usize },
+    TruncatedImmediate { section: usize, pc: usize },
+    InvalidRelativeJump { section: usize, pc: usize },
+    InvalidCodeSectionIndex { section: usize, pc: usize },
+    InvalidDataOffset { section: usize, pc: usize },
+    // RETF in a non-returning section, or a returning section that can't return
+    InvalidReturn { section: usize },
+    StackUnderflow { section: usize, pc: usize },
+    StackOverflow { section: usize, pc: usize },
+    InconsistentStackHeight { section: usize, pc: usize },
+    InvalidMaxStackHeight { section: usize },
+    UnreachableCode { section: usize, pc: usize },
+    NoTerminatingInstruction { section: usize },
+    UnreachableCodeSection { section: usize },
+}
+
+impl Display for EofError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for EofError {}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct CodeSectionType {
+    pub inputs: u8,
+    // 0x80 for sections that never return
+    pub outputs: u8,
+    pub max_stack_height: u16,
+}
+
+impl CodeSectionType {
+    pub fn is_returning(&self) -> bool {
+        self.outputs != NON_RETURNING
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EofContainer {
+    types: Vec<CodeSectionType>,
+    code_sections: Vec<Vec<u8>>,
+    data: Vec<u8>,
+}
+
+impl EofContainer {
+    pub fn is_eof(code: &[u8]) -> bool {
+        code.starts_with(&MAGIC)
+    }
+
+    // Parses and validates a container. Only valid containers can be created, so they are safe to
+    // execute without runtime checks of their control flow.
+    pub fn parse(bytes: &[u8]) -> Result<EofContainer, EofError> {
+        let container = parse_container(bytes)?;
+        validate_container(&container)?;
+
+        Ok(container)
+    }
+
+    pub fn types(&self) -> &[CodeSectionType] {
+        &self.types
+    }
+
+    pub fn code_section(&self, index: usize) -> &[u8] {
+        &self.code_sections[index]
+    }
+
+    pub fn code_sections_count(&self) -> usize {
+        self.code_sections.len()
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+// Returns the size of the instruction's immediate data, which may not be complete
+pub fn immediate_size(code: &[u8], pc: usize) -> usize {
+    let opcode = Opcode::try_from(code[pc]).unwrap();
+
+    match opcode {
+        Opcode::RJUMP | Opcode::RJUMPI | Opcode::CALLF | Opcode::JUMPF | Opcode::DATALOADN => 2,
+        Opcode::RJUMPV => match code.get(pc + 1) {
+            Some(max_index) => 1 + (*max_index as usize + 1) * 2,
+            None => 1,
+        },
+        _ => {
+            let n = opcode as u8;
+            if n >= Opcode::PUSH1 as u8 && n <= Opcode::PUSH32 as u8 {
+                (n - Opcode::PUSH1 as u8 + 1) as usize
+            } else {
+                0
+            }
+        }
+    }
+}
+
+pub fn read_u16(code: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([code[offset], code[offset + 1]])
+}
+
+pub fn read_i16(code: &[u8], offset: usize) -> i16 {
+    i16::from_be_bytes([code[offset], code[offset + 1]])
+}
+
+fn parse_container(bytes: &[u8]) -> Result<EofContainer, EofError> {
+    if !EofContainer::is_eof(bytes) {
+        return Err(EofError::InvalidMagic);
+    }
+
+    let mut reader = HeaderReader {
+        bytes,
+        position: MAGIC.len(),
+    };
+
+    if reader.read_u8()? != VERSION {
+        return Err(EofError::InvalidVersion);
+    }
+
+    reader.expect_kind(KIND_TYPES)?;
+    let types_size = reader.read_u16()? as usize;
+
+    reader.expect_kind(KIND_CODE)?;
+    let code_sections_count = reader.read_u16()? as usize;
+
+    if code_sections_count == 0
+        || code_sections_count > MAX_CODE_SECTIONS
+        || types_size != code_sections_count * TYPE_ENTRY_SIZE
+    {
+        return Err(EofError::InvalidHeader);
+    }
+
+    let mut code_sizes = Vec::with_capacity(code_sections_count);
+    for _ in 0..code_sections_count {
+        let code_size = reader.read_u16()? as usize;
+
+        if code_size == 0 {
+            return Err(EofError::InvalidHeader);
+        }
+
+        code_sizes.push(code_size);
+    }
+
+    reader.expect_kind(KIND_DATA)?;
+    let data_size = reader.read_u16()? as usize;
+
+    reader.expect_kind(TERMINATOR)?;
+
+    let body = &bytes[reader.position..];
+    let body_size = types_size + code_sizes.iter().sum::<usize>() + data_size;
+
+    if body.len() != body_size {
+        return Err(EofError::InvalidContainerSize);
+    }
+
+    let types = body[..types_size]
+        .chunks(TYPE_ENTRY_SIZE)
+        .map(|entry| CodeSectionType {
+            inputs: entry[0],
+            outputs: entry[1],
+            max_stack_height: read_u16(entry, 2),
+        })
+        .collect();
+
+    let mut code_sections = Vec::with_capacity(code_sections_count);
+    let mut offset = types_size;
+
+    for code_size in code_sizes {
+        code_sections.push(body[offset..offset + code_size].to_vec());
+        offset += code_size;
+    }
+
+    Ok(EofContainer {
+        types,
+        code_sections,
+        data: body[offset..].to_vec(),
+    })
+}
+
+struct HeaderReader<'data> {
+    bytes: &'data [u8],
+    position: usize,
+}
+
+impl<'data> HeaderReader<'data> {
+    fn read_u8(&mut self) -> Result<u8, EofError> {
+        let byte = *self
+            .bytes
+            .get(self.position)
+            .ok_or(EofError::IncompleteHeader)?;
+
+        self.position += 1;
+
+        Ok(byte)
+    }
+
+    fn read_u16(&mut self) -> Result<u16, EofError> {
+        let high = self.read_u8()?;
+        let low = self.read_u8()?;
+
+        Ok(u16::from_be_bytes([high, low]))
+    }
+
+    fn expect_kind(&mut self, kind: u8) -> Result<(), EofError> {
+        if self.read_u8()? != kind {
+            return Err(EofError::InvalidHeader);
+        }
+
+        Ok(())
+    }
+}
+
+fn validate_container(container: &EofContainer) -> Result<(), EofError> {
+    validate_types(&container.types)?;
+
+    let mut referenced_sections = Vec::with_capacity(container.code_sections.len());
+
+    for section in 0..container.code_sections.len() {
+        referenced_sections.push(validate_code_section(container, section)?);
+    }
+
+    // Every section has to be reachable from the first one
+    let mut reachable = HashSet::new();
+    let mut pending = vec![0];
+
+    while let Some(section) = pending.pop() {
+        if reachable.insert(section) {
+            pending.extend(referenced_sections[section].iter().copied());
+        }
+    }
+
+    if let Some(section) = (0..container.code_sections.len()).find(|s| !reachable.contains(s)) {
+        return Err(EofError::UnreachableCodeSection { section });
+    }
+
+    Ok(())
+}
+
+fn validate_types(types: &[CodeSectionType]) -> Result<(), EofError> {
+    let first = types[0];
+    if first.inputs != 0 || first.is_returning() {
+        return Err(EofError::InvalidTypeSection);
+    }
+
+    for section_type in types {
+        let valid_outputs =
+            section_type.outputs <= MAX_SECTION_IO || section_type.outputs == NON_RETURNING;
+
+        // The inputs are already on the stack, so they count towards the max stack height
+        if section_type.inputs > MAX_SECTION_IO
+            || !valid_outputs
+            || section_type.max_stack_height as usize > MAX_STACK_HEIGHT
+            || section_type.max_stack_height < section_type.inputs as u16
+        {
+            return Err(EofError::InvalidTypeSection);
+        }
+    }
+
+    Ok(())
+}
+
+// Validates a code section, returning the indexes of the sections it calls or jumps to.
+fn validate_code_section(container: &EofContainer, section: usize) -> Result<Vec<usize>, EofError> {
+    let code = &container.code_sections[section];
+    let section_type = container.types[section];
+
+    let mut is_instruction_start = vec![false; code.len()];
+    let mut pc = 0;
+
+    while pc < code.len() {
+        is_instruction_start[pc] = true;
+
+        let opcode = Opcode::try_from(code[pc]).unwrap();
+        if !is_valid_in_eof(opcode) {
+            return Err(EofError::UndefinedInstruction { section, pc });
+        }
+
+        let next = pc + 1 + immediate_size(code, pc);
+        if next > code.len() || (opcode == Opcode::RJUMPV && pc + 1 >= code.len()) {
+            return Err(EofError::TruncatedImmediate { section, pc });
+        }
+
+        pc = next;
+    }
+
+    let mut referenced_sections = Vec::new();
+    let mut can_return = false;
+    let mut pc = 0;
+
+    while pc < code.len() {
+        let opcode = Opcode::try_from(code[pc]).unwrap();
+
+        match opcode {
+            Opcode::RJUMP | Opcode::RJUMPI | Opcode::RJUMPV => {
+                for target in relative_jump_targets(code, pc) {
+                    let is_valid = target
+                        .map(|target| target < code.len() && is_instruction_start[target])
+                        .unwrap_or(false);
+
+                    if !is_valid {
+                        return Err(EofError::InvalidRelativeJump { section, pc });
+                    }
+                }
+            }
+            Opcode::CALLF | Opcode::JUMPF => {
+                let target = read_u16(code, pc + 1) as usize;
+
+                let target_type = container
+                    .types
+                    .get(target)
+                    .ok_or(EofError::InvalidCodeSectionIndex { section, pc })?;
+
+                if opcode == Opcode::CALLF && !target_type.is_returning() {
+                    return Err(EofError::InvalidCodeSectionIndex { section, pc });
+                }
+
+                if opcode == Opcode::JUMPF && target_type.is_returning() {
+                    if !section_type.is_returning() {
+                        return Err(EofError::InvalidCodeSectionIndex { section, pc });
+                    }
+
+                    can_return = true;
+                }
+
+                referenced_sections.push(target);
+            }
+            Opcode::RETF => {
+                if !section_type.is_returning() {
+                    return Err(EofError::InvalidReturn { section });
+                }
+
+                can_return = true;
+            }
+            Opcode::DATALOADN => {
+                let offset = read_u16(code, pc + 1) as usize;
+
+                if offset + 32 > container.data.len() {
+                    return Err(EofError::InvalidDataOffset { section, pc });
+                }
+            }
+            _ => {}
+        }
+
+        pc += 1 + immediate_size(code, pc);
+    }
+
+    if section_type.is_returning() && !can_return {
+        return Err(EofError::InvalidReturn { section });
+    }
+
+    validate_stack(container, section)?;
+
+    Ok(referenced_sections)
+}
+
+// Computes the range of stack heights that each instruction can be reached with, in a single pass
+// over the code. This is possible because instructions can only be reached from previous ones,
+// except for backwards jumps, which must not change the stack height range of their target.
+fn validate_stack(container: &EofContainer, section: usize) -> Result<(), EofError> {
+    let code = &container.code_sections[section];
+    let section_type = container.types[section];
+
+    let initial_height = section_type.inputs as usize;
+    let mut heights: Vec<Option<(usize, usize)>> = vec![None; code.len()];
+    heights[0] = Some((initial_height, initial_height));
+
+    let mut max_height = initial_height;
+    let mut pc = 0;
+
+    while pc < code.len() {
+        let (min, max) = heights[pc].ok_or(EofError::UnreachableCode { section, pc })?;
+
+        let opcode = Opcode::try_from(code[pc]).unwrap();
+        let next = pc + 1 + immediate_size(code, pc);
+
+        let (inputs, outputs) = match opcode {
+            Opcode::CALLF | Opcode::JUMPF => {
+                let target_type = container.types[read_u16(code, pc + 1) as usize];
+                let target_inputs = target_type.inputs as usize;
+
+                // validate_types already rejects the types whose inputs don't fit in their max
+                // stack height
+                let target_growth = (target_type.max_stack_height as usize)
+                    .checked_sub(target_inputs)
+                    .ok_or(EofError::InvalidTypeSection)?;
+
+                if max + target_growth > MAX_STACK_HEIGHT {
+                    return Err(EofError::StackOverflow { section, pc });
+                }
+
+                if opcode == Opcode::JUMPF && target_type.is_returning() {
+                    // The jumped-to section returns on behalf of this one, so its outputs have to
+                    // end up matching this section's. It can't return more outputs than this one
+                    // plus what it consumes.
+                    let expected = (section_type.outputs as usize + target_inputs)
+                        .checked_sub(target_type.outputs as usize);
+
+                    if min != max || Some(min) != expected {
+                        return Err(EofError::InconsistentStackHeight { section, pc });
+                    }
+                }
+
+                let target_outputs = if target_type.is_returning() {
+                    target_type.outputs as usize
+                } else {
+                    0
+                };
+
+                (target_inputs, target_outputs)
+            }
+            Opcode::RETF => {
+                let outputs = section_type.outputs as usize;
+
+                if min != max || min != outputs {
+                    return Err(EofError::InconsistentStackHeight { section, pc });
+                }
+
+                (outputs, 0)
+            }
+            _ => opcode.stack_io(),
+        };
+
+        if min < inputs {
+            return Err(EofError::StackUnderflow { section, pc });
+        }
+
+        let next_min = min - inputs + outputs;
+        let next_max = max - inputs + outputs;
+        max_height = std::cmp::max(max_height, next_max);
+
+        let successors = match opcode {
+            Opcode::STOP
+            | Opcode::RETURN
+            | Opcode::REVERT
+            | Opcode::INVALID
+            | Opcode::RETF
+            | Opcode::JUMPF => Vec::new(),
+            Opcode::RJUMP => relative_jump_targets(code, pc),
+            Opcode::RJUMPI | Opcode::RJUMPV => {
+                let mut successors = vec![Some(next)];
+                successors.extend(relative_jump_targets(code, pc));
+                successors
+            }
+            _ => vec![Some(next)],
+        };
+
+        for successor in successors {
+            // Targets were already validated, so only falling through can get us out of the code
+            let successor = successor.expect("Jump targets are already validated");
+
+            if successor >= code.len() {
+                return Err(EofError::NoTerminatingInstruction { section });
+            }
+
+            if successor > pc {
+                heights[successor] = Some(match heights[successor] {
+                    Some((successor_min, successor_max)) => (
+                        std::cmp::min(successor_min, next_min),
+                        std::cmp::max(successor_max, next_max),
+                    ),
+                    None => (next_min, next_max),
+                });
+            } else if heights[successor] != Some((next_min, next_max)) {
+                return Err(EofError::InconsistentStackHeight { section, pc });
+            }
+        }
+
+        pc = next;
+    }
+
+    if max_height > MAX_STACK_HEIGHT {
+        return Err(EofError::StackOverflow { section, pc: 0 });
+    }
+
+    if max_height != section_type.max_stack_height as usize {
+        return Err(EofError::InvalidMaxStackHeight { section });
+    }
+
+    Ok(())
+}
+
+// Returns the targets of RJUMP, RJUMPI and RJUMPV, or None for the ones that are out of bounds.
+pub fn relative_jump_targets(code: &[u8], pc: usize) -> Vec<Option<usize>> {
+    let opcode = Opcode::try_from(code[pc]).unwrap();
+    let next = pc + 1 + immediate_size(code, pc);
+
+    let offsets_start = if opcode == Opcode::RJUMPV {
+        pc + 2
+    } else {
+        pc + 1
+    };
+
+    (offsets_start..next)
+        .step_by(2)
+        .map(|offset_position| {
+            let target = next as isize + read_i16(code, offset_position) as isize;
+
+            if target < 0 {
+                None
+            } else {
+                Some(target as usize)
+            }
+        })
+        .collect()
+}
+
+fn is_valid_in_eof(opcode: Opcode) -> bool {
+    if opcode.is_unassigned() {
+        return false;
+    }
+
+    // These are deprecated in EOF code, as they depend on the code's layout, observe gas, or are
+    // replaced by EOF-specific alternatives
+    !matches!(
+        opcode,
+        Opcode::CALLCODE
+            | Opcode::SELFDESTRUCT
+            | Opcode::JUMP
+            | Opcode::JUMPI
+            | Opcode::PC
+            | Opcode::CREATE
+            | Opcode::CREATE2
+            | Opcode::CODESIZE
+            | Opcode::CODECOPY
+            | Opcode::EXTCODESIZE
+            | Opcode::EXTCODECOPY
+            | Opcode::EXTCODEHASH
+            | Opcode::GAS
+            | Opcode::CALL
+            | Opcode::STATICCALL
+            | Opcode::DELEGATECALL
+    )
+}
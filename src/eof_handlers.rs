@@ -0,0 +1,190 @@
+use crate::eof::{read_i16, read_u16, relative_jump_targets, EofContainer};
+use crate::execution_error::StepError::StackOverflow;
+use crate::opcode_handlers::{data_copy_handler, get_slice, ExecutionStatus, StepResult};
+use crate::opcodes::Opcode;
+use crate::stack::MAX_STACK_DEPTH;
+use crate::vm::VmState;
+use ethereum_types::U256;
+use ExecutionStatus::Running;
+
+const MAX_RETURN_STACK_DEPTH: usize = 1024;
+
+#[derive(Debug)]
+pub struct EofState {
+    pub section: usize,
+    // The section and pc to go back to when executing RETF
+    return_stack: Vec<(usize, usize)>,
+}
+
+impl EofState {
+    pub fn new() -> EofState {
+        EofState {
+            section: 0,
+            return_stack: Vec::new(),
+        }
+    }
+}
+
+pub fn is_eof_opcode(opcode: Opcode) -> bool {
+    matches!(
+        opcode,
+        Opcode::DATALOAD
+            | Opcode::DATALOADN
+            | Opcode::DATASIZE
+            | Opcode::DATACOPY
+            | Opcode::RJUMP
+            | Opcode::RJUMPI
+            | Opcode::RJUMPV
+            | Opcode::CALLF
+            | Opcode::RETF
+            | Opcode::JUMPF
+    )
+}
+
+// Executes one of the opcodes introduced by EOF. The container has already been validated, so
+// immediates and jump destinations don't need to be checked here. vm_state.pc points to the byte
+// after the opcode.
+pub fn execute_eof_opcode(
+    opcode: Opcode,
+    vm_state: &mut VmState,
+    eof_state: &mut EofState,
+    container: &EofContainer,
+) -> StepResult {
+    let code = container.code_section(eof_state.section);
+
+    match opcode {
+        Opcode::DATALOAD => {
+            let u0 = vm_state.stack.pop()?;
+
+            let value = if u0 > U256::from(usize::MAX) {
+                U256::zero()
+            } else {
+                read_word(container.data(), u0.as_usize())
+            };
+
+            vm_state.stack.push(value)?;
+
+            Ok(Running)
+        }
+        Opcode::DATALOADN => {
+            let offset = read_u16(code, vm_state.pc) as usize;
+
+            vm_state.stack.push(read_word(container.data(), offset))?;
+
+            vm_state.pc += 2;
+
+            Ok(Running)
+        }
+        Opcode::DATASIZE => {
+            vm_state.stack.push(U256::from(container.data().len()))?;
+
+            Ok(Running)
+        }
+        Opcode::DATACOPY => {
+            data_copy_handler(&mut vm_state.stack, &mut vm_state.memory, container.data())
+        }
+        Opcode::RJUMP => {
+            let offset = read_i16(code, vm_state.pc);
+            vm_state.pc = relative_jump(vm_state.pc + 2, offset);
+
+            Ok(Running)
+        }
+        Opcode::RJUMPI => {
+            let condition = vm_state.stack.pop()?;
+            let offset = read_i16(code, vm_state.pc);
+
+            vm_state.pc += 2;
+
+            if !condition.is_zero() {
+                vm_state.pc = relative_jump(vm_state.pc, offset);
+            }
+
+            Ok(Running)
+        }
+        Opcode::RJUMPV => {
+            let case = vm_state.stack.pop()?;
+            let max_index = code[vm_state.pc] as usize;
+
+            let targets = relative_jump_targets(code, vm_state.pc - 1);
+
+            vm_state.pc += 1 + (max_index + 1) * 2;
+
+            if case <= U256::from(max_index) {
+                vm_state.pc = targets[case.as_usize()].expect("Jump targets are already validated");
+            }
+
+            Ok(Running)
+        }
+        Opcode::CALLF => {
+            let target = read_u16(code, vm_state.pc) as usize;
+
+            if eof_state.return_stack.len() == MAX_RETURN_STACK_DEPTH {
+                return Err(StackOverflow);
+            }
+
+            ensure_stack_fits_section(vm_state, container, target)?;
+
+            eof_state
+                .return_stack
+                .push((eof_state.section, vm_state.pc + 2));
+
+            eof_state.section = target;
+            vm_state.pc = 0;
+
+            Ok(Running)
+        }
+        Opcode::RETF => {
+            // Validation ensures that the first section, which is never called, can't use RETF
+            let (section, pc) = eof_state
+                .return_stack
+                .pop()
+                .expect("RETF should only be used in called sections");
+
+            eof_state.section = section;
+            vm_state.pc = pc;
+
+            Ok(Running)
+        }
+        Opcode::JUMPF => {
+            let target = read_u16(code, vm_state.pc) as usize;
+
+            ensure_stack_fits_section(vm_state, container, target)?;
+
+            eof_state.section = target;
+            vm_state.pc = 0;
+
+            Ok(Running)
+        }
+        _ => unreachable!("{:?} is not an EOF opcode", opcode),
+    }
+}
+
+fn relative_jump(pc: usize, offset: i16) -> usize {
+    (pc as isize + offset as isize) as usize
+}
+
+fn read_word(data: &[u8], offset: usize) -> U256 {
+    let mut word = [0; 32];
+    let slice = get_slice(data, offset, 32);
+    word[..slice.len()].copy_from_slice(slice);
+
+    U256::from_big_endian(&word)
+}
+
+// The stack heights within a section are validated, but the stack may still overflow when entering
+// a section at a height the validation couldn't know about.
+fn ensure_stack_fits_section(
+    vm_state: &VmState,
+    container: &EofContainer,
+    section: usize,
+) -> StepResult {
+    let section_type = container.types()[section];
+    let max_height = vm_state.stack.len() + section_type.max_stack_height as usize
+        - section_type.inputs as usize;
+
+    if max_height > MAX_STACK_DEPTH {
+        return Err(StackOverflow);
+    }
+
+    Ok(Running)
+}
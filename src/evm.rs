@@ -1,20 +1,135 @@
+use crate::analyzed_code::AnalyzedCode;
 use crate::bytecode::Bytecode;
 
 use crate::context::{BlockContext, CallContext};
+use crate::eof::EofContainer;
+use crate::eof_handlers::{execute_eof_opcode, is_eof_opcode, EofState};
 use crate::execution_error::ExecutionError;
-use crate::opcode_handlers::{execute_opcode, ExecutionStatus, StepResult};
+use crate::instruction_table::InstructionTable;
+use crate::log::Log;
+use crate::opcode_handlers::{ExecutionStatus, HaltReason};
+use crate::revert_reason::{RevertDecoder, RevertReason};
+use crate::stack::MAX_STACK_DEPTH;
+use crate::tracer::{NoopTracer, Tracer, TracerAction};
 use crate::vm::VmState;
+use ethereum_types::U256;
+use std::collections::HashMap;
 
 #[derive(Debug)]
 pub struct ExecutionResult {
     pub return_data: Vec<u8>,
+    // How a successful execution ended. It's None if the execution failed.
+    pub halt_reason: Option<HaltReason>,
     pub error: Option<ExecutionError>,
+    pub logs: Vec<Log>,
+    pub storage: HashMap<U256, U256>,
+}
+
+impl ExecutionResult {
+    pub(crate) fn success(vm_state: VmState, halt_reason: HaltReason) -> ExecutionResult {
+        ExecutionResult {
+            return_data: vm_state.return_data,
+            halt_reason: Some(halt_reason),
+            error: None,
+            logs: vm_state.logs,
+            storage: vm_state.storage,
+        }
+    }
+
+    // A failed execution reverts its state changes, so only the return data is kept
+    pub(crate) fn failure(vm_state: VmState, error: ExecutionError) -> ExecutionResult {
+        ExecutionResult {
+            return_data: vm_state.return_data,
+            halt_reason: None,
+            error: Some(error),
+            logs: Vec::new(),
+            storage: HashMap::new(),
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+
+    // Decodes the return data of a reverted execution as an Error(string) or a Panic(uint256)
+    pub fn revert_reason(&self) -> Option<RevertReason> {
+        self.revert_reason_with(&RevertDecoder::new())
+    }
+
+    // Like revert_reason, but also recognizes the custom errors known to the decoder
+    pub fn revert_reason_with(&self, decoder: &RevertDecoder) -> Option<RevertReason> {
+        match self.error {
+            Some(ExecutionError::Revert { .. }) => decoder.decode(&self.return_data),
+            _ => None,
+        }
+    }
 }
 
 pub fn run(
     bytecode: &Bytecode,
     call_context: &CallContext,
     block_context: &BlockContext,
+) -> ExecutionResult {
+    run_with_table(
+        bytecode,
+        InstructionTable::default_table(),
+        call_context,
+        block_context,
+    )
+}
+
+pub fn run_with_table(
+    bytecode: &Bytecode,
+    instruction_table: &InstructionTable,
+    call_context: &CallContext,
+    block_context: &BlockContext,
+) -> ExecutionResult {
+    execute(
+        bytecode,
+        instruction_table,
+        &mut NoopTracer,
+        call_context,
+        block_context,
+    )
+}
+
+pub fn run_with_tracer(
+    bytecode: &Bytecode,
+    tracer: &mut dyn Tracer,
+    call_context: &CallContext,
+    block_context: &BlockContext,
+) -> ExecutionResult {
+    run_with_table_and_tracer(
+        bytecode,
+        InstructionTable::default_table(),
+        tracer,
+        call_context,
+        block_context,
+    )
+}
+
+pub fn run_with_table_and_tracer(
+    bytecode: &Bytecode,
+    instruction_table: &InstructionTable,
+    tracer: &mut dyn Tracer,
+    call_context: &CallContext,
+    block_context: &BlockContext,
+) -> ExecutionResult {
+    execute(
+        bytecode,
+        instruction_table,
+        tracer,
+        call_context,
+        block_context,
+    )
+}
+
+fn execute<T: Tracer + ?Sized>(
+    bytecode: &Bytecode,
+    instruction_table: &InstructionTable,
+    tracer: &mut T,
+    call_context: &CallContext,
+    block_context: &BlockContext,
 ) -> ExecutionResult {
     let mut vm_state = VmState::new();
 
@@ -26,37 +141,154 @@ pub fn run(
         // Apart from PUSH, only jumps could bring us to a similar situation, but those are handled
         // differently.
         if vm_state.pc >= bytecode_size {
-            return ExecutionResult {
-                error: None,
-                return_data: vm_state.return_data,
-            };
+            return ExecutionResult::success(vm_state, HaltReason::Stop);
         }
 
-        let step_result = run_next_step(&mut vm_state, bytecode, call_context, block_context);
+        let pc = vm_state.pc;
+        let opcode = bytecode.get_opcode_at(pc);
 
-        if let Err(error) = step_result {
-            return ExecutionResult {
-                error: Some(error),
-                return_data: vm_state.return_data,
-            };
-        } else if let Ok(ExecutionStatus::Halted) = step_result {
-            return ExecutionResult {
-                error: None,
-                return_data: vm_state.return_data,
-            };
+        if tracer.step(opcode, &vm_state, instruction_table) == TracerAction::Abort {
+            let error = ExecutionError::Aborted { pc, opcode };
+            return ExecutionResult::failure(vm_state, error);
+        }
+
+        vm_state.pc += 1;
+
+        let step_result =
+            instruction_table.execute(opcode, &mut vm_state, bytecode, call_context, block_context);
+
+        tracer.step_end(opcode, &vm_state, &step_result);
+
+        match step_result {
+            Err(error) => {
+                let error = ExecutionError::new(error, pc, opcode);
+                return ExecutionResult::failure(vm_state, error);
+            }
+            Ok(ExecutionStatus::Halted(halt_reason)) => {
+                return ExecutionResult::success(vm_state, halt_reason);
+            }
+            Ok(ExecutionStatus::Running) => {}
         }
     }
 }
 
-fn run_next_step(
-    vm_state: &mut VmState,
-    bytecode: &Bytecode,
+pub fn run_analyzed(
+    code: &AnalyzedCode,
+    call_context: &CallContext,
+    block_context: &BlockContext,
+) -> ExecutionResult {
+    execute_blocks(
+        code,
+        InstructionTable::default_table(),
+        call_context,
+        block_context,
+    )
+}
+
+// Like execute, but checking the stack bounds once per basic block. Blocks that could underflow or
+// overflow the stack are run one instruction at a time instead, until the next block starts, so
+// that the error is raised by the same instruction as in execute.
+//
+// The handlers still check the stack themselves, so this isn't faster yet. It's where gas will be
+// charged per block once it's metered.
+fn execute_blocks(
+    code: &AnalyzedCode,
+    instruction_table: &InstructionTable,
+    call_context: &CallContext,
+    block_context: &BlockContext,
+) -> ExecutionResult {
+    let mut vm_state = VmState::new();
+
+    let bytecode = code.bytecode();
+    let bytecode_size = bytecode.size();
+
+    loop {
+        if vm_state.pc >= bytecode_size {
+            return ExecutionResult::success(vm_state, HaltReason::Stop);
+        }
+
+        // Only the start of a block has one, so instructions run one by one keep doing so until
+        // they reach the next block
+        let block_end = match code.basic_block_at(vm_state.pc) {
+            Some(block) if block.fits_stack(vm_state.stack.len(), MAX_STACK_DEPTH) => block.end,
+            _ => vm_state.pc + 1,
+        };
+
+        loop {
+            let pc = vm_state.pc;
+            let opcode = bytecode.get_opcode_at(pc);
+            vm_state.pc += 1;
+
+            let step_result = instruction_table.execute(
+                opcode,
+                &mut vm_state,
+                &bytecode,
+                call_context,
+                block_context,
+            );
+
+            match step_result {
+                Err(error) => {
+                    let error = ExecutionError::new(error, pc, opcode);
+                    return ExecutionResult::failure(vm_state, error);
+                }
+                Ok(ExecutionStatus::Halted(halt_reason)) => {
+                    return ExecutionResult::success(vm_state, halt_reason);
+                }
+                Ok(ExecutionStatus::Running) => {}
+            }
+
+            // A jump back to the start of the block has to check its bounds again
+            if vm_state.pc >= block_end || vm_state.pc <= pc {
+                break;
+            }
+        }
+    }
+}
+
+// Runs the code of an EOF container, starting from its first code section. Legacy code keeps
+// treating the EOF opcodes as invalid, as there is no way to select a hardfork yet.
+pub fn run_eof(
+    container: &EofContainer,
     call_context: &CallContext,
     block_context: &BlockContext,
-) -> StepResult {
-    let opcode = bytecode.get_opcode_at(vm_state.pc);
+) -> ExecutionResult {
+    let mut vm_state = VmState::new();
+    let mut eof_state = EofState::new();
 
-    vm_state.pc += 1;
+    let code_sections: Vec<Bytecode> = (0..container.code_sections_count())
+        .map(|section| Bytecode::new(container.code_section(section)))
+        .collect();
+
+    loop {
+        let bytecode = &code_sections[eof_state.section];
 
-    execute_opcode(opcode, vm_state, bytecode, call_context, block_context)
+        let pc = vm_state.pc;
+        let opcode = bytecode.get_opcode_at(pc);
+        vm_state.pc += 1;
+
+        // Validation guarantees that the code doesn't fall off the end of its section
+        let step_result = if is_eof_opcode(opcode) {
+            execute_eof_opcode(opcode, &mut vm_state, &mut eof_state, container)
+        } else {
+            InstructionTable::default_table().execute(
+                opcode,
+                &mut vm_state,
+                bytecode,
+                call_context,
+                block_context,
+            )
+        };
+
+        match step_result {
+            Err(error) => {
+                let error = ExecutionError::new(error, pc, opcode);
+                return ExecutionResult::failure(vm_state, error);
+            }
+            Ok(ExecutionStatus::Halted(halt_reason)) => {
+                return ExecutionResult::success(vm_state, halt_reason);
+            }
+            Ok(ExecutionStatus::Running) => {}
+        }
+    }
 }
@@ -1,15 +1,134 @@
 use crate::opcodes::Opcode;
+use ethereum_types::U256;
 use std::fmt::{Display, Formatter, Result};
 
-#[derive(Debug, Eq, PartialEq)]
-pub enum ExecutionError {
+// Why an instruction failed. Instruction handlers return these, and the interpreter turns them
+// into an ExecutionError, adding the position of the instruction.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum StepError {
     StackOverflow,
     StackUnderflow,
-    InvalidJump,
+    InvalidJump { destination: U256 },
     Revert,
     InvalidOpcode,
-    OutOfGas,
-    UnsupportedOpcode(Opcode),
+    OutOfGas { requested_memory_size: U256 },
+    UnsupportedOpcode,
+}
+
+impl Display for StepError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for StepError {}
+
+// New kinds of errors may be added as more of the EVM is implemented
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[non_exhaustive]
+pub enum ExecutionError {
+    StackOverflow {
+        pc: usize,
+        opcode: Opcode,
+    },
+    StackUnderflow {
+        pc: usize,
+        opcode: Opcode,
+    },
+    InvalidJump {
+        pc: usize,
+        opcode: Opcode,
+        destination: U256,
+    },
+    Revert {
+        pc: usize,
+        opcode: Opcode,
+    },
+    InvalidOpcode {
+        pc: usize,
+        opcode: Opcode,
+    },
+    // There's no gas metering, so this only happens when the memory would exceed its limit.
+    // Sizes that overflow are reported as U256::MAX.
+    OutOfGas {
+        pc: usize,
+        opcode: Opcode,
+        requested_memory_size: U256,
+    },
+    UnsupportedOpcode {
+        pc: usize,
+        opcode: Opcode,
+    },
+    // A tracer stopped the execution before running the instruction
+    Aborted {
+        pc: usize,
+        opcode: Opcode,
+    },
+}
+
+impl ExecutionError {
+    pub fn new(error: StepError, pc: usize, opcode: Opcode) -> ExecutionError {
+        match error {
+            StepError::StackOverflow => ExecutionError::StackOverflow { pc, opcode },
+            StepError::StackUnderflow => ExecutionError::StackUnderflow { pc, opcode },
+            StepError::InvalidJump { destination } => ExecutionError::InvalidJump {
+                pc,
+                opcode,
+                destination,
+            },
+            StepError::Revert => ExecutionError::Revert { pc, opcode },
+            StepError::InvalidOpcode => ExecutionError::InvalidOpcode { pc, opcode },
+            StepError::OutOfGas {
+                requested_memory_size,
+            } => ExecutionError::OutOfGas {
+                pc,
+                opcode,
+                requested_memory_size,
+            },
+            StepError::UnsupportedOpcode => ExecutionError::UnsupportedOpcode { pc, opcode },
+        }
+    }
+
+    // The position of the instruction that failed
+    pub fn pc(&self) -> usize {
+        match *self {
+            ExecutionError::StackOverflow { pc, .. }
+            | ExecutionError::StackUnderflow { pc, .. }
+            | ExecutionError::InvalidJump { pc, .. }
+            | ExecutionError::Revert { pc, .. }
+            | ExecutionError::InvalidOpcode { pc, .. }
+            | ExecutionError::OutOfGas { pc, .. }
+            | ExecutionError::UnsupportedOpcode { pc, .. }
+            | ExecutionError::Aborted { pc, .. } => pc,
+        }
+    }
+
+    pub fn opcode(&self) -> Opcode {
+        match *self {
+            ExecutionError::StackOverflow { opcode, .. }
+            | ExecutionError::StackUnderflow { opcode, .. }
+            | ExecutionError::InvalidJump { opcode, .. }
+            | ExecutionError::Revert { opcode, .. }
+            | ExecutionError::InvalidOpcode { opcode, .. }
+            | ExecutionError::OutOfGas { opcode, .. }
+            | ExecutionError::UnsupportedOpcode { opcode, .. }
+            | ExecutionError::Aborted { opcode, .. } => opcode,
+        }
+    }
+
+    // The name of the variant, without its context
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ExecutionError::StackOverflow { .. } => "StackOverflow",
+            ExecutionError::StackUnderflow { .. } => "StackUnderflow",
+            ExecutionError::InvalidJump { .. } => "InvalidJump",
+            ExecutionError::Revert { .. } => "Revert",
+            ExecutionError::InvalidOpcode { .. } => "InvalidOpcode",
+            ExecutionError::OutOfGas { .. } => "OutOfGas",
+            ExecutionError::UnsupportedOpcode { .. } => "UnsupportedOpcode",
+            ExecutionError::Aborted { .. } => "Aborted",
+        }
+    }
 }
 
 impl Display for ExecutionError {
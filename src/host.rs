@@ -0,0 +1,55 @@
+use crate::keccak::keccak256;
+use ethereum_types::{Address, H256, U256};
+use std::fmt::{Debug, Formatter};
+
+// Read access to the world state the code runs in. Storage writes are kept by the VM and
+// returned in the ExecutionResult, so embedders decide whether to apply them.
+pub trait Host {
+    fn balance(&self, address: &Address) -> U256;
+
+    fn code(&self, address: &Address) -> Vec<u8>;
+
+    fn storage(&self, address: &Address, key: &U256) -> U256;
+
+    fn block_hash(&self, number: u32) -> H256;
+
+    // Accounts without code nor balance are considered non-existent, and have a zero hash. Hosts
+    // that track nonces should override this.
+    fn code_hash(&self, address: &Address) -> H256 {
+        let code = self.code(address);
+
+        if code.is_empty() && self.balance(address).is_zero() {
+            return H256::zero();
+        }
+
+        keccak256(&code)
+    }
+}
+
+impl Debug for dyn Host + '_ {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Host")
+    }
+}
+
+// A host where every account is empty
+#[derive(Debug, Default, Copy, Clone)]
+pub struct EmptyHost;
+
+impl Host for EmptyHost {
+    fn balance(&self, _address: &Address) -> U256 {
+        U256::zero()
+    }
+
+    fn code(&self, _address: &Address) -> Vec<u8> {
+        Vec::new()
+    }
+
+    fn storage(&self, _address: &Address, _key: &U256) -> U256 {
+        U256::zero()
+    }
+
+    fn block_hash(&self, _number: u32) -> H256 {
+        H256::zero()
+    }
+}
@@ -0,0 +1,146 @@
+use crate::bytecode::Bytecode;
+use crate::context::{BlockContext, CallContext};
+use crate::execution_error::StepError;
+use crate::opcode_handlers::{default_handlers, InstructionHandler, StepResult};
+use crate::opcodes::Opcode;
+use crate::stack::MAX_STACK_DEPTH;
+use crate::vm::VmState;
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::OnceLock;
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum InstructionTableError {
+    // Custom opcodes can only use bytes that have no instruction assigned
+    OpcodeAlreadyAssigned(Opcode),
+    OpcodeAlreadyRegistered(Opcode),
+}
+
+impl Display for InstructionTableError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for InstructionTableError {}
+
+// An opcode added by an embedder, like an L2-specific instruction. Its handler reads the stack and
+// memory through the VmState, and the world state through the CallContext's host. There's no gas
+// metering yet, so custom opcodes don't declare a gas cost.
+#[derive(Debug, Copy, Clone)]
+pub struct CustomOpcode {
+    pub name: &'static str,
+    pub inputs: usize,
+    pub outputs: usize,
+    pub handler: InstructionHandler,
+}
+
+// The handler that executes each opcode. Embedders can replace any of them, or register custom
+// opcodes in the unassigned ones.
+#[derive(Clone)]
+pub struct InstructionTable {
+    handlers: [InstructionHandler; 256],
+    custom_opcodes: [Option<CustomOpcode>; 256],
+}
+
+impl InstructionTable {
+    pub fn new() -> InstructionTable {
+        InstructionTable {
+            handlers: default_handlers(),
+            custom_opcodes: [None; 256],
+        }
+    }
+
+    // A shared instance of the default table, for executions that don't customize it
+    pub fn default_table() -> &'static InstructionTable {
+        static DEFAULT_TABLE: OnceLock<InstructionTable> = OnceLock::new();
+
+        DEFAULT_TABLE.get_or_init(InstructionTable::new)
+    }
+
+    pub fn set(&mut self, opcode: Opcode, handler: InstructionHandler) {
+        self.handlers[opcode as usize] = handler;
+    }
+
+    pub fn get(&self, opcode: Opcode) -> InstructionHandler {
+        self.handlers[opcode as usize]
+    }
+
+    pub fn register_custom_opcode(
+        &mut self,
+        opcode: Opcode,
+        custom_opcode: CustomOpcode,
+    ) -> Result<(), InstructionTableError> {
+        if !opcode.is_unassigned() {
+            return Err(InstructionTableError::OpcodeAlreadyAssigned(opcode));
+        }
+
+        if self.custom_opcodes[opcode as usize].is_some() {
+            return Err(InstructionTableError::OpcodeAlreadyRegistered(opcode));
+        }
+
+        self.handlers[opcode as usize] = custom_opcode.handler;
+        self.custom_opcodes[opcode as usize] = Some(custom_opcode);
+
+        Ok(())
+    }
+
+    pub fn custom_opcode(&self, opcode: Opcode) -> Option<&CustomOpcode> {
+        self.custom_opcodes[opcode as usize].as_ref()
+    }
+
+    // Returns the opcode's mnemonic, using the registered name for custom opcodes
+    pub fn opcode_name(&self, opcode: Opcode) -> String {
+        match self.custom_opcode(opcode) {
+            Some(custom_opcode) => custom_opcode.name.to_string(),
+            None => format!("{:?}", opcode),
+        }
+    }
+
+    pub fn execute(
+        &self,
+        opcode: Opcode,
+        vm_state: &mut VmState,
+        bytecode: &Bytecode,
+        call_context: &CallContext,
+        block_context: &BlockContext,
+    ) -> StepResult {
+        // Built-in handlers check the stack themselves, but custom ones rely on their declared
+        // arity being validated here
+        if let Some(custom_opcode) = &self.custom_opcodes[opcode as usize] {
+            let stack_height = vm_state.stack.len();
+
+            if stack_height < custom_opcode.inputs {
+                return Err(StepError::StackUnderflow);
+            }
+
+            if stack_height - custom_opcode.inputs + custom_opcode.outputs > MAX_STACK_DEPTH {
+                return Err(StepError::StackOverflow);
+            }
+        }
+
+        self.handlers[opcode as usize](opcode, vm_state, bytecode, call_context, block_context)
+    }
+}
+
+impl Default for InstructionTable {
+    fn default() -> Self {
+        InstructionTable::new()
+    }
+}
+
+impl Debug for InstructionTable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let custom_opcodes: Vec<&str> = self
+            .custom_opcodes
+            .iter()
+            .flatten()
+            .map(|custom_opcode| custom_opcode.name)
+            .collect();
+
+        write!(
+            f,
+            "InstructionTable {{ custom_opcodes: {:?} }}",
+            custom_opcodes
+        )
+    }
+}
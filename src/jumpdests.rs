@@ -0,0 +1,30 @@
+use crate::bytecode::BytecodeIterator;
+use crate::opcodes::Opcode;
+
+// A bitvector with a bit set for every JUMPDEST that isn't part of a PUSH's data. It only depends
+// on the code, so it can be computed once and shared between executions of the same contract.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct JumpdestMap {
+    bits: Vec<u64>,
+}
+
+impl JumpdestMap {
+    pub fn analyze(code: &[u8]) -> JumpdestMap {
+        let mut bits = vec![0u64; code.len().div_ceil(64)];
+
+        for instruction in BytecodeIterator::new(code) {
+            if instruction.opcode == Opcode::JUMPDEST {
+                bits[instruction.pc / 64] |= 1 << (instruction.pc % 64);
+            }
+        }
+
+        JumpdestMap { bits }
+    }
+
+    pub fn contains(&self, pc: usize) -> bool {
+        match self.bits.get(pc / 64) {
+            Some(word) => word & (1 << (pc % 64)) != 0,
+            None => false,
+        }
+    }
+}
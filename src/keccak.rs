@@ -0,0 +1,6 @@
+use ethereum_types::H256;
+use sha3::{Digest, Keccak256};
+
+pub fn keccak256(data: &[u8]) -> H256 {
+    H256::from_slice(&Keccak256::digest(data))
+}
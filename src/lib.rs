@@ -1,15 +1,69 @@
+pub mod abi;
+mod analyzed_code;
+pub mod arithmetic;
+mod basic_blocks;
 mod bytecode;
+mod cheatcodes;
 mod context;
+mod debugger;
+mod delegation;
+mod disassembler;
+mod eof;
+mod eof_handlers;
 mod evm;
 mod execution_error;
+mod host;
 mod i256;
+mod instruction_table;
+mod jumpdests;
+mod keccak;
+mod log;
 mod memory;
 mod opcode_handlers;
 mod opcodes;
+mod revert_reason;
+pub mod rlp;
+mod session;
+mod signature;
 mod stack;
+mod state;
+mod tracer;
+mod transaction;
+mod trie;
 mod vm;
 
 pub use crate::context::{BlockContext, CallContext};
+pub use analyzed_code::{AnalyzedCode, CodeCache};
+pub use basic_blocks::{analyze_basic_blocks, BasicBlock};
 pub use bytecode::Bytecode;
 pub use bytecode::Instruction;
-pub use evm::run;
+pub use cheatcodes::CHEATCODE_ADDRESS;
+pub use debugger::{Breakpoint, Debugger};
+pub use delegation::{
+    apply_authorizations, delegated_address, delegation_designator, resolve_code,
+};
+pub use disassembler::disassemble;
+pub use eof::{CodeSectionType, EofContainer, EofError};
+pub use evm::{
+    run, run_analyzed, run_eof, run_with_table, run_with_table_and_tracer, run_with_tracer,
+    ExecutionResult,
+};
+pub use execution_error::{ExecutionError, StepError};
+pub use host::{EmptyHost, Host};
+pub use instruction_table::{CustomOpcode, InstructionTable, InstructionTableError};
+pub use jumpdests::JumpdestMap;
+pub use log::Log;
+pub use memory::Memory;
+pub use opcode_handlers::{ExecutionStatus, HaltReason, InstructionHandler, StepResult};
+pub use opcodes::Opcode;
+pub use revert_reason::{RevertDecoder, RevertReason};
+pub use session::{CallError, Deployment, Evm, EvmError, SnapshotId};
+pub use signature::Signature;
+pub use stack::Stack;
+pub use state::{create_address, state_root, storage_root, Account};
+pub use tracer::{Tracer, TracerAction};
+pub use transaction::{
+    AccessListItem, Authorization, Transaction, TransactionError, TransactionType,
+};
+pub use trie::{SecureTrie, Trie};
+pub use vm::VmState;
@@ -0,0 +1,8 @@
+use ethereum_types::{Address, H256};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Log {
+    pub address: Address,
+    pub topics: Vec<H256>,
+    pub data: Vec<u8>,
+}
@@ -0,0 +1,316 @@
+use ethereum_types::U256;
+use std::convert::TryFrom;
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::process;
+use tiny_evm::abi::Abi;
+use tiny_evm::{
+    disassemble, run, BlockContext, Breakpoint, Bytecode, CallContext, Debugger, ExecutionResult,
+    InstructionTable, Opcode, RevertDecoder,
+};
+
+const USAGE: &str =
+    "Usage: tiny-evm [--calldata <hex>] [--error <signature>]... [--abi <path>] [--debug] <code>
+
+Runs the hex-encoded code and prints its result.
+
+Options:
+  --calldata <hex>     The calldata of the execution
+  --error <signature>  A custom error to decode reverts with, like \"Unauthorized(address)\"
+  --abi <path>         A JSON ABI whose custom errors are used to decode reverts
+  --debug              Step through the execution interactively";
+
+const DEBUGGER_HELP: &str = "Commands:
+  s, step                        Run the next instruction
+  n, next                        Step over the next instruction
+  c, continue                    Run until a breakpoint or the end of the execution
+  b, back                        Undo the last instruction
+  rc, reverse                    Run backwards until a breakpoint or the oldest recorded state
+  break pc|op|slot <value>       Break before a pc, an opcode like SSTORE, or a write to a slot
+  delete pc|op|slot <value>      Remove a breakpoint
+  breakpoints                    List the breakpoints
+  stack                          Print the stack, from its top
+  memory                         Print a hex dump of the memory
+  storage                        Print the storage written so far
+  return                         Print the return data
+  h, help                        Print this message
+  q, quit                        Stop debugging";
+
+struct Options {
+    code: Vec<u8>,
+    calldata: Vec<u8>,
+    revert_decoder: RevertDecoder,
+    debug: bool,
+}
+
+fn main() {
+    let options = match parse_options(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+
+    let call_context = CallContext {
+        calldata: &options.calldata,
+        ..CallContext::default()
+    };
+
+    let block_context = BlockContext::default();
+
+    let result = if options.debug {
+        match debug(&options.code, &call_context, &block_context) {
+            Some(result) => result,
+            None => return,
+        }
+    } else {
+        run(&Bytecode::new(&options.code), &call_context, &block_context)
+    };
+
+    print_result(&result, &options.revert_decoder);
+
+    if !result.is_success() {
+        process::exit(1);
+    }
+}
+
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut code = None;
+    let mut calldata = Vec::new();
+    let mut revert_decoder = RevertDecoder::new();
+    let mut debug = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--calldata" => {
+                let value = args.next().ok_or("Missing the value of --calldata")?;
+                calldata = decode_hex(&value)?;
+            }
+            "--error" => {
+                let signature = args.next().ok_or("Missing the value of --error")?;
+                revert_decoder.add_custom_error(&signature);
+            }
+            "--abi" => {
+                let path = args.next().ok_or("Missing the value of --abi")?;
+                let json = fs::read_to_string(&path)
+                    .map_err(|error| format!("Can't read {}: {}", path, error))?;
+                let abi = Abi::from_json(&json).map_err(|_| format!("Invalid ABI {}", path))?;
+
+                revert_decoder.add_abi(&abi);
+            }
+            "--debug" => debug = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            _ if code.is_none() => code = Some(decode_hex(&arg)?),
+            _ => return Err(format!("Unexpected argument {}", arg)),
+        }
+    }
+
+    Ok(Options {
+        code: code.ok_or("Missing the code to run")?,
+        calldata,
+        revert_decoder,
+        debug,
+    })
+}
+
+fn decode_hex(value: &str) -> Result<Vec<u8>, String> {
+    hex::decode(value.trim_start_matches("0x")).map_err(|_| format!("Invalid hex value {}", value))
+}
+
+// Runs the debugger's commands from stdin. Returns the result if the execution finished, or None
+// if it was stopped before.
+fn debug(
+    code: &[u8],
+    call_context: &CallContext,
+    block_context: &BlockContext,
+) -> Option<ExecutionResult> {
+    let disassembly = disassemble(code, InstructionTable::default_table());
+    let mut debugger = Debugger::new(code, call_context, block_context);
+
+    println!("{}\n", DEBUGGER_HELP);
+    print_location(&debugger, &disassembly);
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+
+    loop {
+        print!("(tiny-evm) ");
+        io::stdout().flush().ok();
+
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            _ => break,
+        };
+
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        match words.as_slice() {
+            [] => {}
+            ["s"] | ["step"] => move_and_print(&mut debugger, &disassembly, Debugger::step),
+            ["n"] | ["next"] => move_and_print(&mut debugger, &disassembly, Debugger::step_over),
+            ["b"] | ["back"] => move_and_print(&mut debugger, &disassembly, Debugger::step_back),
+            ["c"] | ["continue"] => {
+                if let Some(breakpoint) = debugger.resume() {
+                    println!("Breakpoint: {:?}", breakpoint);
+                }
+
+                print_location(&debugger, &disassembly);
+            }
+            ["rc"] | ["reverse"] => {
+                if let Some(breakpoint) = debugger.resume_backwards() {
+                    println!("Breakpoint: {:?}", breakpoint);
+                }
+
+                print_location(&debugger, &disassembly);
+            }
+            ["break", kind, value] => match parse_breakpoint(kind, value) {
+                Ok(breakpoint) => debugger.add_breakpoint(breakpoint),
+                Err(message) => println!("{}", message),
+            },
+            ["delete", kind, value] => match parse_breakpoint(kind, value) {
+                Ok(breakpoint) if debugger.remove_breakpoint(breakpoint) => {}
+                Ok(_) => println!("There's no such breakpoint"),
+                Err(message) => println!("{}", message),
+            },
+            ["breakpoints"] => {
+                for breakpoint in debugger.breakpoints() {
+                    println!("{:?}", breakpoint);
+                }
+            }
+            ["stack"] => {
+                for (index, value) in debugger
+                    .vm_state()
+                    .stack
+                    .as_slice()
+                    .iter()
+                    .rev()
+                    .enumerate()
+                {
+                    println!("{}: {:#x}", index, value);
+                }
+            }
+            ["memory"] => println!("{}", debugger.memory_dump()),
+            ["storage"] => {
+                let mut storage: Vec<_> = debugger.vm_state().storage.iter().collect();
+                storage.sort();
+
+                for (key, value) in storage {
+                    println!("{} = {}", key, value);
+                }
+            }
+            ["return"] => println!("0x{}", hex::encode(&debugger.vm_state().return_data)),
+            ["h"] | ["help"] => println!("{}", DEBUGGER_HELP),
+            ["q"] | ["quit"] => break,
+            _ => println!("Unknown command {}", line.trim()),
+        }
+    }
+
+    debugger.result()
+}
+
+fn move_and_print<'a>(
+    debugger: &mut Debugger<'a>,
+    disassembly: &str,
+    movement: fn(&mut Debugger<'a>) -> bool,
+) {
+    if !movement(debugger) {
+        println!("Can't move further");
+    }
+
+    print_location(debugger, disassembly);
+}
+
+fn print_location(debugger: &Debugger, disassembly: &str) {
+    match debugger.outcome() {
+        Some(Ok(halt_reason)) => println!("Halted: {:?}", halt_reason),
+        Some(Err(error)) => println!(
+            "Error: {} at pc {} ({:?})",
+            error.kind(),
+            error.pc(),
+            error.opcode()
+        ),
+        None => {
+            let prefix = format!("{:04x}:", debugger.pc());
+
+            match disassembly.lines().find(|line| line.starts_with(&prefix)) {
+                Some(line) => println!("{}", line),
+                None => println!("{} end of the code", prefix),
+            }
+        }
+    }
+}
+
+fn parse_breakpoint(kind: &str, value: &str) -> Result<Breakpoint, String> {
+    match kind {
+        "pc" => {
+            let pc = parse_number(value)?;
+
+            if pc > U256::from(usize::MAX) {
+                return Err(format!("Invalid pc {}", value));
+            }
+
+            Ok(Breakpoint::Pc(pc.as_usize()))
+        }
+        "op" => (0..=u8::MAX)
+            .filter_map(|byte| Opcode::try_from(byte).ok())
+            .find(|opcode| format!("{:?}", opcode).eq_ignore_ascii_case(value))
+            .map(Breakpoint::Opcode)
+            .ok_or_else(|| format!("Unknown opcode {}", value)),
+        "slot" => Ok(Breakpoint::StorageWrite(parse_number(value)?)),
+        _ => Err(format!("Unknown breakpoint kind {}", kind)),
+    }
+}
+
+// Parses decimal numbers, or hex ones prefixed with 0x
+fn parse_number(value: &str) -> Result<U256, String> {
+    let number = match value.strip_prefix("0x") {
+        Some(hex) => hex.parse::<U256>().ok(),
+        None => U256::from_dec_str(value).ok(),
+    };
+
+    number.ok_or_else(|| format!("Invalid number {}", value))
+}
+
+fn print_result(result: &ExecutionResult, revert_decoder: &RevertDecoder) {
+    println!("Return data: 0x{}", hex::encode(&result.return_data));
+
+    match (&result.halt_reason, &result.error) {
+        (_, Some(error)) => println!(
+            "Error: {} at pc {} ({:?})",
+            error.kind(),
+            error.pc(),
+            error.opcode()
+        ),
+        (Some(halt_reason), None) => println!("Halted: {:?}", halt_reason),
+        (None, None) => {}
+    }
+
+    if let Some(reason) = result.revert_reason_with(revert_decoder) {
+        println!("Revert reason: {}", reason);
+    }
+
+    for log in &result.logs {
+        println!(
+            "Log: address {:?}, topics [{}], data 0x{}",
+            log.address,
+            log.topics
+                .iter()
+                .map(|topic| format!("{:?}", topic))
+                .collect::<Vec<_>>()
+                .join(", "),
+            hex::encode(&log.data)
+        );
+    }
+
+    let mut storage: Vec<_> = result.storage.iter().collect();
+    storage.sort();
+
+    for (key, value) in storage {
+        println!("Storage: {} = {}", key, value);
+    }
+}
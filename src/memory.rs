@@ -1,7 +1,7 @@
-use crate::execution_error::ExecutionError;
-use crate::execution_error::ExecutionError::OutOfGas;
+use crate::execution_error::StepError;
+use ethereum_types::U256;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Memory {
     data: Vec<u8>,
 }
@@ -15,18 +15,13 @@ impl Memory {
         }
     }
 
-    pub fn read(&mut self, offset: usize, length: usize) -> Result<&[u8], ExecutionError> {
+    pub fn read(&mut self, offset: usize, length: usize) -> Result<&[u8], StepError> {
         self.resize_if_necessary(offset + length)?;
 
         Ok(&self.data[offset..offset + length])
     }
 
-    pub fn write(
-        &mut self,
-        offset: usize,
-        length: usize,
-        data: &[u8],
-    ) -> Result<(), ExecutionError> {
+    pub fn write(&mut self, offset: usize, length: usize, data: &[u8]) -> Result<(), StepError> {
         let data_len = data.len();
         assert!(data_len <= length);
 
@@ -41,7 +36,11 @@ impl Memory {
         self.data.len()
     }
 
-    fn resize_if_necessary(&mut self, length: usize) -> Result<(), ExecutionError> {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    fn resize_if_necessary(&mut self, length: usize) -> Result<(), StepError> {
         let rem = length % 32;
         let rounded_length = if rem == 0 { length } else { length - rem + 32 };
 
@@ -50,7 +49,9 @@ impl Memory {
         }
 
         if rounded_length > MEMORY_LIMIT {
-            return Err(OutOfGas);
+            return Err(StepError::OutOfGas {
+                requested_memory_size: U256::from(rounded_length),
+            });
         }
 
         self.data.resize(rounded_length, 0);
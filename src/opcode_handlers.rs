@@ -1,854 +1,1345 @@
+use crate::arithmetic;
 use crate::bytecode::Bytecode;
-use crate::execution_error::ExecutionError;
+use crate::execution_error::StepError;
 use crate::opcodes::Opcode;
 
-use crate::execution_error::ExecutionError::{
+use crate::execution_error::StepError::{
     InvalidJump, InvalidOpcode, OutOfGas, Revert, UnsupportedOpcode,
 };
 
 use crate::context::{BlockContext, CallContext};
 use crate::i256::{Sign, I256};
+use crate::log::Log;
 use crate::memory::{Memory, MEMORY_LIMIT};
 use crate::stack::Stack;
 use crate::vm::VmState;
-use ethereum_types::{Address, U256, U512};
+use ethereum_types::{Address, H256, U256};
 use sha3::{Digest, Keccak256};
-use std::convert::TryFrom;
 use std::io::Write;
 use ExecutionStatus::{Halted, Running};
 
-#[derive(Debug)]
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[non_exhaustive]
+pub enum HaltReason {
+    Stop,
+    Return,
+    SelfDestruct,
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum ExecutionStatus {
     Running,
-    Halted,
+    Halted(HaltReason),
 }
 
-pub type StepResult = Result<ExecutionStatus, ExecutionError>;
+pub type StepResult = Result<ExecutionStatus, StepError>;
+
+pub type InstructionHandler =
+    fn(Opcode, &mut VmState, &Bytecode, &CallContext, &BlockContext) -> StepResult;
+
+// Returns the handlers of every opcode of the EVM. Unassigned opcodes are handled as INVALID.
+pub fn default_handlers() -> [InstructionHandler; 256] {
+    let mut handlers: [InstructionHandler; 256] = [invalid_opcode_handler; 256];
+
+    handlers[Opcode::STOP as usize] = stop_handler;
+    handlers[Opcode::ADD as usize] = add_handler;
+    handlers[Opcode::MUL as usize] = mul_handler;
+    handlers[Opcode::SUB as usize] = sub_handler;
+    handlers[Opcode::DIV as usize] = div_handler;
+    handlers[Opcode::SDIV as usize] = sdiv_handler;
+    handlers[Opcode::MOD as usize] = mod_handler;
+    handlers[Opcode::SMOD as usize] = smod_handler;
+    handlers[Opcode::ADDMOD as usize] = addmod_handler;
+    handlers[Opcode::MULMOD as usize] = mulmod_handler;
+    handlers[Opcode::EXP as usize] = exp_handler;
+    handlers[Opcode::SIGNEXTEND as usize] = signextend_handler;
+    handlers[Opcode::LT as usize] = lt_handler;
+    handlers[Opcode::GT as usize] = gt_handler;
+    handlers[Opcode::SLT as usize] = slt_handler;
+    handlers[Opcode::SGT as usize] = sgt_handler;
+    handlers[Opcode::EQ as usize] = eq_handler;
+    handlers[Opcode::ISZERO as usize] = iszero_handler;
+    handlers[Opcode::AND as usize] = and_handler;
+    handlers[Opcode::OR as usize] = or_handler;
+    handlers[Opcode::XOR as usize] = xor_handler;
+    handlers[Opcode::NOT as usize] = not_handler;
+    handlers[Opcode::BYTE as usize] = byte_handler;
+    handlers[Opcode::SHL as usize] = shl_handler;
+    handlers[Opcode::SHR as usize] = shr_handler;
+    handlers[Opcode::SAR as usize] = sar_handler;
+    handlers[Opcode::SHA3 as usize] = sha3_handler;
+    handlers[Opcode::ADDRESS as usize] = address_handler;
+    handlers[Opcode::BALANCE as usize] = balance_handler;
+    handlers[Opcode::ORIGIN as usize] = origin_handler;
+    handlers[Opcode::CALLER as usize] = caller_handler;
+    handlers[Opcode::CALLVALUE as usize] = callvalue_handler;
+    handlers[Opcode::CALLDATALOAD as usize] = calldataload_handler;
+    handlers[Opcode::CALLDATASIZE as usize] = calldatasize_handler;
+    handlers[Opcode::CALLDATACOPY as usize] = calldatacopy_handler;
+    handlers[Opcode::CODESIZE as usize] = codesize_handler;
+    handlers[Opcode::CODECOPY as usize] = codecopy_handler;
+    handlers[Opcode::GASPRICE as usize] = gasprice_handler;
+    handlers[Opcode::EXTCODESIZE as usize] = extcodesize_handler;
+    handlers[Opcode::EXTCODECOPY as usize] = extcodecopy_handler;
+    handlers[Opcode::RETURNDATASIZE as usize] = returndatasize_handler;
+    handlers[Opcode::RETURNDATACOPY as usize] = returndatacopy_handler;
+    handlers[Opcode::EXTCODEHASH as usize] = extcodehash_handler;
+    handlers[Opcode::BLOCKHASH as usize] = blockhash_handler;
+    handlers[Opcode::COINBASE as usize] = coinbase_handler;
+    handlers[Opcode::TIMESTAMP as usize] = timestamp_handler;
+    handlers[Opcode::NUMBER as usize] = number_handler;
+    handlers[Opcode::DIFFICULTY as usize] = difficulty_handler;
+    handlers[Opcode::GASLIMIT as usize] = gaslimit_handler;
+    handlers[Opcode::CHAINID as usize] = chainid_handler;
+    handlers[Opcode::POP as usize] = pop_handler;
+    handlers[Opcode::MLOAD as usize] = mload_handler;
+    handlers[Opcode::MSTORE as usize] = mstore_handler;
+    handlers[Opcode::MSTORE8 as usize] = mstore8_handler;
+    handlers[Opcode::SLOAD as usize] = sload_handler;
+    handlers[Opcode::SSTORE as usize] = sstore_handler;
+    handlers[Opcode::JUMP as usize] = jump_handler;
+    handlers[Opcode::JUMPI as usize] = jumpi_handler;
+    handlers[Opcode::PC as usize] = pc_handler;
+    handlers[Opcode::MSIZE as usize] = msize_handler;
+    handlers[Opcode::GAS as usize] = unsupported_opcode_handler;
+    handlers[Opcode::JUMPDEST as usize] = jumpdest_handler;
+    handlers[Opcode::PUSH1 as usize] = push_handler;
+    handlers[Opcode::PUSH2 as usize] = push_handler;
+    handlers[Opcode::PUSH3 as usize] = push_handler;
+    handlers[Opcode::PUSH4 as usize] = push_handler;
+    handlers[Opcode::PUSH5 as usize] = push_handler;
+    handlers[Opcode::PUSH6 as usize] = push_handler;
+    handlers[Opcode::PUSH7 as usize] = push_handler;
+    handlers[Opcode::PUSH8 as usize] = push_handler;
+    handlers[Opcode::PUSH9 as usize] = push_handler;
+    handlers[Opcode::PUSH10 as usize] = push_handler;
+    handlers[Opcode::PUSH11 as usize] = push_handler;
+    handlers[Opcode::PUSH12 as usize] = push_handler;
+    handlers[Opcode::PUSH13 as usize] = push_handler;
+    handlers[Opcode::PUSH14 as usize] = push_handler;
+    handlers[Opcode::PUSH15 as usize] = push_handler;
+    handlers[Opcode::PUSH16 as usize] = push_handler;
+    handlers[Opcode::PUSH17 as usize] = push_handler;
+    handlers[Opcode::PUSH18 as usize] = push_handler;
+    handlers[Opcode::PUSH19 as usize] = push_handler;
+    handlers[Opcode::PUSH20 as usize] = push_handler;
+    handlers[Opcode::PUSH21 as usize] = push_handler;
+    handlers[Opcode::PUSH22 as usize] = push_handler;
+    handlers[Opcode::PUSH23 as usize] = push_handler;
+    handlers[Opcode::PUSH24 as usize] = push_handler;
+    handlers[Opcode::PUSH25 as usize] = push_handler;
+    handlers[Opcode::PUSH26 as usize] = push_handler;
+    handlers[Opcode::PUSH27 as usize] = push_handler;
+    handlers[Opcode::PUSH28 as usize] = push_handler;
+    handlers[Opcode::PUSH29 as usize] = push_handler;
+    handlers[Opcode::PUSH30 as usize] = push_handler;
+    handlers[Opcode::PUSH31 as usize] = push_handler;
+    handlers[Opcode::PUSH32 as usize] = push_handler;
+    handlers[Opcode::DUP1 as usize] = dup_handler;
+    handlers[Opcode::DUP2 as usize] = dup_handler;
+    handlers[Opcode::DUP3 as usize] = dup_handler;
+    handlers[Opcode::DUP4 as usize] = dup_handler;
+    handlers[Opcode::DUP5 as usize] = dup_handler;
+    handlers[Opcode::DUP6 as usize] = dup_handler;
+    handlers[Opcode::DUP7 as usize] = dup_handler;
+    handlers[Opcode::DUP8 as usize] = dup_handler;
+    handlers[Opcode::DUP9 as usize] = dup_handler;
+    handlers[Opcode::DUP10 as usize] = dup_handler;
+    handlers[Opcode::DUP11 as usize] = dup_handler;
+    handlers[Opcode::DUP12 as usize] = dup_handler;
+    handlers[Opcode::DUP13 as usize] = dup_handler;
+    handlers[Opcode::DUP14 as usize] = dup_handler;
+    handlers[Opcode::DUP15 as usize] = dup_handler;
+    handlers[Opcode::DUP16 as usize] = dup_handler;
+    handlers[Opcode::SWAP1 as usize] = swap_handler;
+    handlers[Opcode::SWAP2 as usize] = swap_handler;
+    handlers[Opcode::SWAP3 as usize] = swap_handler;
+    handlers[Opcode::SWAP4 as usize] = swap_handler;
+    handlers[Opcode::SWAP5 as usize] = swap_handler;
+    handlers[Opcode::SWAP6 as usize] = swap_handler;
+    handlers[Opcode::SWAP7 as usize] = swap_handler;
+    handlers[Opcode::SWAP8 as usize] = swap_handler;
+    handlers[Opcode::SWAP9 as usize] = swap_handler;
+    handlers[Opcode::SWAP10 as usize] = swap_handler;
+    handlers[Opcode::SWAP11 as usize] = swap_handler;
+    handlers[Opcode::SWAP12 as usize] = swap_handler;
+    handlers[Opcode::SWAP13 as usize] = swap_handler;
+    handlers[Opcode::SWAP14 as usize] = swap_handler;
+    handlers[Opcode::SWAP15 as usize] = swap_handler;
+    handlers[Opcode::SWAP16 as usize] = swap_handler;
+    handlers[Opcode::LOG0 as usize] = log_handler;
+    handlers[Opcode::LOG1 as usize] = log_handler;
+    handlers[Opcode::LOG2 as usize] = log_handler;
+    handlers[Opcode::LOG3 as usize] = log_handler;
+    handlers[Opcode::LOG4 as usize] = log_handler;
+    handlers[Opcode::CREATE as usize] = unsupported_opcode_handler;
+    handlers[Opcode::CALL as usize] = unsupported_opcode_handler;
+    handlers[Opcode::CALLCODE as usize] = unsupported_opcode_handler;
+    handlers[Opcode::RETURN as usize] = return_handler;
+    handlers[Opcode::DELEGATECALL as usize] = unsupported_opcode_handler;
+    handlers[Opcode::CREATE2 as usize] = unsupported_opcode_handler;
+    handlers[Opcode::STATICCALL as usize] = unsupported_opcode_handler;
+    handlers[Opcode::REVERT as usize] = revert_handler;
+    handlers[Opcode::INVALID as usize] = invalid_opcode_handler;
+    handlers[Opcode::SELFDESTRUCT as usize] = selfdestruct_handler;
+
+    handlers
+}
 
-pub fn execute_opcode(
-    opcode: Opcode,
+fn stop_handler(
+    _opcode: Opcode,
+    _vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    Ok(Halted(HaltReason::Stop))
+}
+
+fn add_handler(
+    _opcode: Opcode,
     vm_state: &mut VmState,
-    bytecode: &Bytecode,
-    call_context: &CallContext,
-    block_context: &BlockContext,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
 ) -> StepResult {
-    match opcode {
-        Opcode::STOP => Ok(Halted),
-        Opcode::ADD => {
-            let u0 = vm_state.stack.pop()?;
-            let u1 = vm_state.stack.pop()?;
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
 
-            let (result, _) = u0.overflowing_add(u1);
+    let result = arithmetic::add(u0, u1);
 
-            vm_state.stack.push(result)?;
+    vm_state.stack.push(result)?;
 
-            Ok(Running)
-        }
-        Opcode::MUL => {
-            let u0 = vm_state.stack.pop()?;
-            let u1 = vm_state.stack.pop()?;
+    Ok(Running)
+}
 
-            let (result, _) = u0.overflowing_mul(u1);
+fn mul_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
 
-            vm_state.stack.push(result)?;
+    let (result, _) = u0.overflowing_mul(u1);
 
-            Ok(Running)
-        }
-        Opcode::SUB => {
-            let u0 = vm_state.stack.pop()?;
-            let u1 = vm_state.stack.pop()?;
+    vm_state.stack.push(result)?;
 
-            let (result, _) = u0.overflowing_sub(u1);
+    Ok(Running)
+}
 
-            vm_state.stack.push(result)?;
+fn sub_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
 
-            Ok(Running)
-        }
-        Opcode::DIV => {
-            let u0 = vm_state.stack.pop()?;
-            let u1 = vm_state.stack.pop()?;
+    let result = arithmetic::sub(u0, u1);
 
-            let result = if u1 == U256::zero() {
-                U256::zero()
-            } else {
-                u0 / u1
-            };
+    vm_state.stack.push(result)?;
 
-            vm_state.stack.push(result)?;
+    Ok(Running)
+}
 
-            Ok(Running)
-        }
-        Opcode::SDIV => {
-            let u0 = vm_state.stack.pop()?;
-            let u1 = vm_state.stack.pop()?;
+fn div_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
 
-            let op1: I256 = u0.into();
-            let op2: I256 = u1.into();
-            let value: U256 = (op1 / op2).into();
+    let result = if u1 == U256::zero() {
+        U256::zero()
+    } else {
+        u0 / u1
+    };
 
-            vm_state.stack.push(value)?;
+    vm_state.stack.push(result)?;
 
-            Ok(Running)
-        }
-        Opcode::MOD => {
-            let u0 = vm_state.stack.pop()?;
-            let u1 = vm_state.stack.pop()?;
+    Ok(Running)
+}
 
-            let result = if u1 == U256::zero() {
-                U256::zero()
-            } else {
-                u0 % u1
-            };
+fn sdiv_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
 
-            vm_state.stack.push(result)?;
+    let op1: I256 = u0.into();
+    let op2: I256 = u1.into();
+    let value: U256 = (op1 / op2).into();
 
-            Ok(Running)
-        }
-        Opcode::SMOD => {
-            let u0 = vm_state.stack.pop()?;
-            let u1 = vm_state.stack.pop()?;
+    vm_state.stack.push(value)?;
 
-            let result = if u1 == U256::zero() {
-                U256::zero()
-            } else {
-                let s0: I256 = u0.into();
-                let s1: I256 = u1.into();
+    Ok(Running)
+}
 
-                (s0 % s1).into()
-            };
+fn mod_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
 
-            vm_state.stack.push(result)?;
+    let result = arithmetic::modulo(u0, u1);
 
-            Ok(Running)
-        }
-        Opcode::ADDMOD => {
-            let u0: U512 = vm_state.stack.pop()?.into();
-            let u1: U512 = vm_state.stack.pop()?.into();
-            let u2: U512 = vm_state.stack.pop()?.into();
+    vm_state.stack.push(result)?;
 
-            let result = if u2 == U512::zero() {
-                U256::zero()
-            } else {
-                let value = (u0 + u1) % u2;
+    Ok(Running)
+}
 
-                U256::try_from(value)
-                    .expect("We applied (mod 256_bits_value), so the result fits in 256 bits")
-            };
+fn smod_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
 
-            vm_state.stack.push(result)?;
+    let result = if u1 == U256::zero() {
+        U256::zero()
+    } else {
+        let s0: I256 = u0.into();
+        let s1: I256 = u1.into();
 
-            Ok(Running)
-        }
-        Opcode::MULMOD => {
-            let u0: U512 = vm_state.stack.pop()?.into();
-            let u1: U512 = vm_state.stack.pop()?.into();
-            let u2: U512 = vm_state.stack.pop()?.into();
+        (s0 % s1).into()
+    };
 
-            let result = if u2 == U512::zero() {
-                U256::zero()
-            } else {
-                let value = (u0 * u1) % u2;
+    vm_state.stack.push(result)?;
 
-                U256::try_from(value)
-                    .expect("We applied (mod 256_bits_value), so the result fits in 256 bits")
-            };
+    Ok(Running)
+}
 
-            vm_state.stack.push(result)?;
+fn addmod_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
+    let u2 = vm_state.stack.pop()?;
 
-            Ok(Running)
-        }
-        Opcode::EXP => {
-            let u0 = vm_state.stack.pop()?;
-            let u1 = vm_state.stack.pop()?;
+    let result = arithmetic::addmod(u0, u1, u2);
 
-            let (result, _) = u0.overflowing_pow(u1);
+    vm_state.stack.push(result)?;
 
-            vm_state.stack.push(result)?;
+    Ok(Running)
+}
 
-            Ok(Running)
-        }
-        Opcode::SIGNEXTEND => {
-            let u0 = vm_state.stack.pop()?;
-            let u1 = vm_state.stack.pop()?;
+fn mulmod_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
+    let u2 = vm_state.stack.pop()?;
 
-            let value = if u0 < U256::from(32) {
-                let value_bytes = u0 + U256::one();
-                let sig_bit = 256 - 8 * value_bytes.as_usize();
+    let result = arithmetic::mulmod(u0, u1, u2);
 
-                let value_mask = (U256::one() << sig_bit) - U256::one();
+    vm_state.stack.push(result)?;
 
-                let sig = u1.bit(sig_bit);
+    Ok(Running)
+}
 
-                if sig {
-                    u1 | !value_mask
-                } else {
-                    u1 & value_mask
-                }
-            } else {
-                u1
-            };
+fn exp_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
 
-            vm_state.stack.push(value)?;
+    let result = arithmetic::exp(u0, u1);
 
-            Ok(Running)
-        }
-        Opcode::UNRECOGNIZED0C => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZED0D => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZED0E => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZED0F => Err(InvalidOpcode),
-        Opcode::LT => {
-            let u0 = vm_state.stack.pop()?;
-            let u1 = vm_state.stack.pop()?;
+    vm_state.stack.push(result)?;
 
-            let result = if u0 < u1 { U256::one() } else { U256::zero() };
+    Ok(Running)
+}
 
-            vm_state.stack.push(result)?;
+fn signextend_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
 
-            Ok(Running)
-        }
-        Opcode::GT => {
-            let u0 = vm_state.stack.pop()?;
-            let u1 = vm_state.stack.pop()?;
+    let value = if u0 < U256::from(32) {
+        let value_bytes = u0 + U256::one();
+        let sig_bit = 256 - 8 * value_bytes.as_usize();
 
-            let result = if u0 > u1 { U256::one() } else { U256::zero() };
+        let value_mask = (U256::one() << sig_bit) - U256::one();
 
-            vm_state.stack.push(result)?;
+        let sig = u1.bit(sig_bit);
 
-            Ok(Running)
+        if sig {
+            u1 | !value_mask
+        } else {
+            u1 & value_mask
         }
-        Opcode::SLT => {
-            let u0 = vm_state.stack.pop()?;
-            let u1 = vm_state.stack.pop()?;
+    } else {
+        u1
+    };
 
-            let s0: I256 = u0.into();
-            let s1: I256 = u1.into();
+    vm_state.stack.push(value)?;
 
-            let result = if s0 < s1 { U256::one() } else { U256::zero() };
+    Ok(Running)
+}
 
-            vm_state.stack.push(result)?;
+fn lt_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
 
-            Ok(Running)
-        }
-        Opcode::SGT => {
-            let u0 = vm_state.stack.pop()?;
-            let u1 = vm_state.stack.pop()?;
+    let result = if u0 < u1 { U256::one() } else { U256::zero() };
 
-            let s0: I256 = u0.into();
-            let s1: I256 = u1.into();
+    vm_state.stack.push(result)?;
 
-            let result = if s0 > s1 { U256::one() } else { U256::zero() };
+    Ok(Running)
+}
 
-            vm_state.stack.push(result)?;
+fn gt_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
 
-            Ok(Running)
-        }
-        Opcode::EQ => {
-            let u0 = vm_state.stack.pop()?;
-            let u1 = vm_state.stack.pop()?;
+    let result = if u0 > u1 { U256::one() } else { U256::zero() };
 
-            let result = if u0 == u1 { U256::one() } else { U256::zero() };
+    vm_state.stack.push(result)?;
 
-            vm_state.stack.push(result)?;
+    Ok(Running)
+}
 
-            Ok(Running)
-        }
-        Opcode::ISZERO => {
-            let u0 = vm_state.stack.pop()?;
+fn slt_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
 
-            let result = if u0 == U256::zero() {
-                U256::one()
-            } else {
-                U256::zero()
-            };
+    let s0: I256 = u0.into();
+    let s1: I256 = u1.into();
 
-            vm_state.stack.push(result)?;
+    let result = if s0 < s1 { U256::one() } else { U256::zero() };
 
-            Ok(Running)
-        }
-        Opcode::AND => {
-            let u0 = vm_state.stack.pop()?;
-            let u1 = vm_state.stack.pop()?;
+    vm_state.stack.push(result)?;
 
-            let result = u0 & u1;
+    Ok(Running)
+}
 
-            vm_state.stack.push(result)?;
+fn sgt_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
 
-            Ok(Running)
-        }
-        Opcode::OR => {
-            let u0 = vm_state.stack.pop()?;
-            let u1 = vm_state.stack.pop()?;
+    let s0: I256 = u0.into();
+    let s1: I256 = u1.into();
 
-            let result = u0 | u1;
+    let result = if s0 > s1 { U256::one() } else { U256::zero() };
 
-            vm_state.stack.push(result)?;
+    vm_state.stack.push(result)?;
 
-            Ok(Running)
-        }
-        Opcode::XOR => {
-            let u0 = vm_state.stack.pop()?;
-            let u1 = vm_state.stack.pop()?;
+    Ok(Running)
+}
 
-            let result = u0 | u1;
+fn eq_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
 
-            vm_state.stack.push(result)?;
+    let result = if u0 == u1 { U256::one() } else { U256::zero() };
 
-            Ok(Running)
-        }
-        Opcode::NOT => {
-            let u0 = vm_state.stack.pop()?;
+    vm_state.stack.push(result)?;
 
-            let result = !u0;
+    Ok(Running)
+}
 
-            vm_state.stack.push(result)?;
+fn iszero_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
 
-            Ok(Running)
-        }
-        Opcode::BYTE => {
-            let u0 = vm_state.stack.pop()?;
-            let u1 = vm_state.stack.pop()?;
+    let result = if u0 == U256::zero() {
+        U256::one()
+    } else {
+        U256::zero()
+    };
 
-            let result = if u0 > U256::from(31) {
-                U256::zero()
-            } else {
-                (u1 >> (U256::from(31) - u0) * 8) & U256::from(0xFF)
-            };
+    vm_state.stack.push(result)?;
 
-            vm_state.stack.push(result)?;
+    Ok(Running)
+}
 
-            Ok(Running)
-        }
-        Opcode::SHL => {
-            let u0 = vm_state.stack.pop()?;
-            let u1 = vm_state.stack.pop()?;
+fn and_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
 
-            let result = u1 << u0;
+    let result = u0 & u1;
 
-            vm_state.stack.push(result)?;
+    vm_state.stack.push(result)?;
 
-            Ok(Running)
-        }
-        Opcode::SHR => {
-            let u0 = vm_state.stack.pop()?;
-            let u1 = vm_state.stack.pop()?;
+    Ok(Running)
+}
 
-            let result = u1 >> u0;
+fn or_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
 
-            vm_state.stack.push(result)?;
+    let result = u0 | u1;
 
-            Ok(Running)
-        }
-        Opcode::SAR => {
-            let u0 = vm_state.stack.pop()?;
-            let u1 = vm_state.stack.pop()?;
-
-            // This was heavily inspired by sorpass
-            let result = if u1 == U256::zero() {
-                U256::zero()
-            } else if u0 >= U256::from(256) {
-                if u1 > U256::zero() {
-                    U256::zero()
-                } else {
-                    I256(Sign::Minus, U256::one()).into()
-                }
-            } else {
-                let I256(sig, value) = I256::from(u1);
-
-                if sig == Sign::Plus {
-                    value >> u0
-                } else {
-                    let shift = u0.as_usize();
-                    let shifted = ((value.overflowing_sub(U256::one()).0) >> shift)
-                        .overflowing_add(U256::one())
-                        .0;
-                    I256(Sign::Minus, shifted).into()
-                }
-            };
-
-            vm_state.stack.push(result)?;
-
-            Ok(Running)
-        }
-        Opcode::UNRECOGNIZED1E => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZED1F => Err(InvalidOpcode),
-        Opcode::SHA3 => {
-            let u0 = vm_state.stack.pop()?;
-            let u1 = vm_state.stack.pop()?;
+    vm_state.stack.push(result)?;
 
-            ensure_offset_and_length_fit_usize(u0, u1)?;
-            let offset = u0.as_usize();
-            let length = u1.as_usize();
+    Ok(Running)
+}
 
-            let mut keccak = Keccak256::new();
+fn xor_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
 
-            if offset >= vm_state.memory.size() {
-                if length >= MEMORY_LIMIT {
-                    return Err(OutOfGas);
-                }
+    let result = u0 | u1;
 
-                let data = vec![0; length];
+    vm_state.stack.push(result)?;
 
-                keccak
-                    .write(data.as_slice())
-                    .expect("Keccak's write should never fail");
-            } else {
-                let data = vm_state.memory.read(offset, length)?;
-                keccak
-                    .write(data)
-                    .expect("Keccak's write should never fail");
-            };
+    Ok(Running)
+}
 
-            let hash = keccak.finalize();
+fn not_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
 
-            let result = U256::from(hash.as_slice());
+    let result = !u0;
 
-            vm_state.stack.push(result)?;
+    vm_state.stack.push(result)?;
 
-            Ok(Running)
-        }
-        Opcode::UNRECOGNIZED21 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZED22 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZED23 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZED24 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZED25 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZED26 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZED27 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZED28 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZED29 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZED2A => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZED2B => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZED2C => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZED2D => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZED2E => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZED2F => Err(InvalidOpcode),
-        Opcode::ADDRESS => {
-            let address = address_to_u256(&call_context.contract_address);
-
-            vm_state.stack.push(address)?;
-
-            Ok(Running)
-        }
-        Opcode::BALANCE => unsupported_opcode_handler(Opcode::BALANCE),
-        Opcode::ORIGIN => {
-            let address = address_to_u256(&call_context.origin_address);
+    Ok(Running)
+}
 
-            vm_state.stack.push(address)?;
+fn byte_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
 
-            Ok(Running)
-        }
-        Opcode::CALLER => {
-            let address = address_to_u256(&call_context.caller_address);
+    let result = if u0 > U256::from(31) {
+        U256::zero()
+    } else {
+        (u1 >> (U256::from(31) - u0) * 8) & U256::from(0xFF)
+    };
 
-            vm_state.stack.push(address)?;
+    vm_state.stack.push(result)?;
 
-            Ok(Running)
-        }
-        Opcode::CALLVALUE => {
-            vm_state.stack.push(call_context.value)?;
+    Ok(Running)
+}
 
-            Ok(Running)
-        }
-        Opcode::CALLDATALOAD => {
-            let u0 = vm_state.stack.pop()?;
+fn shl_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
 
-            let value = if u0 > U256::from(usize::max_value()) {
-                U256::zero()
-            } else {
-                let data = get_slice(call_context.calldata, u0.as_usize(), 32);
-                U256::from(data)
-            };
+    let result = u1 << u0;
 
-            vm_state.stack.push(value)?;
+    vm_state.stack.push(result)?;
 
-            Ok(Running)
-        }
-        Opcode::CALLDATASIZE => {
-            let size = call_context.calldata.len();
+    Ok(Running)
+}
 
-            vm_state.stack.push(U256::from(size))?;
+fn shr_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
 
-            Ok(Running)
-        }
-        Opcode::CALLDATACOPY => data_copy_handler(
-            &mut vm_state.stack,
-            &mut vm_state.memory,
-            call_context.calldata,
-        ),
-        Opcode::CODESIZE => {
-            let size = bytecode.size();
+    let result = u1 >> u0;
 
-            vm_state.stack.push(U256::from(size))?;
+    vm_state.stack.push(result)?;
 
-            Ok(Running)
+    Ok(Running)
+}
+
+fn sar_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
+
+    // This was heavily inspired by sorpass
+    let result = if u1 == U256::zero() {
+        U256::zero()
+    } else if u0 >= U256::from(256) {
+        if u1 > U256::zero() {
+            U256::zero()
+        } else {
+            I256(Sign::Minus, U256::one()).into()
         }
-        Opcode::CODECOPY => data_copy_handler(
-            &mut vm_state.stack,
-            &mut vm_state.memory,
-            bytecode.as_bytes(),
-        ),
-        Opcode::GASPRICE => {
-            vm_state.stack.push(call_context.gas_price)?;
-
-            Ok(Running)
+    } else {
+        let I256(sig, value) = I256::from(u1);
+
+        if sig == Sign::Plus {
+            value >> u0
+        } else {
+            let shift = u0.as_usize();
+            let shifted = ((value.overflowing_sub(U256::one()).0) >> shift)
+                .overflowing_add(U256::one())
+                .0;
+            I256(Sign::Minus, shifted).into()
         }
-        Opcode::EXTCODESIZE => unsupported_opcode_handler(Opcode::EXTCODESIZE),
-        Opcode::EXTCODECOPY => unsupported_opcode_handler(Opcode::EXTCODECOPY),
-        Opcode::RETURNDATASIZE => {
-            let size = vm_state.return_data.len();
+    };
 
-            vm_state.stack.push(U256::from(size))?;
+    vm_state.stack.push(result)?;
 
-            Ok(Running)
-        }
-        Opcode::RETURNDATACOPY => data_copy_handler(
-            &mut vm_state.stack,
-            &mut vm_state.memory,
-            vm_state.return_data.as_slice(),
-        ),
-        Opcode::EXTCODEHASH => unsupported_opcode_handler(Opcode::EXTCODEHASH),
-        Opcode::BLOCKHASH => unsupported_opcode_handler(Opcode::BLOCKHASH),
-        Opcode::COINBASE => {
-            let address = address_to_u256(&block_context.coinbase_address);
-
-            vm_state.stack.push(address)?;
-
-            Ok(Running)
-        }
-        Opcode::TIMESTAMP => {
-            let value = U256::from(block_context.timestamp);
+    Ok(Running)
+}
 
-            vm_state.stack.push(value)?;
+fn sha3_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
 
-            Ok(Running)
-        }
-        Opcode::NUMBER => {
-            let value = U256::from(block_context.number);
+    ensure_offset_and_length_fit_usize(u0, u1)?;
+    let offset = u0.as_usize();
+    let length = u1.as_usize();
 
-            vm_state.stack.push(value)?;
+    let mut keccak = Keccak256::new();
 
-            Ok(Running)
+    if offset >= vm_state.memory.size() {
+        if length >= MEMORY_LIMIT {
+            return Err(OutOfGas {
+                requested_memory_size: u0 + u1,
+            });
         }
-        Opcode::DIFFICULTY => {
-            let value = U256::from(block_context.difficulty);
 
-            vm_state.stack.push(value)?;
+        let data = vec![0; length];
 
-            Ok(Running)
-        }
-        Opcode::GASLIMIT => {
-            let value = U256::from(block_context.gas_limit);
+        keccak
+            .write(data.as_slice())
+            .expect("Keccak's write should never fail");
+    } else {
+        let data = vm_state.memory.read(offset, length)?;
+        keccak
+            .write(data)
+            .expect("Keccak's write should never fail");
+    };
 
-            vm_state.stack.push(value)?;
+    let hash = keccak.finalize();
 
-            Ok(Running)
-        }
-        Opcode::CHAINID => {
-            let value = U256::from(block_context.chain_id);
+    let result = U256::from(hash.as_slice());
 
-            vm_state.stack.push(value)?;
+    vm_state.stack.push(result)?;
 
-            Ok(Running)
-        }
-        Opcode::UNRECOGNIZED47 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZED48 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZED49 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZED4A => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZED4B => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZED4C => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZED4D => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZED4E => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZED4F => Err(InvalidOpcode),
-        Opcode::POP => {
-            vm_state.stack.pop()?;
-
-            Ok(Running)
-        }
-        Opcode::MLOAD => {
-            let u0 = vm_state.stack.pop()?;
+    Ok(Running)
+}
 
-            ensure_fits_usize(u0)?;
-            let offset = u0.as_usize();
+fn address_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let address = address_to_u256(&call_context.contract_address);
 
-            let data = vm_state.memory.read(offset, 32)?;
-            let value = U256::from(data);
+    vm_state.stack.push(address)?;
 
-            vm_state.stack.push(value)?;
+    Ok(Running)
+}
 
-            Ok(Running)
-        }
-        Opcode::MSTORE => {
-            let u0 = vm_state.stack.pop()?;
-            let u1 = vm_state.stack.pop()?;
+fn balance_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
 
-            let mut bytes = [0; 32];
-            u1.to_big_endian(&mut bytes);
+    let balance = call_context.host.balance(&u256_to_address(u0));
 
-            ensure_fits_usize(u0)?;
-            vm_state.memory.write(u0.as_usize(), 32, &bytes)?;
+    vm_state.stack.push(balance)?;
 
-            Ok(Running)
-        }
-        Opcode::MSTORE8 => {
-            let u0 = vm_state.stack.pop()?;
-            let u1 = vm_state.stack.pop()?;
+    Ok(Running)
+}
 
-            let byte = u1.byte(0);
+fn origin_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let address = address_to_u256(&call_context.origin_address);
 
-            ensure_fits_usize(u0)?;
-            vm_state.memory.write(u0.as_usize(), 1, &[byte])?;
+    vm_state.stack.push(address)?;
 
-            Ok(Running)
-        }
-        Opcode::SLOAD => {
-            let u0 = vm_state.stack.pop()?;
+    Ok(Running)
+}
 
-            let default = U256::zero();
-            let value = vm_state.storage.get(&u0).unwrap_or(&default);
+fn caller_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let address = address_to_u256(&call_context.caller_address);
 
-            vm_state.stack.push(*value)?;
+    vm_state.stack.push(address)?;
 
-            Ok(Running)
-        }
-        Opcode::SSTORE => {
-            let u0 = vm_state.stack.pop()?;
-            let u1 = vm_state.stack.pop()?;
+    Ok(Running)
+}
 
-            vm_state.storage.insert(u0, u1);
+fn callvalue_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    vm_state.stack.push(call_context.value)?;
 
-            Ok(Running)
-        }
-        Opcode::JUMP => {
-            let u0 = vm_state.stack.pop()?;
+    Ok(Running)
+}
 
-            jump(vm_state, bytecode, u0)
-        }
-        Opcode::JUMPI => {
-            let u0 = vm_state.stack.pop()?;
-            let u1 = vm_state.stack.pop()?;
+fn calldataload_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
 
-            if u1 != U256::zero() {
-                return jump(vm_state, bytecode, u0);
-            }
+    let value = if u0 > U256::from(usize::max_value()) {
+        U256::zero()
+    } else {
+        let data = get_slice(call_context.calldata, u0.as_usize(), 32);
+        U256::from(data)
+    };
 
-            Ok(Running)
-        }
-        Opcode::PC => {
-            let pc = U256::from(vm_state.pc - 1);
+    vm_state.stack.push(value)?;
 
-            vm_state.stack.push(pc)?;
+    Ok(Running)
+}
 
-            Ok(Running)
-        }
-        Opcode::MSIZE => {
-            let size = U256::from(vm_state.memory.size());
+fn calldatasize_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let size = call_context.calldata.len();
 
-            vm_state.stack.push(size)?;
+    vm_state.stack.push(U256::from(size))?;
 
-            Ok(Running)
-        }
-        Opcode::GAS => unsupported_opcode_handler(Opcode::GAS),
-        Opcode::JUMPDEST => Ok(Running),
-        Opcode::UNRECOGNIZED5C => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZED5D => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZED5E => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZED5F => Err(InvalidOpcode),
-        Opcode::PUSH1 => push_handler(vm_state, bytecode, 1),
-        Opcode::PUSH2 => push_handler(vm_state, bytecode, 2),
-        Opcode::PUSH3 => push_handler(vm_state, bytecode, 3),
-        Opcode::PUSH4 => push_handler(vm_state, bytecode, 4),
-        Opcode::PUSH5 => push_handler(vm_state, bytecode, 5),
-        Opcode::PUSH6 => push_handler(vm_state, bytecode, 6),
-        Opcode::PUSH7 => push_handler(vm_state, bytecode, 7),
-        Opcode::PUSH8 => push_handler(vm_state, bytecode, 8),
-        Opcode::PUSH9 => push_handler(vm_state, bytecode, 9),
-        Opcode::PUSH10 => push_handler(vm_state, bytecode, 10),
-        Opcode::PUSH11 => push_handler(vm_state, bytecode, 11),
-        Opcode::PUSH12 => push_handler(vm_state, bytecode, 12),
-        Opcode::PUSH13 => push_handler(vm_state, bytecode, 13),
-        Opcode::PUSH14 => push_handler(vm_state, bytecode, 14),
-        Opcode::PUSH15 => push_handler(vm_state, bytecode, 15),
-        Opcode::PUSH16 => push_handler(vm_state, bytecode, 16),
-        Opcode::PUSH17 => push_handler(vm_state, bytecode, 17),
-        Opcode::PUSH18 => push_handler(vm_state, bytecode, 18),
-        Opcode::PUSH19 => push_handler(vm_state, bytecode, 19),
-        Opcode::PUSH20 => push_handler(vm_state, bytecode, 20),
-        Opcode::PUSH21 => push_handler(vm_state, bytecode, 21),
-        Opcode::PUSH22 => push_handler(vm_state, bytecode, 22),
-        Opcode::PUSH23 => push_handler(vm_state, bytecode, 23),
-        Opcode::PUSH24 => push_handler(vm_state, bytecode, 24),
-        Opcode::PUSH25 => push_handler(vm_state, bytecode, 25),
-        Opcode::PUSH26 => push_handler(vm_state, bytecode, 26),
-        Opcode::PUSH27 => push_handler(vm_state, bytecode, 27),
-        Opcode::PUSH28 => push_handler(vm_state, bytecode, 28),
-        Opcode::PUSH29 => push_handler(vm_state, bytecode, 29),
-        Opcode::PUSH30 => push_handler(vm_state, bytecode, 30),
-        Opcode::PUSH31 => push_handler(vm_state, bytecode, 31),
-        Opcode::PUSH32 => push_handler(vm_state, bytecode, 32),
-        Opcode::DUP1 => dup_handler(vm_state, 1),
-        Opcode::DUP2 => dup_handler(vm_state, 2),
-        Opcode::DUP3 => dup_handler(vm_state, 3),
-        Opcode::DUP4 => dup_handler(vm_state, 4),
-        Opcode::DUP5 => dup_handler(vm_state, 5),
-        Opcode::DUP6 => dup_handler(vm_state, 6),
-        Opcode::DUP7 => dup_handler(vm_state, 7),
-        Opcode::DUP8 => dup_handler(vm_state, 8),
-        Opcode::DUP9 => dup_handler(vm_state, 9),
-        Opcode::DUP10 => dup_handler(vm_state, 10),
-        Opcode::DUP11 => dup_handler(vm_state, 11),
-        Opcode::DUP12 => dup_handler(vm_state, 12),
-        Opcode::DUP13 => dup_handler(vm_state, 13),
-        Opcode::DUP14 => dup_handler(vm_state, 14),
-        Opcode::DUP15 => dup_handler(vm_state, 15),
-        Opcode::DUP16 => dup_handler(vm_state, 16),
-        Opcode::SWAP1 => swap_handler(vm_state, 1),
-        Opcode::SWAP2 => swap_handler(vm_state, 2),
-        Opcode::SWAP3 => swap_handler(vm_state, 3),
-        Opcode::SWAP4 => swap_handler(vm_state, 4),
-        Opcode::SWAP5 => swap_handler(vm_state, 5),
-        Opcode::SWAP6 => swap_handler(vm_state, 6),
-        Opcode::SWAP7 => swap_handler(vm_state, 7),
-        Opcode::SWAP8 => swap_handler(vm_state, 8),
-        Opcode::SWAP9 => swap_handler(vm_state, 9),
-        Opcode::SWAP10 => swap_handler(vm_state, 10),
-        Opcode::SWAP11 => swap_handler(vm_state, 11),
-        Opcode::SWAP12 => swap_handler(vm_state, 12),
-        Opcode::SWAP13 => swap_handler(vm_state, 13),
-        Opcode::SWAP14 => swap_handler(vm_state, 14),
-        Opcode::SWAP15 => swap_handler(vm_state, 15),
-        Opcode::SWAP16 => swap_handler(vm_state, 16),
-        Opcode::LOG0 => unsupported_opcode_handler(Opcode::LOG0),
-        Opcode::LOG1 => unsupported_opcode_handler(Opcode::LOG1),
-        Opcode::LOG2 => unsupported_opcode_handler(Opcode::LOG2),
-        Opcode::LOG3 => unsupported_opcode_handler(Opcode::LOG3),
-        Opcode::LOG4 => unsupported_opcode_handler(Opcode::LOG4),
-        Opcode::UNRECOGNIZEDA5 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDA6 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDA7 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDA8 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDA9 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDAA => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDAB => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDAC => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDAD => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDAE => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDAF => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDB0 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDB1 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDB2 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDB3 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDB4 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDB5 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDB6 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDB7 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDB8 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDB9 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDBA => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDBB => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDBC => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDBD => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDBE => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDBF => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDC0 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDC1 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDC2 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDC3 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDC4 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDC5 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDC6 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDC7 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDC8 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDC9 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDCA => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDCB => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDCC => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDCD => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDCE => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDCF => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDD0 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDD1 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDD2 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDD3 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDD4 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDD5 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDD6 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDD7 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDD8 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDD9 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDDA => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDDB => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDDC => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDDD => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDDE => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDDF => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDE0 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDE1 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDE2 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDE3 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDE4 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDE5 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDE6 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDE7 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDE8 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDE9 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDEA => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDEB => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDEC => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDED => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDEE => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDEF => Err(InvalidOpcode),
-        Opcode::CREATE => unsupported_opcode_handler(Opcode::CREATE),
-        Opcode::CALL => unsupported_opcode_handler(Opcode::CALL),
-        Opcode::CALLCODE => unsupported_opcode_handler(Opcode::CALLCODE),
-        Opcode::RETURN => {
-            let u0 = vm_state.stack.pop()?;
-            let u1 = vm_state.stack.pop()?;
-
-            ensure_offset_and_length_fit_usize(u0, u1)?;
-            let offset = u0.as_usize();
-            let length = u1.as_usize();
-
-            let data = vm_state.memory.read(offset, length)?;
-
-            vm_state.return_data.extend_from_slice(data);
-
-            Ok(Halted)
-        }
-        Opcode::DELEGATECALL => unsupported_opcode_handler(Opcode::DELEGATECALL),
-        Opcode::CREATE2 => unsupported_opcode_handler(Opcode::CREATE2),
-        Opcode::UNRECOGNIZEDF6 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDF7 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDF8 => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDF9 => Err(InvalidOpcode),
-        Opcode::STATICCALL => unsupported_opcode_handler(Opcode::STATICCALL),
-        Opcode::UNRECOGNIZEDFB => Err(InvalidOpcode),
-        Opcode::UNRECOGNIZEDFC => Err(InvalidOpcode),
-        Opcode::REVERT => {
-            let u0 = vm_state.stack.pop()?;
-            let u1 = vm_state.stack.pop()?;
-
-            ensure_offset_and_length_fit_usize(u0, u1)?;
-            let offset = u0.as_usize();
-            let length = u1.as_usize();
-
-            let data = vm_state.memory.read(offset, length)?;
-
-            vm_state.return_data.extend_from_slice(data);
-
-            Err(Revert)
-        }
-        Opcode::INVALID => Err(InvalidOpcode),
-        Opcode::SELFDESTRUCT => Ok(Halted),
+    Ok(Running)
+}
+
+fn calldatacopy_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    data_copy_handler(
+        &mut vm_state.stack,
+        &mut vm_state.memory,
+        call_context.calldata,
+    )
+}
+
+fn codesize_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let size = bytecode.size();
+
+    vm_state.stack.push(U256::from(size))?;
+
+    Ok(Running)
+}
+
+fn codecopy_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    data_copy_handler(
+        &mut vm_state.stack,
+        &mut vm_state.memory,
+        bytecode.as_bytes(),
+    )
+}
+
+fn gasprice_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    vm_state.stack.push(call_context.gas_price)?;
+
+    Ok(Running)
+}
+
+fn extcodesize_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+
+    let size = U256::from(call_context.host.code(&u256_to_address(u0)).len());
+
+    vm_state.stack.push(size)?;
+
+    Ok(Running)
+}
+
+fn extcodecopy_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+
+    let code = call_context.host.code(&u256_to_address(u0));
+
+    data_copy_handler(&mut vm_state.stack, &mut vm_state.memory, &code)
+}
+
+fn returndatasize_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let size = vm_state.return_data.len();
+
+    vm_state.stack.push(U256::from(size))?;
+
+    Ok(Running)
+}
+
+fn returndatacopy_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    data_copy_handler(
+        &mut vm_state.stack,
+        &mut vm_state.memory,
+        vm_state.return_data.as_slice(),
+    )
+}
+
+fn extcodehash_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+
+    let hash = call_context.host.code_hash(&u256_to_address(u0));
+
+    vm_state
+        .stack
+        .push(U256::from_big_endian(hash.as_bytes()))?;
+
+    Ok(Running)
+}
+
+fn blockhash_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    call_context: &CallContext,
+    block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+
+    // Only the hashes of the 256 most recent blocks are available
+    let current = U256::from(block_context.number);
+    let is_available = u0 < current && current - u0 <= U256::from(256);
+
+    let hash = if is_available {
+        call_context.host.block_hash(u0.as_u32())
+    } else {
+        H256::zero()
+    };
+
+    vm_state
+        .stack
+        .push(U256::from_big_endian(hash.as_bytes()))?;
+
+    Ok(Running)
+}
+
+fn coinbase_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    block_context: &BlockContext,
+) -> StepResult {
+    let address = address_to_u256(&block_context.coinbase_address);
+
+    vm_state.stack.push(address)?;
+
+    Ok(Running)
+}
+
+fn timestamp_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    block_context: &BlockContext,
+) -> StepResult {
+    let value = U256::from(block_context.timestamp);
+
+    vm_state.stack.push(value)?;
+
+    Ok(Running)
+}
+
+fn number_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    block_context: &BlockContext,
+) -> StepResult {
+    let value = U256::from(block_context.number);
+
+    vm_state.stack.push(value)?;
+
+    Ok(Running)
+}
+
+fn difficulty_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    block_context: &BlockContext,
+) -> StepResult {
+    let value = U256::from(block_context.difficulty);
+
+    vm_state.stack.push(value)?;
+
+    Ok(Running)
+}
+
+fn gaslimit_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    block_context: &BlockContext,
+) -> StepResult {
+    let value = U256::from(block_context.gas_limit);
+
+    vm_state.stack.push(value)?;
+
+    Ok(Running)
+}
+
+fn chainid_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    block_context: &BlockContext,
+) -> StepResult {
+    let value = U256::from(block_context.chain_id);
+
+    vm_state.stack.push(value)?;
+
+    Ok(Running)
+}
+
+fn pop_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    vm_state.stack.pop()?;
+
+    Ok(Running)
+}
+
+fn mload_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+
+    ensure_offset_and_length_fit_usize(u0, U256::from(32))?;
+    let offset = u0.as_usize();
+
+    let data = vm_state.memory.read(offset, 32)?;
+    let value = U256::from(data);
+
+    vm_state.stack.push(value)?;
+
+    Ok(Running)
+}
+
+fn mstore_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
+
+    let mut bytes = [0; 32];
+    u1.to_big_endian(&mut bytes);
+
+    ensure_offset_and_length_fit_usize(u0, U256::from(32))?;
+    vm_state.memory.write(u0.as_usize(), 32, &bytes)?;
+
+    Ok(Running)
+}
+
+fn mstore8_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
+
+    let byte = u1.byte(0);
+
+    ensure_offset_and_length_fit_usize(u0, U256::one())?;
+    vm_state.memory.write(u0.as_usize(), 1, &[byte])?;
+
+    Ok(Running)
+}
+
+fn sload_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+
+    // Slots that weren't written during this execution are read from the host
+    let value = match vm_state.storage.get(&u0) {
+        Some(value) => *value,
+        None => call_context
+            .host
+            .storage(&call_context.contract_address, &u0),
+    };
+
+    vm_state.stack.push(value)?;
+
+    Ok(Running)
+}
+
+fn sstore_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
+
+    vm_state.storage.insert(u0, u1);
+
+    Ok(Running)
+}
+
+fn jump_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+
+    jump(vm_state, bytecode, u0)
+}
+
+fn jumpi_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
+
+    if u1 != U256::zero() {
+        return jump(vm_state, bytecode, u0);
     }
+
+    Ok(Running)
+}
+
+fn pc_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let pc = U256::from(vm_state.pc - 1);
+
+    vm_state.stack.push(pc)?;
+
+    Ok(Running)
+}
+
+fn msize_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let size = U256::from(vm_state.memory.size());
+
+    vm_state.stack.push(size)?;
+
+    Ok(Running)
+}
+
+fn jumpdest_handler(
+    _opcode: Opcode,
+    _vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    Ok(Running)
+}
+
+fn return_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
+
+    ensure_offset_and_length_fit_usize(u0, u1)?;
+    let offset = u0.as_usize();
+    let length = u1.as_usize();
+
+    let data = vm_state.memory.read(offset, length)?;
+
+    vm_state.return_data.extend_from_slice(data);
+
+    Ok(Halted(HaltReason::Return))
+}
+
+fn revert_handler(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
+
+    ensure_offset_and_length_fit_usize(u0, u1)?;
+    let offset = u0.as_usize();
+    let length = u1.as_usize();
+
+    let data = vm_state.memory.read(offset, length)?;
+
+    vm_state.return_data.extend_from_slice(data);
+
+    Err(Revert)
+}
+
+fn log_handler(
+    opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let topics_count = (opcode as u8 - Opcode::LOG0 as u8) as usize;
+
+    let u0 = vm_state.stack.pop()?;
+    let u1 = vm_state.stack.pop()?;
+
+    let mut topics = Vec::with_capacity(topics_count);
+    for _ in 0..topics_count {
+        let mut topic = H256::zero();
+        vm_state.stack.pop()?.to_big_endian(topic.as_bytes_mut());
+        topics.push(topic);
+    }
+
+    ensure_offset_and_length_fit_usize(u0, u1)?;
+    let offset = u0.as_usize();
+    let length = u1.as_usize();
+
+    let data = vm_state.memory.read(offset, length)?.to_vec();
+
+    vm_state.logs.push(Log {
+        address: call_context.contract_address,
+        topics,
+        data,
+    });
+
+    Ok(Running)
+}
+
+fn selfdestruct_handler(
+    _opcode: Opcode,
+    _vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    Ok(Halted(HaltReason::SelfDestruct))
 }
 
 fn jump(vm_state: &mut VmState, bytecode: &Bytecode, dest: U256) -> StepResult {
     if dest >= U256::from(bytecode.size()) {
-        return Err(InvalidJump);
+        return Err(InvalidJump { destination: dest });
     }
 
     let pc = dest.as_usize();
     if !bytecode.is_jumpdest(pc) {
-        return Err(InvalidJump);
+        return Err(InvalidJump { destination: dest });
     }
 
     vm_state.pc = pc;
@@ -856,7 +1347,14 @@ fn jump(vm_state: &mut VmState, bytecode: &Bytecode, dest: U256) -> StepResult {
     Ok(Running)
 }
 
-fn push_handler(vm_state: &mut VmState, bytecode: &Bytecode, size: usize) -> StepResult {
+fn push_handler(
+    opcode: Opcode,
+    vm_state: &mut VmState,
+    bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let size = (opcode as u8 - Opcode::PUSH1 as u8 + 1) as usize;
     let value = bytecode.read_push_value(vm_state.pc, size);
     vm_state.stack.push(value)?;
 
@@ -865,24 +1363,54 @@ fn push_handler(vm_state: &mut VmState, bytecode: &Bytecode, size: usize) -> Ste
     Ok(Running)
 }
 
-fn dup_handler(vm_state: &mut VmState, dup_number: usize) -> StepResult {
+fn dup_handler(
+    opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let dup_number = (opcode as u8 - Opcode::DUP1 as u8 + 1) as usize;
     let value = vm_state.stack.read(dup_number - 1)?;
     vm_state.stack.push(value)?;
 
     Ok(Running)
 }
 
-fn swap_handler(vm_state: &mut VmState, swap_number: usize) -> StepResult {
+fn swap_handler(
+    opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let swap_number = (opcode as u8 - Opcode::SWAP1 as u8 + 1) as usize;
     vm_state.stack.swap_with_top(swap_number)?;
 
     Ok(Running)
 }
 
-fn unsupported_opcode_handler(opcode: Opcode) -> StepResult {
-    Err(UnsupportedOpcode(opcode))
+fn unsupported_opcode_handler(
+    _opcode: Opcode,
+    _vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    Err(UnsupportedOpcode)
+}
+
+fn invalid_opcode_handler(
+    _opcode: Opcode,
+    _vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    Err(InvalidOpcode)
 }
 
-fn get_slice(data: &[u8], start: usize, length: usize) -> &[u8] {
+pub fn get_slice(data: &[u8], start: usize, length: usize) -> &[u8] {
     let data_len = data.len();
     if start >= data_len {
         return &[];
@@ -896,17 +1424,24 @@ fn address_to_u256(address: &Address) -> U256 {
     U256::from_big_endian(address.as_bytes())
 }
 
-fn data_copy_handler(stack: &mut Stack, memory: &mut Memory, data: &[u8]) -> StepResult {
+fn u256_to_address(value: U256) -> Address {
+    let mut bytes = [0; 32];
+    value.to_big_endian(&mut bytes);
+
+    Address::from_slice(&bytes[12..])
+}
+
+pub fn data_copy_handler(stack: &mut Stack, memory: &mut Memory, data: &[u8]) -> StepResult {
     let u0 = stack.pop()?;
     let u1 = stack.pop()?;
     let u2 = stack.pop()?;
 
-    ensure_fits_usize(u0)?;
+    ensure_offset_and_length_fit_usize(u0, u2)?;
 
     let u0_usize = u0.as_usize();
     let u2_usize = u2.as_usize();
 
-    let data = if u1 > U256::from(usize::max_value()) {
+    let data = if u1 > U256::from(usize::MAX) {
         // We don't really need the data here, we use an empty slice
         // and write will take care of this
         &[]
@@ -922,20 +1457,20 @@ fn data_copy_handler(stack: &mut Stack, memory: &mut Memory, data: &[u8]) -> Ste
     Ok(Running)
 }
 
-fn ensure_offset_and_length_fit_usize(offset: U256, length: U256) -> Result<(), ExecutionError> {
+// The memory can't be larger than usize::MAX, so accessing a range that ends after it runs out of
+// gas
+fn ensure_offset_and_length_fit_usize(offset: U256, length: U256) -> Result<(), StepError> {
     let (size, overflow) = offset.overflowing_add(length);
     if overflow {
-        return Err(OutOfGas);
+        return Err(OutOfGas {
+            requested_memory_size: U256::MAX,
+        });
     }
 
-    ensure_fits_usize(size)?;
-
-    Ok(())
-}
-
-fn ensure_fits_usize(number: U256) -> Result<(), ExecutionError> {
-    if number > U256::from(usize::max_value()) {
-        return Err(OutOfGas);
+    if size > U256::from(usize::MAX) {
+        return Err(OutOfGas {
+            requested_memory_size: size,
+        });
     }
 
     Ok(())
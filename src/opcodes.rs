@@ -247,10 +247,13 @@ pub enum Opcode {
     UNRECOGNIZEDCE = 0xce,
     UNRECOGNIZEDCF = 0xcf,
 
-    UNRECOGNIZEDD0 = 0xd0,
-    UNRECOGNIZEDD1 = 0xd1,
-    UNRECOGNIZEDD2 = 0xd2,
-    UNRECOGNIZEDD3 = 0xd3,
+    // EOF data section operations. These are invalid in legacy code.
+    DATALOAD = 0xd0,
+    DATALOADN = 0xd1,
+    DATASIZE = 0xd2,
+    DATACOPY = 0xd3,
+
+    // Unallocated
     UNRECOGNIZEDD4 = 0xd4,
     UNRECOGNIZEDD5 = 0xd5,
     UNRECOGNIZEDD6 = 0xd6,
@@ -264,12 +267,15 @@ pub enum Opcode {
     UNRECOGNIZEDDE = 0xde,
     UNRECOGNIZEDDF = 0xdf,
 
-    UNRECOGNIZEDE0 = 0xe0,
-    UNRECOGNIZEDE1 = 0xe1,
-    UNRECOGNIZEDE2 = 0xe2,
-    UNRECOGNIZEDE3 = 0xe3,
-    UNRECOGNIZEDE4 = 0xe4,
-    UNRECOGNIZEDE5 = 0xe5,
+    // EOF control flow operations. These are invalid in legacy code.
+    RJUMP = 0xe0,
+    RJUMPI = 0xe1,
+    RJUMPV = 0xe2,
+    CALLF = 0xe3,
+    RETF = 0xe4,
+    JUMPF = 0xe5,
+
+    // Unallocated
     UNRECOGNIZEDE6 = 0xe6,
     UNRECOGNIZEDE7 = 0xe7,
     UNRECOGNIZEDE8 = 0xe8,
@@ -307,3 +313,210 @@ pub enum Opcode {
     INVALID = 0xfe,
     SELFDESTRUCT = 0xff,
 }
+
+impl Opcode {
+    // Returns how many stack items the opcode consumes, and how many it pushes. The stack effect of
+    // CALLF, RETF and JUMPF depends on the section they call, so they are reported as (0, 0).
+    pub fn stack_io(self) -> (usize, usize) {
+        let n = self as u8;
+
+        if n >= Opcode::PUSH1 as u8 && n <= Opcode::PUSH32 as u8 {
+            return (0, 1);
+        }
+
+        if n >= Opcode::DUP1 as u8 && n <= Opcode::DUP16 as u8 {
+            let depth = (n - Opcode::DUP1 as u8 + 1) as usize;
+            return (depth, depth + 1);
+        }
+
+        if n >= Opcode::SWAP1 as u8 && n <= Opcode::SWAP16 as u8 {
+            let depth = (n - Opcode::SWAP1 as u8 + 2) as usize;
+            return (depth, depth);
+        }
+
+        if n >= Opcode::LOG0 as u8 && n <= Opcode::LOG4 as u8 {
+            return ((n - Opcode::LOG0 as u8 + 2) as usize, 0);
+        }
+
+        match self {
+            Opcode::ADD
+            | Opcode::MUL
+            | Opcode::SUB
+            | Opcode::DIV
+            | Opcode::SDIV
+            | Opcode::MOD
+            | Opcode::SMOD
+            | Opcode::EXP
+            | Opcode::SIGNEXTEND
+            | Opcode::LT
+            | Opcode::GT
+            | Opcode::SLT
+            | Opcode::SGT
+            | Opcode::EQ
+            | Opcode::AND
+            | Opcode::OR
+            | Opcode::XOR
+            | Opcode::BYTE
+            | Opcode::SHL
+            | Opcode::SHR
+            | Opcode::SAR
+            | Opcode::SHA3 => (2, 1),
+            Opcode::ADDMOD | Opcode::MULMOD => (3, 1),
+            Opcode::ISZERO
+            | Opcode::NOT
+            | Opcode::BALANCE
+            | Opcode::CALLDATALOAD
+            | Opcode::EXTCODESIZE
+            | Opcode::EXTCODEHASH
+            | Opcode::BLOCKHASH
+            | Opcode::MLOAD
+            | Opcode::SLOAD
+            | Opcode::DATALOAD => (1, 1),
+            Opcode::ADDRESS
+            | Opcode::ORIGIN
+            | Opcode::CALLER
+            | Opcode::CALLVALUE
+            | Opcode::CALLDATASIZE
+            | Opcode::CODESIZE
+            | Opcode::GASPRICE
+            | Opcode::RETURNDATASIZE
+            | Opcode::COINBASE
+            | Opcode::TIMESTAMP
+            | Opcode::NUMBER
+            | Opcode::DIFFICULTY
+            | Opcode::GASLIMIT
+            | Opcode::CHAINID
+            | Opcode::PC
+            | Opcode::MSIZE
+            | Opcode::GAS
+            | Opcode::DATALOADN
+            | Opcode::DATASIZE => (0, 1),
+            Opcode::CALLDATACOPY | Opcode::CODECOPY | Opcode::RETURNDATACOPY | Opcode::DATACOPY => {
+                (3, 0)
+            }
+            Opcode::EXTCODECOPY => (4, 0),
+            Opcode::POP | Opcode::JUMP | Opcode::RJUMPI | Opcode::RJUMPV | Opcode::SELFDESTRUCT => {
+                (1, 0)
+            }
+            Opcode::MSTORE
+            | Opcode::MSTORE8
+            | Opcode::SSTORE
+            | Opcode::JUMPI
+            | Opcode::RETURN
+            | Opcode::REVERT => (2, 0),
+            Opcode::CREATE => (3, 1),
+            Opcode::CREATE2 => (4, 1),
+            Opcode::CALL | Opcode::CALLCODE => (7, 1),
+            Opcode::DELEGATECALL | Opcode::STATICCALL => (6, 1),
+            _ => (0, 0),
+        }
+    }
+
+    // Returns the part of the opcode's gas cost that doesn't depend on its inputs or the state.
+    // Account and storage accesses are priced as warm ones (EIP-2929).
+    pub fn static_gas(self) -> u64 {
+        let n = self as u8;
+
+        if (n >= Opcode::PUSH1 as u8 && n <= Opcode::PUSH32 as u8)
+            || (n >= Opcode::DUP1 as u8 && n <= Opcode::SWAP16 as u8)
+        {
+            return 3;
+        }
+
+        if n >= Opcode::LOG0 as u8 && n <= Opcode::LOG4 as u8 {
+            return 375 * (n - Opcode::LOG0 as u8 + 1) as u64;
+        }
+
+        match self {
+            Opcode::JUMPDEST => 1,
+            Opcode::ADDRESS
+            | Opcode::ORIGIN
+            | Opcode::CALLER
+            | Opcode::CALLVALUE
+            | Opcode::CALLDATASIZE
+            | Opcode::CODESIZE
+            | Opcode::GASPRICE
+            | Opcode::RETURNDATASIZE
+            | Opcode::COINBASE
+            | Opcode::TIMESTAMP
+            | Opcode::NUMBER
+            | Opcode::DIFFICULTY
+            | Opcode::GASLIMIT
+            | Opcode::CHAINID
+            | Opcode::POP
+            | Opcode::PC
+            | Opcode::MSIZE
+            | Opcode::GAS
+            | Opcode::DATASIZE
+            | Opcode::RJUMP => 2,
+            Opcode::ADD
+            | Opcode::SUB
+            | Opcode::LT
+            | Opcode::GT
+            | Opcode::SLT
+            | Opcode::SGT
+            | Opcode::EQ
+            | Opcode::ISZERO
+            | Opcode::AND
+            | Opcode::OR
+            | Opcode::XOR
+            | Opcode::NOT
+            | Opcode::BYTE
+            | Opcode::SHL
+            | Opcode::SHR
+            | Opcode::SAR
+            | Opcode::CALLDATALOAD
+            | Opcode::CALLDATACOPY
+            | Opcode::CODECOPY
+            | Opcode::RETURNDATACOPY
+            | Opcode::MLOAD
+            | Opcode::MSTORE
+            | Opcode::MSTORE8
+            | Opcode::DATALOADN
+            | Opcode::DATACOPY
+            | Opcode::RETF => 3,
+            Opcode::DATALOAD | Opcode::RJUMPI | Opcode::RJUMPV => 4,
+            Opcode::MUL
+            | Opcode::DIV
+            | Opcode::SDIV
+            | Opcode::MOD
+            | Opcode::SMOD
+            | Opcode::SIGNEXTEND
+            | Opcode::CALLF
+            | Opcode::JUMPF => 5,
+            Opcode::ADDMOD | Opcode::MULMOD | Opcode::JUMP => 8,
+            Opcode::EXP | Opcode::JUMPI => 10,
+            Opcode::BLOCKHASH => 20,
+            Opcode::SHA3 => 30,
+            Opcode::BALANCE
+            | Opcode::EXTCODESIZE
+            | Opcode::EXTCODECOPY
+            | Opcode::EXTCODEHASH
+            | Opcode::SLOAD
+            | Opcode::CALL
+            | Opcode::CALLCODE
+            | Opcode::DELEGATECALL
+            | Opcode::STATICCALL => 100,
+            Opcode::SELFDESTRUCT => 5000,
+            Opcode::CREATE | Opcode::CREATE2 => 32000,
+            _ => 0,
+        }
+    }
+
+    // Returns true for the bytes that don't have an instruction assigned
+    pub fn is_unassigned(self) -> bool {
+        matches!(
+            self as u8,
+            0x0c..=0x0f
+                | 0x1e..=0x1f
+                | 0x21..=0x2f
+                | 0x47..=0x4f
+                | 0x5c..=0x5f
+                | 0xa5..=0xcf
+                | 0xd4..=0xdf
+                | 0xe6..=0xef
+                | 0xf6..=0xf9
+                | 0xfb..=0xfc
+        )
+    }
+}
@@ -0,0 +1,140 @@
+use crate::abi::{selector, Abi};
+use ethereum_types::U256;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result};
+
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+// The decoded return data of a reverted execution
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum RevertReason {
+    // Error(string), used by require and revert with a message
+    Error(String),
+    // Panic(uint256), used by the compiler for failed assertions, overflows, etc
+    Panic(U256),
+    // A custom error, with its ABI-encoded arguments
+    Custom {
+        signature: String,
+        arguments: Vec<u8>,
+    },
+}
+
+impl RevertReason {
+    // The meaning of a Panic code, as documented by Solidity
+    pub fn panic_meaning(code: U256) -> Option<&'static str> {
+        if code > U256::from(u8::MAX) {
+            return None;
+        }
+
+        let meaning = match code.low_u32() {
+            0x00 => "generic compiler inserted panic",
+            0x01 => "assertion failed",
+            0x11 => "arithmetic overflow or underflow",
+            0x12 => "division or modulo by zero",
+            0x21 => "conversion to an invalid enum value",
+            0x22 => "access to an incorrectly encoded storage byte array",
+            0x31 => "pop on an empty array",
+            0x32 => "array index out of bounds",
+            0x41 => "too much memory allocated",
+            0x51 => "call to a zero-initialized internal function",
+            _ => return None,
+        };
+
+        Some(meaning)
+    }
+}
+
+impl Display for RevertReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            RevertReason::Error(message) => write!(f, "Error({:?})", message),
+            RevertReason::Panic(code) => match RevertReason::panic_meaning(*code) {
+                Some(meaning) => write!(f, "Panic(0x{:02x}): {}", code.low_u32(), meaning),
+                None => write!(f, "Panic(0x{:x})", code),
+            },
+            RevertReason::Custom {
+                signature,
+                arguments,
+            } => write!(f, "{} 0x{}", signature, hex::encode(arguments)),
+        }
+    }
+}
+
+// Decodes revert data. Error(string) and Panic(uint256) are always recognized, and custom errors
+// only if their signatures are added.
+#[derive(Debug, Clone, Default)]
+pub struct RevertDecoder {
+    custom_errors: HashMap<[u8; 4], String>,
+}
+
+impl RevertDecoder {
+    pub fn new() -> RevertDecoder {
+        RevertDecoder::default()
+    }
+
+    // Adds a custom error by its canonical signature, like "InsufficientBalance(uint256,uint256)"
+    pub fn add_custom_error(&mut self, signature: &str) {
+        self.custom_errors
+            .insert(selector(signature), signature.to_string());
+    }
+
+    // Adds the custom errors declared in the ABI
+    pub fn add_abi(&mut self, abi: &Abi) {
+        for error in &abi.errors {
+            self.add_custom_error(&error.signature());
+        }
+    }
+
+    // Returns None if the data is empty, malformed, or uses an unknown selector
+    pub fn decode(&self, data: &[u8]) -> Option<RevertReason> {
+        if data.len() < 4 {
+            return None;
+        }
+
+        let mut selector = [0; 4];
+        selector.copy_from_slice(&data[..4]);
+        let arguments = &data[4..];
+
+        match selector {
+            ERROR_SELECTOR => decode_string(arguments).map(RevertReason::Error),
+            PANIC_SELECTOR => {
+                if arguments.len() < 32 {
+                    return None;
+                }
+
+                Some(RevertReason::Panic(U256::from_big_endian(&arguments[..32])))
+            }
+            _ => self
+                .custom_errors
+                .get(&selector)
+                .map(|signature| RevertReason::Custom {
+                    signature: signature.clone(),
+                    arguments: arguments.to_vec(),
+                }),
+        }
+    }
+}
+
+// Decodes an ABI-encoded string, which is an offset to its length, followed by its bytes
+fn decode_string(data: &[u8]) -> Option<String> {
+    let offset = read_usize(data, 0)?;
+    let length = read_usize(data, offset)?;
+
+    let start = offset.checked_add(32)?;
+    let end = start.checked_add(length)?;
+    let bytes = data.get(start..end)?;
+
+    Some(String::from_utf8_lossy(bytes).into_owned())
+}
+
+fn read_usize(data: &[u8], position: usize) -> Option<usize> {
+    let word = data.get(position..position.checked_add(32)?)?;
+    let value = U256::from_big_endian(word);
+
+    if value > U256::from(usize::MAX) {
+        return None;
+    }
+
+    Some(value.as_usize())
+}
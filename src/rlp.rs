@@ -0,0 +1,383 @@
+use ethereum_types::{Address, H256, U256};
+use std::fmt::{Display, Formatter};
+
+const STRING_OFFSET: u8 = 0x80;
+const LIST_OFFSET: u8 = 0xc0;
+const MAX_SHORT_LENGTH: usize = 55;
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum DecoderError {
+    InputTooShort,
+    TrailingBytes,
+    ExpectedList,
+    ExpectedBytes,
+    // A length was encoded with leading zeros, or in long form when the short one should be used
+    NonCanonicalSize,
+    // A single byte below 0x80 was encoded as a string instead of as itself
+    NonCanonicalSingleByte,
+    IntegerWithLeadingZeros,
+    IntegerOverflow,
+    InvalidLength,
+}
+
+impl Display for DecoderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for DecoderError {}
+
+pub trait Encodable {
+    fn rlp_append(&self, out: &mut Vec<u8>);
+}
+
+pub trait Decodable: Sized {
+    fn decode(rlp: &Rlp) -> Result<Self, DecoderError>;
+}
+
+pub fn encode<E: Encodable + ?Sized>(value: &E) -> Vec<u8> {
+    let mut out = Vec::new();
+    value.rlp_append(&mut out);
+
+    out
+}
+
+pub fn encode_list<E: Encodable>(items: &[E]) -> Vec<u8> {
+    encode(items)
+}
+
+pub fn decode<D: Decodable>(bytes: &[u8]) -> Result<D, DecoderError> {
+    Rlp::new(bytes)?.as_val()
+}
+
+pub fn decode_list<D: Decodable>(bytes: &[u8]) -> Result<Vec<D>, DecoderError> {
+    decode(bytes)
+}
+
+// Builds an RLP list out of items of different types.
+#[derive(Debug, Default, Clone)]
+pub struct RlpStream {
+    payload: Vec<u8>,
+}
+
+impl RlpStream {
+    pub fn new() -> RlpStream {
+        RlpStream {
+            payload: Vec::new(),
+        }
+    }
+
+    pub fn append<E: Encodable + ?Sized>(&mut self, value: &E) -> &mut RlpStream {
+        value.rlp_append(&mut self.payload);
+        self
+    }
+
+    pub fn append_list<E: Encodable>(&mut self, items: &[E]) -> &mut RlpStream {
+        self.append(items)
+    }
+
+    // Appends an item that is already RLP-encoded
+    pub fn append_raw(&mut self, encoded: &[u8]) -> &mut RlpStream {
+        self.payload.extend_from_slice(encoded);
+        self
+    }
+
+    pub fn out(&self) -> Vec<u8> {
+        let mut out = encode_length(self.payload.len(), LIST_OFFSET);
+        out.extend_from_slice(&self.payload);
+
+        out
+    }
+}
+
+// A view over a single RLP item, which is validated to be canonical when created.
+#[derive(Debug, Copy, Clone)]
+pub struct Rlp<'data> {
+    raw: &'data [u8],
+    payload: &'data [u8],
+    is_list: bool,
+}
+
+impl<'data> Rlp<'data> {
+    pub fn new(bytes: &'data [u8]) -> Result<Rlp<'data>, DecoderError> {
+        let (item, rest) = split_item(bytes)?;
+
+        if !rest.is_empty() {
+            return Err(DecoderError::TrailingBytes);
+        }
+
+        Ok(item)
+    }
+
+    pub fn is_list(&self) -> bool {
+        self.is_list
+    }
+
+    pub fn as_raw(&self) -> &'data [u8] {
+        self.raw
+    }
+
+    pub fn data(&self) -> Result<&'data [u8], DecoderError> {
+        if self.is_list {
+            return Err(DecoderError::ExpectedBytes);
+        }
+
+        Ok(self.payload)
+    }
+
+    pub fn items(&self) -> Result<Vec<Rlp<'data>>, DecoderError> {
+        if !self.is_list {
+            return Err(DecoderError::ExpectedList);
+        }
+
+        let mut items = Vec::new();
+        let mut rest = self.payload;
+
+        while !rest.is_empty() {
+            let (item, next) = split_item(rest)?;
+            items.push(item);
+            rest = next;
+        }
+
+        Ok(items)
+    }
+
+    pub fn as_val<D: Decodable>(&self) -> Result<D, DecoderError> {
+        D::decode(self)
+    }
+
+    pub fn as_list<D: Decodable>(&self) -> Result<Vec<D>, DecoderError> {
+        self.items()?.iter().map(|item| item.as_val()).collect()
+    }
+}
+
+fn split_item(bytes: &[u8]) -> Result<(Rlp<'_>, &[u8]), DecoderError> {
+    let first = *bytes.first().ok_or(DecoderError::InputTooShort)?;
+
+    if first < STRING_OFFSET {
+        let item = Rlp {
+            raw: &bytes[..1],
+            payload: &bytes[..1],
+            is_list: false,
+        };
+
+        return Ok((item, &bytes[1..]));
+    }
+
+    let is_list = first >= LIST_OFFSET;
+    let offset = if is_list { LIST_OFFSET } else { STRING_OFFSET };
+    let prefix = (first - offset) as usize;
+
+    let (header_length, payload_length) = if prefix <= MAX_SHORT_LENGTH {
+        (1, prefix)
+    } else {
+        let length_of_length = prefix - MAX_SHORT_LENGTH;
+        let length_bytes = bytes
+            .get(1..1 + length_of_length)
+            .ok_or(DecoderError::InputTooShort)?;
+
+        (1 + length_of_length, decode_length(length_bytes)?)
+    };
+
+    let end = header_length
+        .checked_add(payload_length)
+        .ok_or(DecoderError::InputTooShort)?;
+
+    if end > bytes.len() {
+        return Err(DecoderError::InputTooShort);
+    }
+
+    let payload = &bytes[header_length..end];
+
+    if !is_list && payload_length == 1 && payload[0] < STRING_OFFSET {
+        return Err(DecoderError::NonCanonicalSingleByte);
+    }
+
+    let item = Rlp {
+        raw: &bytes[..end],
+        payload,
+        is_list,
+    };
+
+    Ok((item, &bytes[end..]))
+}
+
+fn decode_length(length_bytes: &[u8]) -> Result<usize, DecoderError> {
+    if length_bytes[0] == 0 || length_bytes.len() > std::mem::size_of::<usize>() {
+        return Err(DecoderError::NonCanonicalSize);
+    }
+
+    let length = length_bytes
+        .iter()
+        .fold(0usize, |length, byte| (length << 8) | *byte as usize);
+
+    if length <= MAX_SHORT_LENGTH {
+        return Err(DecoderError::NonCanonicalSize);
+    }
+
+    Ok(length)
+}
+
+fn encode_length(length: usize, offset: u8) -> Vec<u8> {
+    if length <= MAX_SHORT_LENGTH {
+        return vec![offset + length as u8];
+    }
+
+    let length_bytes = length.to_be_bytes();
+    let length_bytes = trim_leading_zeros(&length_bytes);
+
+    let mut encoded = Vec::with_capacity(1 + length_bytes.len());
+    encoded.push(offset + MAX_SHORT_LENGTH as u8 + length_bytes.len() as u8);
+    encoded.extend_from_slice(length_bytes);
+
+    encoded
+}
+
+fn encode_bytes(bytes: &[u8], out: &mut Vec<u8>) {
+    if bytes.len() == 1 && bytes[0] < STRING_OFFSET {
+        out.push(bytes[0]);
+        return;
+    }
+
+    out.extend_from_slice(&encode_length(bytes.len(), STRING_OFFSET));
+    out.extend_from_slice(bytes);
+}
+
+fn trim_leading_zeros(bytes: &[u8]) -> &[u8] {
+    let first_non_zero = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    &bytes[first_non_zero..]
+}
+
+fn decode_integer_bytes<'data>(
+    rlp: &Rlp<'data>,
+    max_length: usize,
+) -> Result<&'data [u8], DecoderError> {
+    let data = rlp.data()?;
+
+    if data.len() > max_length {
+        return Err(DecoderError::IntegerOverflow);
+    }
+
+    if data.first() == Some(&0) {
+        return Err(DecoderError::IntegerWithLeadingZeros);
+    }
+
+    Ok(data)
+}
+
+impl<E: Encodable + ?Sized> Encodable for &E {
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        (*self).rlp_append(out)
+    }
+}
+
+impl Encodable for [u8] {
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        encode_bytes(self, out)
+    }
+}
+
+impl Encodable for Vec<u8> {
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        encode_bytes(self, out)
+    }
+}
+
+// Byte strings are encoded as strings, so u8 doesn't implement Encodable nor Decodable, which
+// would make them lists
+impl<E: Encodable> Encodable for [E] {
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        let mut stream = RlpStream::new();
+        for item in self {
+            stream.append(item);
+        }
+
+        out.extend_from_slice(&stream.out());
+    }
+}
+
+impl<E: Encodable> Encodable for Vec<E> {
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        self.as_slice().rlp_append(out)
+    }
+}
+
+impl Encodable for U256 {
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        let mut buffer = [0; 32];
+        self.to_big_endian(&mut buffer);
+
+        encode_bytes(trim_leading_zeros(&buffer), out)
+    }
+}
+
+impl Encodable for u64 {
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        encode_bytes(trim_leading_zeros(&self.to_be_bytes()), out)
+    }
+}
+
+impl Encodable for Address {
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        encode_bytes(self.as_bytes(), out)
+    }
+}
+
+impl Encodable for H256 {
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        encode_bytes(self.as_bytes(), out)
+    }
+}
+
+impl Decodable for Vec<u8> {
+    fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        Ok(rlp.data()?.to_vec())
+    }
+}
+
+impl<D: Decodable> Decodable for Vec<D> {
+    fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        rlp.as_list()
+    }
+}
+
+impl Decodable for U256 {
+    fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        Ok(U256::from_big_endian(decode_integer_bytes(rlp, 32)?))
+    }
+}
+
+impl Decodable for u64 {
+    fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        let data = decode_integer_bytes(rlp, 8)?;
+
+        Ok(data
+            .iter()
+            .fold(0u64, |value, byte| (value << 8) | *byte as u64))
+    }
+}
+
+impl Decodable for Address {
+    fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        let data = rlp.data()?;
+
+        if data.len() != 20 {
+            return Err(DecoderError::InvalidLength);
+        }
+
+        Ok(Address::from_slice(data))
+    }
+}
+
+impl Decodable for H256 {
+    fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        let data = rlp.data()?;
+
+        if data.len() != 32 {
+            return Err(DecoderError::InvalidLength);
+        }
+
+        Ok(H256::from_slice(data))
+    }
+}
@@ -0,0 +1,585 @@
+use crate::abi::{AbiError, AbiValue, Function};
+use crate::analyzed_code::CodeCache;
+use crate::cheatcodes::{Cheatcode, Cheatcodes, Prank, CHEATCODE_ADDRESS};
+use crate::context::{BlockContext, CallContext};
+use crate::delegation::resolve_code;
+use crate::evm::{run_analyzed, ExecutionResult};
+use crate::execution_error::ExecutionError;
+use crate::host::Host;
+use crate::keccak::keccak256;
+use crate::log::Log;
+use crate::opcode_handlers::HaltReason;
+use crate::revert_reason::RevertReason;
+use crate::state::{create_address, Account};
+use ethereum_types::{Address, H256, U256};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+use std::mem;
+use std::sync::Arc;
+
+// Errors that prevent a transaction from being executed, or that make it fail the expectations set
+// with cheatcodes
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum EvmError {
+    InsufficientBalance,
+    NonceOverflow,
+    // There's already a contract at the address of the deployment
+    AddressCollision,
+    // The calldata sent to the cheatcode address isn't a supported cheatcode, its arguments are
+    // out of range, or the transaction has value
+    InvalidCheatcode,
+    // The transaction didn't revert, or reverted with other data, after an expectRevert
+    MissingRevert,
+    // The transaction didn't emit the logs expected with expect_emit
+    MissingEmit,
+}
+
+impl Display for EvmError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for EvmError {}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum CallError {
+    Abi(AbiError),
+    Execution {
+        error: ExecutionError,
+        revert_reason: Option<RevertReason>,
+    },
+}
+
+impl Display for CallError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for CallError {}
+
+impl From<AbiError> for CallError {
+    fn from(error: AbiError) -> Self {
+        CallError::Abi(error)
+    }
+}
+
+#[derive(Debug)]
+pub struct Deployment {
+    // Where the contract is deployed, or would have been if the deployment failed
+    pub address: Address,
+    pub result: ExecutionResult,
+}
+
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+pub struct SnapshotId(u64);
+
+// An EVM with an in-memory world state, which is kept across executions.
+//
+// Inter-account calls aren't supported yet, so each execution only runs the code of the account
+// it targets. For the same reason, contracts can't call the cheatcodes, but transactions sent to
+// CHEATCODE_ADDRESS can, once they are enabled.
+//
+// The code of each execution is analyzed once, and cached by its hash.
+#[derive(Debug, Default)]
+pub struct Evm {
+    accounts: HashMap<Address, Account>,
+    pub block: BlockContext,
+    // The previous values of everything modified since the oldest snapshot, or since the start of
+    // the transaction being executed
+    journal: Vec<JournalEntry>,
+    snapshots: Vec<Snapshot>,
+    next_snapshot_id: u64,
+    executing_transaction: bool,
+    cheatcodes: Option<Cheatcodes>,
+    code_cache: Arc<CodeCache>,
+}
+
+#[derive(Debug)]
+enum JournalEntry {
+    AccountCreated(Address),
+    Balance(Address, U256),
+    Nonce(Address, u64),
+    Code(Address, Vec<u8>),
+    Storage(Address, U256, U256),
+}
+
+#[derive(Debug)]
+struct Snapshot {
+    id: SnapshotId,
+    journal_length: usize,
+    block: BlockContext,
+}
+
+impl Evm {
+    pub fn new() -> Evm {
+        Evm::default()
+    }
+
+    pub fn with_block(block: BlockContext) -> Evm {
+        Evm {
+            block,
+            ..Evm::default()
+        }
+    }
+
+    // Replaces the cache of analyzed code, so that it can be shared with other sessions
+    pub fn set_code_cache(&mut self, code_cache: Arc<CodeCache>) {
+        self.code_cache = code_cache;
+    }
+
+    pub fn account(&self, address: &Address) -> Option<&Account> {
+        self.accounts.get(address)
+    }
+
+    pub fn accounts(&self) -> &HashMap<Address, Account> {
+        &self.accounts
+    }
+
+    pub fn balance(&self, address: &Address) -> U256 {
+        self.account(address)
+            .map_or(U256::zero(), |account| account.balance)
+    }
+
+    pub fn nonce(&self, address: &Address) -> u64 {
+        self.account(address).map_or(0, |account| account.nonce)
+    }
+
+    pub fn code(&self, address: &Address) -> &[u8] {
+        self.account(address)
+            .map_or(&[][..], |account| account.code.as_slice())
+    }
+
+    pub fn storage(&self, address: &Address, key: &U256) -> U256 {
+        self.account(address)
+            .and_then(|account| account.storage.get(key).copied())
+            .unwrap_or_default()
+    }
+
+    pub fn set_balance(&mut self, address: Address, balance: U256) {
+        let previous = mem::replace(&mut self.account_mut(address).balance, balance);
+        self.record(JournalEntry::Balance(address, previous));
+    }
+
+    pub fn set_nonce(&mut self, address: Address, nonce: u64) {
+        let previous = mem::replace(&mut self.account_mut(address).nonce, nonce);
+        self.record(JournalEntry::Nonce(address, previous));
+    }
+
+    pub fn set_code(&mut self, address: Address, code: Vec<u8>) {
+        let previous = mem::replace(&mut self.account_mut(address).code, code);
+        self.record(JournalEntry::Code(address, previous));
+    }
+
+    pub fn set_storage(&mut self, address: Address, key: U256, value: U256) {
+        let previous = write_slot(&mut self.account_mut(address).storage, key, value);
+        self.record(JournalEntry::Storage(address, key, previous));
+    }
+
+    // Saves the current state and block, so that they can be restored with revert_to
+    pub fn snapshot(&mut self) -> SnapshotId {
+        let id = SnapshotId(self.next_snapshot_id);
+        self.next_snapshot_id += 1;
+
+        self.snapshots.push(Snapshot {
+            id,
+            journal_length: self.journal.len(),
+            block: self.block.clone(),
+        });
+
+        id
+    }
+
+    // Restores the state and block saved by the snapshot. The snapshot, and every snapshot taken
+    // after it, can't be reverted to again. Returns false if the snapshot doesn't exist.
+    pub fn revert_to(&mut self, id: SnapshotId) -> bool {
+        let index = match self.snapshots.iter().position(|snapshot| snapshot.id == id) {
+            Some(index) => index,
+            None => return false,
+        };
+
+        let snapshot = self.snapshots.split_off(index).remove(0);
+
+        self.undo(snapshot.journal_length);
+        self.block = snapshot.block;
+
+        true
+    }
+
+    // Makes the transactions sent to CHEATCODE_ADDRESS run Foundry's cheatcodes instead, like
+    // prank(address), warp(uint256), roll(uint256), deal(address,uint256),
+    // store(address,bytes32,bytes32), load(address,bytes32) and expectRevert(bytes). The pranks
+    // and expectations apply to the next transaction or deployment.
+    pub fn enable_cheatcodes(&mut self) {
+        self.cheatcodes.get_or_insert_with(Cheatcodes::default);
+    }
+
+    // Makes the next transaction or deployment fail with MissingEmit unless it emits the log.
+    // Foundry's expectEmit needs the test contract to emit the expected log, so it's set here
+    // instead. The cheatcodes are enabled if they weren't.
+    pub fn expect_emit(&mut self, log: Log) {
+        self.cheatcodes
+            .get_or_insert_with(Cheatcodes::default)
+            .expected_logs
+            .push(log);
+    }
+
+    // Executes a call without committing its changes, like eth_call
+    pub fn call(&self, from: Address, to: Address, data: &[u8]) -> ExecutionResult {
+        self.execute(
+            from,
+            from,
+            to,
+            U256::zero(),
+            data,
+            resolve_code(&self.accounts, &to),
+        )
+    }
+
+    // Calls a function by its human-readable signature, like
+    // "balanceOf(address) returns (uint256)", and decodes what it returns
+    pub fn call_function(
+        &self,
+        from: Address,
+        to: Address,
+        signature: &str,
+        arguments: &[AbiValue],
+    ) -> Result<Vec<AbiValue>, CallError> {
+        let function = Function::parse(signature)?;
+        let result = self.call(from, to, &function.encode_input(arguments)?);
+
+        if let Some(error) = result.error {
+            return Err(CallError::Execution {
+                error,
+                revert_reason: result.revert_reason(),
+            });
+        }
+
+        Ok(function.decode_output(&result.return_data)?)
+    }
+
+    // Executes a transaction, committing its changes if it succeeds. The sender's nonce is
+    // incremented even if the execution fails.
+    //
+    // If the transaction is being pranked, the pranked address sends it instead, but `from` is
+    // still its origin. If it fails the expectations set with cheatcodes, its changes are
+    // discarded and an error is returned.
+    pub fn transact(
+        &mut self,
+        from: Address,
+        to: Address,
+        value: U256,
+        data: &[u8],
+    ) -> Result<ExecutionResult, EvmError> {
+        if to == CHEATCODE_ADDRESS && self.cheatcodes.is_some() {
+            // The cheatcodes aren't an account, so they can't receive value
+            if !value.is_zero() {
+                return Err(EvmError::InvalidCheatcode);
+            }
+
+            return self.run_cheatcode(data);
+        }
+
+        let origin = from;
+        let from = self.pranked_sender().unwrap_or(from);
+
+        self.check_sender(&from, value)?;
+        self.use_prank();
+        self.set_nonce(from, self.nonce(&from) + 1);
+
+        let checkpoint = self.begin_transaction();
+        self.transfer(&from, &to, value);
+
+        let code = resolve_code(&self.accounts, &to).to_vec();
+        let result = self.execute(from, origin, to, value, data, &code);
+
+        let expectations = self.check_expectations(&result);
+        let success = result.is_success() && expectations.is_ok();
+
+        if success {
+            self.commit_storage(&to, &result);
+        }
+
+        self.end_transaction(checkpoint, success);
+        expectations?;
+
+        Ok(result)
+    }
+
+    // Runs the init code, and stores what it returns as the code of the new contract
+    pub fn deploy(
+        &mut self,
+        from: Address,
+        init_code: &[u8],
+        value: U256,
+    ) -> Result<Deployment, EvmError> {
+        let origin = from;
+        let from = self.pranked_sender().unwrap_or(from);
+
+        self.check_sender(&from, value)?;
+
+        let nonce = self.nonce(&from);
+        let address = create_address(&from, nonce);
+
+        if self
+            .account(&address)
+            .is_some_and(|account| account.nonce != 0 || !account.code.is_empty())
+        {
+            return Err(EvmError::AddressCollision);
+        }
+
+        self.use_prank();
+        self.set_nonce(from, nonce + 1);
+
+        let checkpoint = self.begin_transaction();
+
+        // Contracts start with a nonce of 1, as defined in EIP-161
+        self.set_nonce(address, 1);
+        self.transfer(&from, &address, value);
+
+        let result = self.execute(from, origin, address, value, &[], init_code);
+
+        let expectations = self.check_expectations(&result);
+        let success = result.is_success() && expectations.is_ok();
+
+        if success {
+            self.commit_storage(&address, &result);
+            self.set_code(address, result.return_data.clone());
+        }
+
+        self.end_transaction(checkpoint, success);
+        expectations?;
+
+        Ok(Deployment { address, result })
+    }
+
+    fn run_cheatcode(&mut self, data: &[u8]) -> Result<ExecutionResult, EvmError> {
+        let cheatcode = Cheatcode::decode(data).ok_or(EvmError::InvalidCheatcode)?;
+        let mut return_data = Vec::new();
+
+        match cheatcode {
+            Cheatcode::Prank(sender) => self.set_prank(sender, false),
+            Cheatcode::StartPrank(sender) => self.set_prank(sender, true),
+            Cheatcode::StopPrank => self.cheatcodes_mut().prank = None,
+            Cheatcode::Warp(timestamp) => {
+                self.block.timestamp =
+                    u32::try_from(timestamp).map_err(|_| EvmError::InvalidCheatcode)?;
+            }
+            Cheatcode::Roll(number) => {
+                self.block.number =
+                    u32::try_from(number).map_err(|_| EvmError::InvalidCheatcode)?;
+            }
+            Cheatcode::Deal(address, balance) => self.set_balance(address, balance),
+            Cheatcode::Store(address, key, value) => self.set_storage(address, key, value),
+            Cheatcode::Load(address, key) => {
+                return_data.resize(32, 0);
+                self.storage(&address, &key).to_big_endian(&mut return_data);
+            }
+            Cheatcode::ExpectRevert(data) => self.cheatcodes_mut().expected_revert = Some(data),
+        }
+
+        Ok(ExecutionResult {
+            return_data,
+            halt_reason: Some(HaltReason::Return),
+            error: None,
+            logs: Vec::new(),
+            storage: HashMap::new(),
+        })
+    }
+
+    fn cheatcodes_mut(&mut self) -> &mut Cheatcodes {
+        self.cheatcodes.get_or_insert_with(Cheatcodes::default)
+    }
+
+    fn set_prank(&mut self, sender: Address, persistent: bool) {
+        self.cheatcodes_mut().prank = Some(Prank { sender, persistent });
+    }
+
+    fn pranked_sender(&self) -> Option<Address> {
+        self.cheatcodes
+            .as_ref()
+            .and_then(Cheatcodes::pranked_sender)
+    }
+
+    fn use_prank(&mut self) {
+        if let Some(cheatcodes) = &mut self.cheatcodes {
+            cheatcodes.use_prank();
+        }
+    }
+
+    fn check_expectations(&mut self, result: &ExecutionResult) -> Result<(), EvmError> {
+        match &mut self.cheatcodes {
+            Some(cheatcodes) => cheatcodes.check_expectations(result),
+            None => Ok(()),
+        }
+    }
+
+    fn check_sender(&self, from: &Address, value: U256) -> Result<(), EvmError> {
+        if self.nonce(from) == u64::MAX {
+            return Err(EvmError::NonceOverflow);
+        }
+
+        if self.balance(from) < value {
+            return Err(EvmError::InsufficientBalance);
+        }
+
+        Ok(())
+    }
+
+    // The sender's balance must have been checked
+    fn transfer(&mut self, from: &Address, to: &Address, value: U256) {
+        if value.is_zero() || from == to {
+            return;
+        }
+
+        self.set_balance(*from, self.balance(from) - value);
+        self.set_balance(*to, self.balance(to) + value);
+    }
+
+    // Returns the journal position to undo to if the transaction fails
+    fn begin_transaction(&mut self) -> usize {
+        self.executing_transaction = true;
+        self.journal.len()
+    }
+
+    fn end_transaction(&mut self, checkpoint: usize, success: bool) {
+        if !success {
+            self.undo(checkpoint);
+        }
+
+        self.executing_transaction = false;
+
+        if self.snapshots.is_empty() {
+            self.journal.clear();
+        }
+    }
+
+    fn record(&mut self, entry: JournalEntry) {
+        if self.executing_transaction || !self.snapshots.is_empty() {
+            self.journal.push(entry);
+        }
+    }
+
+    fn account_mut(&mut self, address: Address) -> &mut Account {
+        if !self.accounts.contains_key(&address) {
+            self.record(JournalEntry::AccountCreated(address));
+        }
+
+        self.accounts.entry(address).or_default()
+    }
+
+    // Undoes the changes recorded after the given journal position, from the newest to the oldest.
+    // Accounts are always recorded as created before their fields are modified.
+    fn undo(&mut self, journal_length: usize) {
+        for entry in self.journal.drain(journal_length..).rev() {
+            match entry {
+                JournalEntry::AccountCreated(address) => {
+                    self.accounts.remove(&address);
+                }
+                JournalEntry::Balance(address, balance) => {
+                    self.accounts.entry(address).or_default().balance = balance;
+                }
+                JournalEntry::Nonce(address, nonce) => {
+                    self.accounts.entry(address).or_default().nonce = nonce;
+                }
+                JournalEntry::Code(address, code) => {
+                    self.accounts.entry(address).or_default().code = code;
+                }
+                JournalEntry::Storage(address, key, value) => {
+                    write_slot(
+                        &mut self.accounts.entry(address).or_default().storage,
+                        key,
+                        value,
+                    );
+                }
+            }
+        }
+    }
+
+    fn execute(
+        &self,
+        from: Address,
+        origin: Address,
+        to: Address,
+        value: U256,
+        data: &[u8],
+        code: &[u8],
+    ) -> ExecutionResult {
+        let host = StateHost {
+            accounts: &self.accounts,
+        };
+
+        let call_context = CallContext {
+            value,
+            calldata: data,
+            contract_address: to,
+            caller_address: from,
+            origin_address: origin,
+            host: &host,
+            ..CallContext::default()
+        };
+
+        run_analyzed(
+            &self.code_cache.get_or_analyze(code),
+            &call_context,
+            &self.block,
+        )
+    }
+
+    fn commit_storage(&mut self, address: &Address, result: &ExecutionResult) {
+        for (key, value) in &result.storage {
+            self.set_storage(*address, *key, *value);
+        }
+    }
+}
+
+// Zero slots are removed, so that they don't take space. Returns the previous value.
+fn write_slot(storage: &mut HashMap<U256, U256>, key: U256, value: U256) -> U256 {
+    let previous = if value.is_zero() {
+        storage.remove(&key)
+    } else {
+        storage.insert(key, value)
+    };
+
+    previous.unwrap_or_default()
+}
+
+// Gives the executions read access to the accounts
+struct StateHost<'state> {
+    accounts: &'state HashMap<Address, Account>,
+}
+
+impl Host for StateHost<'_> {
+    fn balance(&self, address: &Address) -> U256 {
+        self.accounts
+            .get(address)
+            .map_or(U256::zero(), |account| account.balance)
+    }
+
+    fn code(&self, address: &Address) -> Vec<u8> {
+        self.accounts
+            .get(address)
+            .map_or(Vec::new(), |account| account.code.clone())
+    }
+
+    fn storage(&self, address: &Address, key: &U256) -> U256 {
+        self.accounts
+            .get(address)
+            .and_then(|account| account.storage.get(key).copied())
+            .unwrap_or_default()
+    }
+
+    // There's no chain history, so every block hash is zero
+    fn block_hash(&self, _number: u32) -> H256 {
+        H256::zero()
+    }
+
+    fn code_hash(&self, address: &Address) -> H256 {
+        match self.accounts.get(address) {
+            Some(account) if !account.is_empty() => keccak256(&account.code),
+            _ => H256::zero(),
+        }
+    }
+}
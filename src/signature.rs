@@ -0,0 +1,44 @@
+use crate::keccak::keccak256;
+use ethereum_types::{Address, H256, U256};
+use k256::ecdsa::{RecoveryId, Signature as EcdsaSignature, VerifyingKey};
+
+const SECP256K1_HALF_ORDER: U256 = U256([
+    0xdfe92f46681b20a0,
+    0x5d576e7357a4501d,
+    0xffffffffffffffff,
+    0x7fffffffffffffff,
+]);
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Signature {
+    pub y_parity: bool,
+    pub r: U256,
+    pub s: U256,
+}
+
+impl Signature {
+    // Returns the address whose key signed `hash`, or None if the signature is invalid. As required
+    // since EIP-2, signatures with an s value in the upper half of the curve order are rejected.
+    pub fn recover(&self, hash: &H256) -> Option<Address> {
+        if self.s > SECP256K1_HALF_ORDER {
+            return None;
+        }
+
+        let mut r = [0; 32];
+        let mut s = [0; 32];
+        self.r.to_big_endian(&mut r);
+        self.s.to_big_endian(&mut s);
+
+        let signature = EcdsaSignature::from_scalars(r, s).ok()?;
+        let recovery_id = RecoveryId::from_byte(self.y_parity as u8)?;
+
+        let key =
+            VerifyingKey::recover_from_prehash(hash.as_bytes(), &signature, recovery_id).ok()?;
+        let public_key = key.to_encoded_point(false);
+
+        // The first byte of an uncompressed point is a tag, and isn't part of the public key
+        Some(Address::from_slice(
+            &keccak256(&public_key.as_bytes()[1..])[12..],
+        ))
+    }
+}
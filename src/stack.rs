@@ -1,14 +1,15 @@
-use crate::execution_error::ExecutionError;
+use crate::execution_error::StepError;
 use ethereum_types::U256;
 
 use std::fmt::{Debug, Formatter};
 use std::result::Result;
 
+#[derive(Clone)]
 pub struct Stack {
     stack: Vec<U256>,
 }
 
-const MAX_STACK_DEPTH: usize = 1024;
+pub(crate) const MAX_STACK_DEPTH: usize = 1024;
 
 impl Stack {
     pub fn with_capacity(capacity: usize) -> Stack {
@@ -17,9 +18,9 @@ impl Stack {
         }
     }
 
-    pub fn push(&mut self, value: U256) -> Result<(), ExecutionError> {
+    pub fn push(&mut self, value: U256) -> Result<(), StepError> {
         if self.stack.len() == MAX_STACK_DEPTH {
-            return Err(ExecutionError::StackOverflow);
+            return Err(StepError::StackOverflow);
         }
 
         self.stack.push(value);
@@ -27,23 +28,36 @@ impl Stack {
         Ok(())
     }
 
-    pub fn pop(&mut self) -> Result<U256, ExecutionError> {
-        self.stack.pop().ok_or(ExecutionError::StackUnderflow)
+    pub fn len(&self) -> usize {
+        self.stack.len()
     }
 
-    pub fn read(&self, number_from_top: usize) -> Result<U256, ExecutionError> {
+    pub fn is_empty(&self) -> bool {
+        self.stack.is_empty()
+    }
+
+    // The elements of the stack, from the bottom to the top
+    pub fn as_slice(&self) -> &[U256] {
+        &self.stack
+    }
+
+    pub fn pop(&mut self) -> Result<U256, StepError> {
+        self.stack.pop().ok_or(StepError::StackUnderflow)
+    }
+
+    pub fn read(&self, number_from_top: usize) -> Result<U256, StepError> {
         if self.stack.len() <= number_from_top {
-            return Err(ExecutionError::StackUnderflow);
+            return Err(StepError::StackUnderflow);
         }
 
         Ok(self.stack[self.stack.len() - number_from_top - 1])
     }
 
-    pub fn swap_with_top(&mut self, number_from_top: usize) -> Result<(), ExecutionError> {
+    pub fn swap_with_top(&mut self, number_from_top: usize) -> Result<(), StepError> {
         let stack_len = self.stack.len();
 
         if stack_len <= number_from_top {
-            return Err(ExecutionError::StackUnderflow);
+            return Err(StepError::StackUnderflow);
         }
 
         self.stack
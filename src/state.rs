@@ -0,0 +1,71 @@
+use crate::keccak::keccak256;
+use crate::rlp::{self, RlpStream};
+use crate::trie::SecureTrie;
+use ethereum_types::{Address, H256, U256};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Account {
+    pub nonce: u64,
+    pub balance: U256,
+    pub code: Vec<u8>,
+    pub storage: HashMap<U256, U256>,
+}
+
+impl Account {
+    pub fn code_hash(&self) -> H256 {
+        keccak256(&self.code)
+    }
+
+    pub fn storage_root(&self) -> H256 {
+        storage_root(&self.storage)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nonce == 0 && self.balance.is_zero() && self.code.is_empty()
+    }
+}
+
+// Slots with a value of zero are considered deleted, so they don't affect the root.
+pub fn storage_root(storage: &HashMap<U256, U256>) -> H256 {
+    let mut trie = SecureTrie::new();
+
+    let mut key = [0; 32];
+    for (slot, value) in storage {
+        if value.is_zero() {
+            continue;
+        }
+
+        slot.to_big_endian(&mut key);
+        trie.insert(&key, rlp::encode(value));
+    }
+
+    trie.root_hash()
+}
+
+// All the accounts are included in the trie, even empty ones. Removing them, as required since
+// EIP-161, is up to the caller.
+pub fn state_root(accounts: &HashMap<Address, Account>) -> H256 {
+    let mut trie = SecureTrie::new();
+
+    for (address, account) in accounts {
+        let encoded_account = RlpStream::new()
+            .append(&account.nonce)
+            .append(&account.balance)
+            .append(&account.storage_root())
+            .append(&account.code_hash())
+            .out();
+
+        trie.insert(address.as_bytes(), encoded_account);
+    }
+
+    trie.root_hash()
+}
+
+// The address of a contract deployed with CREATE is the last 20 bytes of keccak256(rlp([sender,
+// nonce])).
+pub fn create_address(sender: &Address, nonce: u64) -> Address {
+    let encoded = RlpStream::new().append(sender).append(&nonce).out();
+
+    Address::from_slice(&keccak256(&encoded)[12..])
+}
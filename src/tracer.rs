@@ -0,0 +1,36 @@
+use crate::instruction_table::InstructionTable;
+use crate::opcode_handlers::StepResult;
+use crate::opcodes::Opcode;
+use crate::vm::VmState;
+
+// What the interpreter does after a tracer is called for an instruction
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum TracerAction {
+    Continue,
+    // Stops the execution without running the instruction, which fails with
+    // ExecutionError::Aborted
+    Abort,
+}
+
+// Observes an execution, one instruction at a time. Both methods do nothing by default, so
+// tracers only implement the ones they need.
+pub trait Tracer {
+    // Called before executing an instruction. vm_state.pc is the instruction's position, and the
+    // instruction table gives the names of custom opcodes.
+    fn step(
+        &mut self,
+        _opcode: Opcode,
+        _vm_state: &VmState,
+        _instruction_table: &InstructionTable,
+    ) -> TracerAction {
+        TracerAction::Continue
+    }
+
+    // Called after executing an instruction, with its result
+    fn step_end(&mut self, _opcode: Opcode, _vm_state: &VmState, _result: &StepResult) {}
+}
+
+// The tracer used by the non-tracing entry points. Its empty methods are optimized away.
+pub(crate) struct NoopTracer;
+
+impl Tracer for NoopTracer {}
@@ -0,0 +1,467 @@
+use crate::context::CallContext;
+use crate::keccak::keccak256;
+use crate::rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
+use crate::signature::Signature;
+use crate::state::create_address;
+use ethereum_types::{Address, H256, U256};
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum TransactionType {
+    Legacy = 0x00,
+    AccessList = 0x01,
+    DynamicFee = 0x02,
+    Blob = 0x03,
+    SetCode = 0x04,
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum TransactionError {
+    Rlp(DecoderError),
+    EmptyInput,
+    UnsupportedType(u8),
+    InvalidFieldCount,
+    // Blob and set code transactions can't create contracts
+    MissingRecipient,
+    EmptyAuthorizationList,
+    InvalidSignature,
+    ChainIdMismatch { expected: u64, actual: u64 },
+}
+
+impl Display for TransactionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for TransactionError {}
+
+impl From<DecoderError> for TransactionError {
+    fn from(error: DecoderError) -> Self {
+        TransactionError::Rlp(error)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AccessListItem {
+    pub address: Address,
+    pub storage_keys: Vec<H256>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Authorization {
+    pub chain_id: U256,
+    pub address: Address,
+    pub nonce: u64,
+    // Invalid authorizations are skipped instead of invalidating the whole transaction, so the y
+    // parity isn't validated when decoding them
+    pub y_parity: u8,
+    pub r: U256,
+    pub s: U256,
+}
+
+// The prefix of the messages signed by EIP-7702 authorizations
+const AUTHORIZATION_MAGIC: u8 = 0x05;
+
+impl Authorization {
+    // Recovers the address of the account that signed the authorization, or returns None if the
+    // signature is invalid.
+    pub fn authority(&self) -> Option<Address> {
+        let y_parity = match self.y_parity {
+            0 => false,
+            1 => true,
+            _ => return None,
+        };
+
+        let mut message = vec![AUTHORIZATION_MAGIC];
+        message.extend_from_slice(
+            &RlpStream::new()
+                .append(&self.chain_id)
+                .append(&self.address)
+                .append(&self.nonce)
+                .out(),
+        );
+
+        let signature = Signature {
+            y_parity,
+            r: self.r,
+            s: self.s,
+        };
+
+        signature.recover(&keccak256(&message))
+    }
+}
+
+// A signed transaction of any of the supported envelope types.
+//
+// Legacy and EIP-2930 transactions have a single gas price, which is used as both
+// `max_fee_per_gas` and `max_priority_fee_per_gas`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Transaction {
+    pub tx_type: TransactionType,
+    // Only legacy transactions created before EIP-155 don't have a chain id
+    pub chain_id: Option<u64>,
+    pub nonce: u64,
+    pub max_priority_fee_per_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub gas_limit: u64,
+    // None for contract creations
+    pub to: Option<Address>,
+    pub value: U256,
+    pub data: Vec<u8>,
+    pub access_list: Vec<AccessListItem>,
+    pub max_fee_per_blob_gas: Option<U256>,
+    pub blob_versioned_hashes: Vec<H256>,
+    pub authorization_list: Vec<Authorization>,
+    pub signature: Signature,
+    pub sender: Address,
+    pub hash: H256,
+}
+
+impl Transaction {
+    // Decodes a transaction in its canonical encoding, as included in blocks and sent with
+    // eth_sendRawTransaction, and recovers its sender. Blob transactions in their network form
+    // (i.e. including the blobs) aren't supported.
+    pub fn decode(raw: &[u8]) -> Result<Transaction, TransactionError> {
+        let first_byte = *raw.first().ok_or(TransactionError::EmptyInput)?;
+
+        if first_byte >= 0xc0 {
+            return decode_legacy(raw);
+        }
+
+        let tx_type = match first_byte {
+            0x01 => TransactionType::AccessList,
+            0x02 => TransactionType::DynamicFee,
+            0x03 => TransactionType::Blob,
+            0x04 => TransactionType::SetCode,
+            _ => return Err(TransactionError::UnsupportedType(first_byte)),
+        };
+
+        decode_typed(tx_type, raw)
+    }
+
+    pub fn is_create(&self) -> bool {
+        self.to.is_none()
+    }
+
+    pub fn is_replay_protected(&self) -> bool {
+        self.chain_id.is_some()
+    }
+
+    // Transactions that aren't replay protected are valid in every chain
+    pub fn validate_chain_id(&self, chain_id: u64) -> Result<(), TransactionError> {
+        match self.chain_id {
+            Some(actual) if actual != chain_id => Err(TransactionError::ChainIdMismatch {
+                expected: chain_id,
+                actual,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn effective_gas_price(&self, base_fee: U256) -> U256 {
+        let (price_with_tip, overflow) = base_fee.overflowing_add(self.max_priority_fee_per_gas);
+
+        if overflow {
+            return self.max_fee_per_gas;
+        }
+
+        std::cmp::min(self.max_fee_per_gas, price_with_tip)
+    }
+
+    // The context of the transaction's top-level call. For contract creations the data is the
+    // init code, so the calldata is left empty.
+    pub fn call_context(&self, base_fee: U256) -> CallContext<'_> {
+        let (contract_address, calldata) = match self.to {
+            Some(to) => (to, self.data.as_slice()),
+            None => (create_address(&self.sender, self.nonce), &[][..]),
+        };
+
+        CallContext {
+            value: self.value,
+            calldata,
+            contract_address,
+            caller_address: self.sender,
+            origin_address: self.sender,
+            gas_price: self.effective_gas_price(base_fee),
+            ..CallContext::default()
+        }
+    }
+}
+
+fn decode_legacy(raw: &[u8]) -> Result<Transaction, TransactionError> {
+    let rlp = Rlp::new(raw)?;
+    let mut fields = FieldReader::new(rlp.items()?);
+
+    let nonce = fields.next()?;
+    let gas_price = fields.next()?;
+    let gas_limit = fields.next()?;
+    let to = fields.next_recipient()?;
+    let value = fields.next()?;
+    let data = fields.next()?;
+
+    let unsigned_fields = fields.consumed();
+
+    let v: u64 = fields.next()?;
+    let r = fields.next()?;
+    let s = fields.next()?;
+    fields.finish()?;
+
+    // Since EIP-155, v encodes the chain id as `chain_id * 2 + 35 + y_parity`
+    let (chain_id, y_parity) = match v {
+        27 | 28 => (None, v == 28),
+        v if v >= 35 => (Some((v - 35) / 2), (v - 35) % 2 == 1),
+        _ => return Err(TransactionError::InvalidSignature),
+    };
+
+    let mut stream = RlpStream::new();
+    for field in unsigned_fields {
+        stream.append_raw(field.as_raw());
+    }
+
+    if let Some(chain_id) = chain_id {
+        stream.append(&chain_id).append(&0u64).append(&0u64);
+    }
+
+    let signing_hash = keccak256(&stream.out());
+    let signature = Signature { y_parity, r, s };
+
+    Ok(Transaction {
+        tx_type: TransactionType::Legacy,
+        chain_id,
+        nonce,
+        max_priority_fee_per_gas: gas_price,
+        max_fee_per_gas: gas_price,
+        gas_limit,
+        to,
+        value,
+        data,
+        access_list: Vec::new(),
+        max_fee_per_blob_gas: None,
+        blob_versioned_hashes: Vec::new(),
+        authorization_list: Vec::new(),
+        signature,
+        sender: recover_sender(&signature, &signing_hash)?,
+        hash: keccak256(raw),
+    })
+}
+
+fn decode_typed(tx_type: TransactionType, raw: &[u8]) -> Result<Transaction, TransactionError> {
+    let rlp = Rlp::new(&raw[1..])?;
+    let mut fields = FieldReader::new(rlp.items()?);
+
+    let chain_id = fields.next()?;
+    let nonce = fields.next()?;
+
+    let (max_priority_fee_per_gas, max_fee_per_gas) = if tx_type == TransactionType::AccessList {
+        let gas_price = fields.next()?;
+        (gas_price, gas_price)
+    } else {
+        (fields.next()?, fields.next()?)
+    };
+
+    let gas_limit = fields.next()?;
+    let to = fields.next_recipient()?;
+    let value = fields.next()?;
+    let data = fields.next()?;
+    let access_list = fields.next_list()?;
+
+    let (max_fee_per_blob_gas, blob_versioned_hashes) = if tx_type == TransactionType::Blob {
+        (Some(fields.next()?), fields.next_list()?)
+    } else {
+        (None, Vec::new())
+    };
+
+    let authorization_list = if tx_type == TransactionType::SetCode {
+        fields.next_list()?
+    } else {
+        Vec::new()
+    };
+
+    let unsigned_fields = fields.consumed();
+
+    let signature = Signature {
+        y_parity: decode_y_parity(fields.next()?)?,
+        r: fields.next()?,
+        s: fields.next()?,
+    };
+    fields.finish()?;
+
+    let is_blob_or_set_code =
+        tx_type == TransactionType::Blob || tx_type == TransactionType::SetCode;
+
+    if is_blob_or_set_code && to.is_none() {
+        return Err(TransactionError::MissingRecipient);
+    }
+
+    if tx_type == TransactionType::SetCode && authorization_list.is_empty() {
+        return Err(TransactionError::EmptyAuthorizationList);
+    }
+
+    let mut stream = RlpStream::new();
+    for field in unsigned_fields {
+        stream.append_raw(field.as_raw());
+    }
+
+    let mut signing_payload = vec![tx_type as u8];
+    signing_payload.extend_from_slice(&stream.out());
+
+    let signing_hash = keccak256(&signing_payload);
+
+    Ok(Transaction {
+        tx_type,
+        chain_id: Some(chain_id),
+        nonce,
+        max_priority_fee_per_gas,
+        max_fee_per_gas,
+        gas_limit,
+        to,
+        value,
+        data,
+        access_list,
+        max_fee_per_blob_gas,
+        blob_versioned_hashes,
+        authorization_list,
+        signature,
+        sender: recover_sender(&signature, &signing_hash)?,
+        hash: keccak256(raw),
+    })
+}
+
+fn recover_sender(signature: &Signature, signing_hash: &H256) -> Result<Address, TransactionError> {
+    signature
+        .recover(signing_hash)
+        .ok_or(TransactionError::InvalidSignature)
+}
+
+fn decode_y_parity(value: u64) -> Result<bool, TransactionError> {
+    match value {
+        0 => Ok(false),
+        1 => Ok(true),
+        _ => Err(TransactionError::InvalidSignature),
+    }
+}
+
+fn decode_byte(rlp: &Rlp) -> Result<u8, DecoderError> {
+    let value: u64 = rlp.as_val()?;
+
+    u8::try_from(value).map_err(|_| DecoderError::IntegerOverflow)
+}
+
+// Reads the fields of a transaction's RLP list in order.
+struct FieldReader<'data> {
+    fields: Vec<Rlp<'data>>,
+    next_field: usize,
+}
+
+impl<'data> FieldReader<'data> {
+    fn new(fields: Vec<Rlp<'data>>) -> FieldReader<'data> {
+        FieldReader {
+            fields,
+            next_field: 0,
+        }
+    }
+
+    fn next_rlp(&mut self) -> Result<Rlp<'data>, TransactionError> {
+        let field = self
+            .fields
+            .get(self.next_field)
+            .ok_or(TransactionError::InvalidFieldCount)?;
+
+        self.next_field += 1;
+
+        Ok(*field)
+    }
+
+    fn next<D: Decodable>(&mut self) -> Result<D, TransactionError> {
+        Ok(self.next_rlp()?.as_val()?)
+    }
+
+    fn next_list<D: Decodable>(&mut self) -> Result<Vec<D>, TransactionError> {
+        Ok(self.next_rlp()?.as_list()?)
+    }
+
+    // The recipient is encoded as an empty string for contract creations
+    fn next_recipient(&mut self) -> Result<Option<Address>, TransactionError> {
+        let field = self.next_rlp()?;
+
+        if field.data()?.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(field.as_val()?))
+    }
+
+    fn consumed(&self) -> Vec<Rlp<'data>> {
+        self.fields[..self.next_field].to_vec()
+    }
+
+    fn finish(&self) -> Result<(), TransactionError> {
+        if self.next_field != self.fields.len() {
+            return Err(TransactionError::InvalidFieldCount);
+        }
+
+        Ok(())
+    }
+}
+
+impl Encodable for AccessListItem {
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        let encoded = RlpStream::new()
+            .append(&self.address)
+            .append_list(&self.storage_keys)
+            .out();
+
+        out.extend_from_slice(&encoded);
+    }
+}
+
+impl Decodable for AccessListItem {
+    fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        let items = rlp.items()?;
+
+        if items.len() != 2 {
+            return Err(DecoderError::InvalidLength);
+        }
+
+        Ok(AccessListItem {
+            address: items[0].as_val()?,
+            storage_keys: items[1].as_list()?,
+        })
+    }
+}
+
+impl Encodable for Authorization {
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        let encoded = RlpStream::new()
+            .append(&self.chain_id)
+            .append(&self.address)
+            .append(&self.nonce)
+            .append(&(self.y_parity as u64))
+            .append(&self.r)
+            .append(&self.s)
+            .out();
+
+        out.extend_from_slice(&encoded);
+    }
+}
+
+impl Decodable for Authorization {
+    fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        let items = rlp.items()?;
+
+        if items.len() != 6 {
+            return Err(DecoderError::InvalidLength);
+        }
+
+        Ok(Authorization {
+            chain_id: items[0].as_val()?,
+            address: items[1].as_val()?,
+            nonce: items[2].as_val()?,
+            y_parity: decode_byte(&items[3])?,
+            r: items[4].as_val()?,
+            s: items[5].as_val()?,
+        })
+    }
+}
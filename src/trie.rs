@@ -0,0 +1,318 @@
+use crate::keccak::keccak256;
+use crate::rlp::{self, RlpStream};
+use ethereum_types::H256;
+
+const EMPTY_STRING: &[u8] = &[];
+
+// An in-memory Merkle Patricia Trie, as defined in the Yellow Paper's Appendix D.
+//
+// Nodes are kept fully expanded, and hashes are computed on demand by `root_hash`.
+#[derive(Debug, Clone, Default)]
+pub struct Trie {
+    root: Node,
+}
+
+#[derive(Debug, Clone, Default)]
+enum Node {
+    #[default]
+    Empty,
+    Leaf(Vec<u8>, Vec<u8>),
+    Extension(Vec<u8>, Box<Node>),
+    Branch(Box<[Node; 16]>, Option<Vec<u8>>),
+}
+
+impl Trie {
+    pub fn new() -> Trie {
+        Trie { root: Node::Empty }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        matches!(self.root, Node::Empty)
+    }
+
+    pub fn get(&self, key: &[u8]) -> Option<&[u8]> {
+        get(&self.root, &to_nibbles(key))
+    }
+
+    // Inserting an empty value is equivalent to deleting the key, as the trie doesn't distinguish
+    // between missing keys and empty values.
+    pub fn insert(&mut self, key: &[u8], value: Vec<u8>) {
+        if value.is_empty() {
+            self.delete(key);
+            return;
+        }
+
+        let root = std::mem::take(&mut self.root);
+        self.root = insert(root, &to_nibbles(key), value);
+    }
+
+    pub fn delete(&mut self, key: &[u8]) {
+        let root = std::mem::take(&mut self.root);
+        self.root = delete(root, &to_nibbles(key));
+    }
+
+    pub fn root_hash(&self) -> H256 {
+        keccak256(&encode_node(&self.root))
+    }
+}
+
+// A trie whose keys are hashed with keccak256 before being inserted, as used by the account and
+// storage tries.
+#[derive(Debug, Clone, Default)]
+pub struct SecureTrie {
+    trie: Trie,
+}
+
+impl SecureTrie {
+    pub fn new() -> SecureTrie {
+        SecureTrie { trie: Trie::new() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.trie.is_empty()
+    }
+
+    pub fn get(&self, key: &[u8]) -> Option<&[u8]> {
+        self.trie.get(keccak256(key).as_bytes())
+    }
+
+    pub fn insert(&mut self, key: &[u8], value: Vec<u8>) {
+        self.trie.insert(keccak256(key).as_bytes(), value)
+    }
+
+    pub fn delete(&mut self, key: &[u8]) {
+        self.trie.delete(keccak256(key).as_bytes())
+    }
+
+    pub fn root_hash(&self) -> H256 {
+        self.trie.root_hash()
+    }
+}
+
+fn get<'node>(node: &'node Node, path: &[u8]) -> Option<&'node [u8]> {
+    match node {
+        Node::Empty => None,
+        Node::Leaf(leaf_path, value) => {
+            if leaf_path.as_slice() == path {
+                Some(value)
+            } else {
+                None
+            }
+        }
+        Node::Extension(extension_path, child) => {
+            if path.starts_with(extension_path) {
+                get(child, &path[extension_path.len()..])
+            } else {
+                None
+            }
+        }
+        Node::Branch(children, value) => {
+            if path.is_empty() {
+                value.as_deref()
+            } else {
+                get(&children[path[0] as usize], &path[1..])
+            }
+        }
+    }
+}
+
+fn insert(node: Node, path: &[u8], value: Vec<u8>) -> Node {
+    match node {
+        Node::Empty => Node::Leaf(path.to_vec(), value),
+        Node::Leaf(leaf_path, leaf_value) => {
+            let common = common_prefix_length(&leaf_path, path);
+
+            if common == leaf_path.len() && common == path.len() {
+                return Node::Leaf(leaf_path, value);
+            }
+
+            let branch = empty_branch();
+            let branch = insert(branch, &leaf_path[common..], leaf_value);
+            let branch = insert(branch, &path[common..], value);
+
+            with_prefix(&path[..common], branch)
+        }
+        Node::Extension(extension_path, child) => {
+            let common = common_prefix_length(&extension_path, path);
+
+            if common == extension_path.len() {
+                let child = insert(*child, &path[common..], value);
+                return Node::Extension(extension_path, Box::new(child));
+            }
+
+            // The new key diverges in the middle of the extension, so we split it in a branch
+            let mut children: Box<[Node; 16]> = Box::default();
+            children[extension_path[common] as usize] =
+                with_prefix(&extension_path[common + 1..], *child);
+
+            let branch = insert(Node::Branch(children, None), &path[common..], value);
+
+            with_prefix(&path[..common], branch)
+        }
+        Node::Branch(mut children, branch_value) => {
+            if path.is_empty() {
+                return Node::Branch(children, Some(value));
+            }
+
+            let index = path[0] as usize;
+            let child = std::mem::take(&mut children[index]);
+            children[index] = insert(child, &path[1..], value);
+
+            Node::Branch(children, branch_value)
+        }
+    }
+}
+
+fn delete(node: Node, path: &[u8]) -> Node {
+    match node {
+        Node::Empty => Node::Empty,
+        Node::Leaf(leaf_path, value) => {
+            if leaf_path.as_slice() == path {
+                Node::Empty
+            } else {
+                Node::Leaf(leaf_path, value)
+            }
+        }
+        Node::Extension(extension_path, child) => {
+            if !path.starts_with(&extension_path) {
+                return Node::Extension(extension_path, child);
+            }
+
+            let child = delete(*child, &path[extension_path.len()..]);
+            with_prefix(&extension_path, child)
+        }
+        Node::Branch(mut children, mut value) => {
+            if path.is_empty() {
+                value = None;
+            } else {
+                let index = path[0] as usize;
+                let child = std::mem::take(&mut children[index]);
+                children[index] = delete(child, &path[1..]);
+            }
+
+            normalize_branch(children, value)
+        }
+    }
+}
+
+// Branches with less than two children and no value aren't valid, so they are collapsed into a leaf
+// or extension.
+fn normalize_branch(mut children: Box<[Node; 16]>, value: Option<Vec<u8>>) -> Node {
+    let mut non_empty = children
+        .iter()
+        .enumerate()
+        .filter(|(_, child)| !matches!(child, Node::Empty))
+        .map(|(index, _)| index);
+
+    let first = non_empty.next();
+    let has_more = non_empty.next().is_some();
+
+    match (first, has_more, value) {
+        (None, _, None) => Node::Empty,
+        (None, _, Some(value)) => Node::Leaf(Vec::new(), value),
+        (Some(index), false, None) => {
+            let child = std::mem::take(&mut children[index]);
+            with_prefix(&[index as u8], child)
+        }
+        (_, _, value) => Node::Branch(children, value),
+    }
+}
+
+// Prepends a path to a node, merging it with the node's own path if it has one.
+fn with_prefix(prefix: &[u8], node: Node) -> Node {
+    if prefix.is_empty() {
+        return node;
+    }
+
+    match node {
+        Node::Empty => Node::Empty,
+        Node::Leaf(path, value) => Node::Leaf(concat(prefix, &path), value),
+        Node::Extension(path, child) => Node::Extension(concat(prefix, &path), child),
+        branch @ Node::Branch(..) => Node::Extension(prefix.to_vec(), Box::new(branch)),
+    }
+}
+
+fn empty_branch() -> Node {
+    Node::Branch(Box::default(), None)
+}
+
+fn encode_node(node: &Node) -> Vec<u8> {
+    match node {
+        Node::Empty => rlp::encode(EMPTY_STRING),
+        Node::Leaf(path, value) => RlpStream::new()
+            .append(&hex_prefix(path, true))
+            .append(value)
+            .out(),
+        Node::Extension(path, child) => RlpStream::new()
+            .append(&hex_prefix(path, false))
+            .append_raw(&node_reference(child))
+            .out(),
+        Node::Branch(children, value) => {
+            let mut stream = RlpStream::new();
+
+            for child in children.iter() {
+                stream.append_raw(&node_reference(child));
+            }
+
+            stream
+                .append(value.as_deref().unwrap_or(EMPTY_STRING))
+                .out()
+        }
+    }
+}
+
+// Nodes whose encoding is shorter than 32 bytes are inlined into their parents. The rest are
+// referenced by their hash.
+fn node_reference(node: &Node) -> Vec<u8> {
+    let encoded = encode_node(node);
+
+    if encoded.len() < 32 {
+        encoded
+    } else {
+        rlp::encode(&keccak256(&encoded))
+    }
+}
+
+fn hex_prefix(nibbles: &[u8], is_leaf: bool) -> Vec<u8> {
+    let flag = if is_leaf { 2 } else { 0 };
+    let is_odd = nibbles.len() % 2 == 1;
+
+    let mut encoded = Vec::with_capacity(nibbles.len() / 2 + 1);
+
+    let rest = if is_odd {
+        encoded.push(((flag + 1) << 4) | nibbles[0]);
+        &nibbles[1..]
+    } else {
+        encoded.push(flag << 4);
+        nibbles
+    };
+
+    for pair in rest.chunks(2) {
+        encoded.push((pair[0] << 4) | pair[1]);
+    }
+
+    encoded
+}
+
+fn to_nibbles(key: &[u8]) -> Vec<u8> {
+    let mut nibbles = Vec::with_capacity(key.len() * 2);
+
+    for byte in key {
+        nibbles.push(byte >> 4);
+        nibbles.push(byte & 0x0f);
+    }
+
+    nibbles
+}
+
+fn common_prefix_length(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
+fn concat(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(a.len() + b.len());
+    result.extend_from_slice(a);
+    result.extend_from_slice(b);
+
+    result
+}
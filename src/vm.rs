@@ -1,15 +1,17 @@
+use crate::log::Log;
 use crate::memory::Memory;
 use crate::stack::Stack;
 use ethereum_types::U256;
 use std::collections::HashMap;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct VmState {
     pub pc: usize,
     pub stack: Stack,
     pub memory: Memory,
     pub return_data: Vec<u8>,
     pub storage: HashMap<U256, U256>,
+    pub logs: Vec<Log>,
 }
 
 // Solidity always writes the free pointer in 0x40, so we same some allocations by starting with
@@ -24,6 +26,13 @@ impl VmState {
             memory: Memory::with_capacity(INITIAL_MEMORY_CAPACITY),
             return_data: Vec::new(),
             storage: HashMap::new(),
+            logs: Vec::new(),
         }
     }
 }
+
+impl Default for VmState {
+    fn default() -> Self {
+        VmState::new()
+    }
+}
@@ -0,0 +1,259 @@
+extern crate tiny_evm;
+
+use ethereum_types::{Address, H256, U256};
+use tiny_evm::abi::{decode, encode, Abi, AbiError, AbiValue, Event, Function, ParamType};
+use tiny_evm::{Log, RevertDecoder, RevertReason};
+
+#[test]
+fn encodes_and_decodes_calls() {
+    // The example of the Solidity ABI specification
+    let function = Function::parse("function f(uint256, uint32[], bytes10, bytes)").unwrap();
+    assert_eq!(function.signature(), "f(uint256,uint32[],bytes10,bytes)");
+    assert_eq!(function.selector(), [0x8b, 0xe6, 0x52, 0x46]);
+
+    let arguments = vec![
+        AbiValue::from(0x123u64),
+        AbiValue::Array(vec![AbiValue::from(0x456u64), AbiValue::from(0x789u64)]),
+        AbiValue::FixedBytes(b"1234567890".to_vec()),
+        AbiValue::from(b"Hello, world!".to_vec()),
+    ];
+
+    let calldata = function.encode_input(&arguments).unwrap();
+    assert_eq!(
+        hex::encode(&calldata),
+        "8be65246\
+         0000000000000000000000000000000000000000000000000000000000000123\
+         0000000000000000000000000000000000000000000000000000000000000080\
+         3132333435363738393000000000000000000000000000000000000000000000\
+         00000000000000000000000000000000000000000000000000000000000000e0\
+         0000000000000000000000000000000000000000000000000000000000000002\
+         0000000000000000000000000000000000000000000000000000000000000456\
+         0000000000000000000000000000000000000000000000000000000000000789\
+         000000000000000000000000000000000000000000000000000000000000000d\
+         48656c6c6f2c20776f726c642100000000000000000000000000000000000000"
+    );
+    assert_eq!(function.decode_input(&calldata).unwrap(), arguments);
+
+    assert_eq!(
+        function.encode_input(&arguments[1..]),
+        Err(AbiError::InvalidArguments)
+    );
+
+    // Nested dynamic types and negative integers
+    let types = vec![
+        ParamType::parse("(string,int8)[]").unwrap(),
+        ParamType::parse("uint256[][2]").unwrap(),
+    ];
+    let values = vec![
+        AbiValue::Array(vec![
+            AbiValue::Tuple(vec![AbiValue::from("a"), AbiValue::int(-3)]),
+            AbiValue::Tuple(vec![AbiValue::from("bc"), AbiValue::int(127)]),
+        ]),
+        AbiValue::FixedArray(vec![
+            AbiValue::Array(vec![]),
+            AbiValue::Array(vec![AbiValue::from(U256::MAX)]),
+        ]),
+    ];
+    assert_eq!(decode(&types, &encode(&values)).unwrap(), values);
+    assert_eq!(values[0].to_string(), "[(\"a\",-3),(\"bc\",127)]");
+}
+
+#[test]
+fn parses_json_abis_and_decodes_events() {
+    let abi = Abi::from_json(
+        r#"[
+            {
+                "type": "function",
+                "name": "balanceOf",
+                "stateMutability": "view",
+                "inputs": [{ "name": "owner", "type": "address" }],
+                "outputs": [{ "name": "", "type": "uint256" }]
+            },
+            {
+                "type": "event",
+                "name": "Transfer",
+                "anonymous": false,
+                "inputs": [
+                    { "name": "from", "type": "address", "indexed": true },
+                    { "name": "to", "type": "address", "indexed": true },
+                    {
+                        "name": "details",
+                        "type": "tuple",
+                        "indexed": false,
+                        "components": [
+                            { "name": "value", "type": "uint256" },
+                            { "name": "memo", "type": "string" }
+                        ]
+                    }
+                ]
+            },
+            { "type": "error", "name": "Unauthorized", "inputs": [] },
+            { "type": "constructor", "inputs": [] }
+        ]"#,
+    )
+    .unwrap();
+
+    let balance_of = abi.function("balanceOf").unwrap();
+    assert_eq!(
+        balance_of.decode_output(&encode(&[AbiValue::from(5u64)])),
+        Ok(vec![AbiValue::from(5u64)])
+    );
+
+    let mut revert_decoder = RevertDecoder::new();
+    revert_decoder.add_abi(&abi);
+    assert_eq!(
+        revert_decoder.decode(&abi.error("Unauthorized").unwrap().selector()),
+        Some(RevertReason::Custom {
+            signature: "Unauthorized()".to_string(),
+            arguments: vec![],
+        })
+    );
+
+    let transfer = abi.event("Transfer").unwrap();
+    assert_eq!(
+        transfer.signature(),
+        "Transfer(address,address,(uint256,string))"
+    );
+
+    let details = AbiValue::Tuple(vec![AbiValue::from(7u64), AbiValue::from("rent")]);
+    let log = Log {
+        address: Address::zero(),
+        topics: vec![
+            transfer.topic(),
+            H256::from(Address::from_low_u64_be(1)),
+            H256::from(Address::from_low_u64_be(2)),
+        ],
+        data: encode(std::slice::from_ref(&details)),
+    };
+
+    assert_eq!(
+        transfer.decode_log(&log).unwrap(),
+        vec![
+            AbiValue::from(Address::from_low_u64_be(1)),
+            AbiValue::from(Address::from_low_u64_be(2)),
+            details,
+        ]
+    );
+}
+
+fn words(hex_words: &[&str]) -> Vec<u8> {
+    hex_words
+        .iter()
+        .flat_map(|word| hex::decode(format!("{:0>64}", word)).unwrap())
+        .collect()
+}
+
+#[test]
+fn rejects_invalid_encodings() {
+    let decode_one =
+        |type_name: &str, data: &[u8]| decode(&[ParamType::parse(type_name).unwrap()], data);
+
+    // Truncated data
+    assert_eq!(
+        decode_one("uint256", &[0; 31]),
+        Err(AbiError::InputTooShort)
+    );
+    assert_eq!(
+        decode_one("bytes", &words(&["20", "21", "00"])),
+        Err(AbiError::InputTooShort)
+    );
+
+    // Offsets past the end of the data, or that don't fit in a usize
+    assert_eq!(
+        decode_one("bytes", &words(&["40"])),
+        Err(AbiError::InvalidOffset)
+    );
+    assert_eq!(
+        decode_one("string", &words(&[&"f".repeat(64)])),
+        Err(AbiError::InvalidOffset)
+    );
+
+    // An array length whose elements can't fit in the data
+    assert_eq!(
+        decode_one("uint256[]", &words(&["20", "10000000000"])),
+        Err(AbiError::InputTooShort)
+    );
+
+    // Values that aren't padded as their type requires
+    assert_eq!(
+        decode_one("bool", &words(&["2"])),
+        Err(AbiError::InvalidValue)
+    );
+    assert_eq!(
+        decode_one("address", &words(&[&format!("01{:0>62}", "1")])),
+        Err(AbiError::InvalidValue)
+    );
+    assert_eq!(
+        decode_one("bytes1", &words(&["1"])),
+        Err(AbiError::InvalidValue)
+    );
+    assert_eq!(
+        decode_one("uint8", &words(&["100"])),
+        Err(AbiError::InvalidValue)
+    );
+    assert_eq!(
+        decode_one("uint8", &words(&["ff"])),
+        Ok(vec![AbiValue::from(255u64)])
+    );
+
+    // Negative intN values must be sign-extended to 256 bits
+    assert_eq!(
+        decode_one("int8", &words(&["80"])),
+        Err(AbiError::InvalidValue)
+    );
+    assert_eq!(
+        decode_one("int8", &words(&[&format!("{:f>64}", "80")])),
+        Ok(vec![AbiValue::int(-128)])
+    );
+    assert_eq!(
+        decode_one("int8", &words(&[&format!("{:f>64}", "7f")])),
+        Err(AbiError::InvalidValue)
+    );
+}
+
+#[test]
+fn checks_the_topics_of_logs() {
+    let event = Event::parse("event Ping(address indexed from, uint256 value)").unwrap();
+    let from = H256::from(Address::from_low_u64_be(1));
+    let log = |topics: Vec<H256>| Log {
+        address: Address::zero(),
+        topics,
+        data: words(&["7"]),
+    };
+
+    assert_eq!(
+        event.decode_log(&log(vec![event.topic(), from])),
+        Ok(vec![
+            AbiValue::from(Address::from_low_u64_be(1)),
+            AbiValue::from(7u64),
+        ])
+    );
+    assert_eq!(
+        event.decode_log(&log(vec![H256::zero(), from])),
+        Err(AbiError::InvalidLog)
+    );
+    assert_eq!(
+        event.decode_log(&log(vec![event.topic()])),
+        Err(AbiError::InvalidLog)
+    );
+    assert_eq!(
+        event.decode_log(&log(vec![event.topic(), from, from])),
+        Err(AbiError::InvalidLog)
+    );
+
+    // Anonymous events don't have the topic of their signature
+    let anonymous =
+        Event::parse("event Ping(address indexed from, uint256 value) anonymous").unwrap();
+    assert!(anonymous.anonymous);
+    assert_eq!(
+        anonymous.decode_log(&log(vec![from])),
+        Ok(vec![
+            AbiValue::from(Address::from_low_u64_be(1)),
+            AbiValue::from(7u64),
+        ])
+    );
+    assert_eq!(
+        anonymous.decode_log(&log(vec![from, from])),
+        Err(AbiError::InvalidLog)
+    );
+}
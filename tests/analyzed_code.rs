@@ -0,0 +1,161 @@
+extern crate tiny_evm;
+
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::thread;
+use tiny_evm::{
+    analyze_basic_blocks, run, run_analyzed, AnalyzedCode, BasicBlock, BlockContext, Bytecode,
+    CallContext, CodeCache, ExecutionError, Opcode,
+};
+
+// PUSH1 4 JUMP INVALID JUMPDEST PUSH1 1 PUSH1 0 RETURN
+const CODE: [u8; 11] = [
+    0x60, 0x04, 0x56, 0xfe, 0x5b, 0x60, 0x01, 0x60, 0x00, 0xf3, 0x00,
+];
+
+#[test]
+fn runs_analyzed_code() {
+    let analyzed = AnalyzedCode::new(&CODE);
+
+    assert_eq!(analyzed.code(), &CODE[..]);
+
+    let result = run_analyzed(&analyzed, &CallContext::default(), &BlockContext::default());
+
+    assert_eq!(result.error, None);
+    assert_eq!(result.return_data, vec![0]);
+}
+
+#[test]
+fn checks_the_stack_per_block_like_per_instruction() {
+    let cases = [
+        // PUSH1 1 ADD
+        (
+            "600101",
+            Some(ExecutionError::StackUnderflow {
+                pc: 2,
+                opcode: Opcode::ADD,
+            }),
+        ),
+        // PUSH1 3 JUMP JUMPDEST POP, which underflows in the block it jumps to
+        (
+            "6003565b50",
+            Some(ExecutionError::StackUnderflow {
+                pc: 4,
+                opcode: Opcode::POP,
+            }),
+        ),
+        // JUMPDEST PUSH1 1 PUSH1 0 JUMP, which grows the stack by one per iteration. The block fits
+        // until the stack has 1023 items, and then the second PUSH1 overflows it.
+        (
+            "5b6001600056",
+            Some(ExecutionError::StackOverflow {
+                pc: 3,
+                opcode: Opcode::PUSH1,
+            }),
+        ),
+        // PUSH1 1 DUP1 ADD PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+        ("6001800160005260206000f3", None),
+    ];
+
+    for (code, expected_error) in cases.iter() {
+        let code = hex::decode(code).unwrap();
+
+        let per_instruction = run(
+            &Bytecode::new(&code),
+            &CallContext::default(),
+            &BlockContext::default(),
+        );
+        let per_block = run_analyzed(
+            &AnalyzedCode::new(&code),
+            &CallContext::default(),
+            &BlockContext::default(),
+        );
+
+        assert_eq!(per_block.error, *expected_error);
+        assert_eq!(per_block.error, per_instruction.error);
+        assert_eq!(per_block.halt_reason, per_instruction.halt_reason);
+        assert_eq!(per_block.return_data, per_instruction.return_data);
+    }
+}
+
+#[test]
+fn caches_analysis_by_code_hash() {
+    let cache = CodeCache::new(NonZeroUsize::new(1).unwrap());
+
+    let first = cache.get_or_analyze(&CODE);
+    let second = cache.get_or_analyze(&CODE);
+    assert!(Arc::ptr_eq(&first, &second));
+
+    // The cache only has room for one entry, so this evicts CODE
+    let other = cache.get_or_analyze(&[0x00]);
+    assert_eq!(cache.len(), 1);
+    assert!(cache.get(&first.code_hash()).is_none());
+    assert!(cache.get(&other.code_hash()).is_some());
+}
+
+#[test]
+fn can_be_shared_between_threads() {
+    let cache = Arc::new(CodeCache::new(NonZeroUsize::new(16).unwrap()));
+
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let cache = Arc::clone(&cache);
+            thread::spawn(move || {
+                let analyzed = cache.get_or_analyze(&CODE);
+                run_analyzed(&analyzed, &CallContext::default(), &BlockContext::default()).error
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), None);
+    }
+
+    assert_eq!(cache.len(), 1);
+}
+
+#[test]
+fn splits_code_into_basic_blocks() {
+    let analyzed = AnalyzedCode::new(&CODE);
+    let blocks = analyzed.basic_blocks();
+
+    assert_eq!(
+        blocks[0],
+        BasicBlock {
+            start: 0,
+            end: 3,
+            static_gas: 3 + 8,
+            stack_required: 0,
+            stack_max_growth: 1,
+        }
+    );
+
+    // INVALID ends a block by itself
+    assert_eq!((blocks[1].start, blocks[1].end), (3, 4));
+
+    assert_eq!(
+        analyzed.basic_block_at(4),
+        Some(&BasicBlock {
+            start: 4,
+            end: 10,
+            static_gas: 1 + 3 + 3,
+            stack_required: 0,
+            stack_max_growth: 2,
+        })
+    );
+
+    assert!(!blocks[2].fits_stack(1023, 1024));
+    assert_eq!(blocks.len(), 4);
+}
+
+#[test]
+fn computes_required_stack_height() {
+    // POP ADD PUSH1 1 DUP3 STOP
+    let blocks = analyze_basic_blocks(&[0x50, 0x01, 0x60, 0x01, 0x82, 0x00]);
+
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].stack_required, 4);
+    assert_eq!(blocks[0].stack_max_growth, 0);
+    assert!(blocks[0].fits_stack(4, 1024));
+    assert!(!blocks[0].fits_stack(3, 1024));
+}
@@ -0,0 +1,105 @@
+extern crate tiny_evm;
+
+use ethereum_types::{U256, U512};
+use std::convert::TryFrom;
+use tiny_evm::arithmetic;
+
+// A xorshift generator, biased towards values with zero limbs and extreme values, which are the
+// edge cases of the limb arithmetic
+struct Generator(u64);
+
+impl Generator {
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn next_u256(&mut self) -> U256 {
+        let mut limbs = [0; 4];
+
+        for limb in limbs.iter_mut() {
+            *limb = match self.next_u64() % 4 {
+                0 => 0,
+                1 => u64::MAX,
+                _ => self.next_u64(),
+            };
+        }
+
+        U256(limbs)
+    }
+}
+
+fn reference_addmod(a: U256, b: U256, modulus: U256) -> U256 {
+    if modulus.is_zero() {
+        return U256::zero();
+    }
+
+    U256::try_from((U512::from(a) + U512::from(b)) % U512::from(modulus)).unwrap()
+}
+
+fn reference_mulmod(a: U256, b: U256, modulus: U256) -> U256 {
+    if modulus.is_zero() {
+        return U256::zero();
+    }
+
+    U256::try_from((U512::from(a) * U512::from(b)) % U512::from(modulus)).unwrap()
+}
+
+#[test]
+fn matches_reference_implementation() {
+    let mut generator = Generator(0x2545f4914f6cdd1d);
+
+    for _ in 0..20000 {
+        let a = generator.next_u256();
+        let b = generator.next_u256();
+        let modulus = generator.next_u256();
+
+        assert_eq!(arithmetic::add(a, b), a.overflowing_add(b).0);
+        assert_eq!(arithmetic::sub(a, b), a.overflowing_sub(b).0);
+
+        let expected_mod = if modulus.is_zero() {
+            U256::zero()
+        } else {
+            a % modulus
+        };
+        assert_eq!(arithmetic::modulo(a, modulus), expected_mod);
+
+        assert_eq!(
+            arithmetic::addmod(a, b, modulus),
+            reference_addmod(a, b, modulus)
+        );
+        assert_eq!(
+            arithmetic::mulmod(a, b, modulus),
+            reference_mulmod(a, b, modulus)
+        );
+    }
+}
+
+#[test]
+fn computes_exponentiations() {
+    let mut generator = Generator(0x9e3779b97f4a7c15);
+
+    for _ in 0..2000 {
+        let base = generator.next_u256();
+        let exponent = U256::from(generator.next_u64() % 1024);
+
+        assert_eq!(
+            arithmetic::exp(base, exponent),
+            base.overflowing_pow(exponent).0
+        );
+    }
+
+    let max = U256::max_value();
+    assert_eq!(
+        arithmetic::exp(U256::from(2), U256::from(255)),
+        U256::one() << 255
+    );
+    assert_eq!(
+        arithmetic::exp(U256::from(2), U256::from(256)),
+        U256::zero()
+    );
+    assert_eq!(arithmetic::exp(max, max), max);
+    assert_eq!(arithmetic::exp(U256::zero(), U256::zero()), U256::one());
+}
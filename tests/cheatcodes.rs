@@ -0,0 +1,191 @@
+extern crate tiny_evm;
+
+use ethereum_types::{Address, H256, U256};
+use sha3::{Digest, Keccak256};
+use tiny_evm::abi::{encode, selector, AbiValue, Function};
+use tiny_evm::{create_address, Evm, EvmError, Log, CHEATCODE_ADDRESS};
+
+fn cheatcode(evm: &mut Evm, signature: &str, arguments: &[AbiValue]) -> Vec<u8> {
+    let calldata = Function::parse(signature)
+        .unwrap()
+        .encode_input(arguments)
+        .unwrap();
+
+    evm.transact(Address::zero(), CHEATCODE_ADDRESS, U256::zero(), &calldata)
+        .unwrap()
+        .return_data
+}
+
+#[test]
+fn pranks_and_changes_the_state() {
+    assert_eq!(
+        CHEATCODE_ADDRESS.as_bytes(),
+        &Keccak256::digest(b"hevm cheat code")[12..]
+    );
+
+    let alice = Address::from_low_u64_be(1);
+    let bob = Address::from_low_u64_be(2);
+    let contract = Address::from_low_u64_be(3);
+
+    let mut evm = Evm::new();
+    evm.enable_cheatcodes();
+
+    // CALLER PUSH1 0 MSTORE ORIGIN PUSH1 32 MSTORE TIMESTAMP PUSH1 64 MSTORE PUSH1 96 PUSH1 0 RETURN
+    evm.set_code(
+        contract,
+        hex::decode("33600052326020524260405260606000f3").unwrap(),
+    );
+
+    cheatcode(&mut evm, "warp(uint256)", &[AbiValue::from(1000u64)]);
+    cheatcode(&mut evm, "roll(uint256)", &[AbiValue::from(20u64)]);
+    cheatcode(
+        &mut evm,
+        "deal(address,uint256)",
+        &[AbiValue::from(bob), AbiValue::from(50u64)],
+    );
+    cheatcode(&mut evm, "prank(address)", &[AbiValue::from(bob)]);
+
+    // Rejected transactions don't use the prank
+    assert_eq!(
+        evm.transact(alice, contract, U256::from(1000), &[]).err(),
+        Some(EvmError::InsufficientBalance)
+    );
+
+    let result = evm.transact(alice, contract, U256::from(20), &[]).unwrap();
+    assert_eq!(
+        result.return_data,
+        encode(&[
+            AbiValue::from(bob),
+            AbiValue::from(alice),
+            AbiValue::from(1000u64)
+        ])
+    );
+    assert_eq!(evm.block.number, 20);
+    assert_eq!(evm.balance(&bob), U256::from(30));
+    assert_eq!(evm.nonce(&bob), 1);
+
+    // The prank only lasts for one transaction
+    let result = evm.transact(alice, contract, U256::zero(), &[]).unwrap();
+    assert_eq!(&result.return_data[12..32], alice.as_bytes());
+
+    let key = AbiValue::FixedBytes(H256::from_low_u64_be(1).as_bytes().to_vec());
+    let value = AbiValue::FixedBytes(H256::from_low_u64_be(7).as_bytes().to_vec());
+    cheatcode(
+        &mut evm,
+        "store(address,bytes32,bytes32)",
+        &[AbiValue::from(contract), key.clone(), value.clone()],
+    );
+    assert_eq!(evm.storage(&contract, &U256::one()), U256::from(7));
+    assert_eq!(
+        cheatcode(
+            &mut evm,
+            "load(address,bytes32)",
+            &[AbiValue::from(contract), key]
+        ),
+        encode(&[value])
+    );
+
+    assert_eq!(
+        evm.transact(
+            alice,
+            CHEATCODE_ADDRESS,
+            U256::zero(),
+            &selector("unknown()")
+        )
+        .err(),
+        Some(EvmError::InvalidCheatcode)
+    );
+
+    let calldata = Function::parse("roll(uint256)")
+        .unwrap()
+        .encode_input(&[AbiValue::from(30u64)])
+        .unwrap();
+    assert_eq!(
+        evm.transact(alice, CHEATCODE_ADDRESS, U256::one(), &calldata)
+            .err(),
+        Some(EvmError::InvalidCheatcode)
+    );
+    assert_eq!(evm.block.number, 20);
+}
+
+#[test]
+fn keeps_the_prank_when_a_deployment_collides() {
+    let alice = Address::from_low_u64_be(1);
+    let bob = Address::from_low_u64_be(2);
+    let contract = Address::from_low_u64_be(3);
+
+    let mut evm = Evm::new();
+    evm.enable_cheatcodes();
+
+    // CALLER PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+    let code = hex::decode("3360005260206000f3").unwrap();
+    evm.set_code(contract, code.clone());
+    evm.set_code(create_address(&bob, 0), code);
+
+    cheatcode(&mut evm, "prank(address)", &[AbiValue::from(bob)]);
+
+    assert_eq!(
+        evm.deploy(alice, &[], U256::zero()).err(),
+        Some(EvmError::AddressCollision)
+    );
+    assert_eq!(evm.nonce(&bob), 0);
+
+    let result = evm.transact(alice, contract, U256::zero(), &[]).unwrap();
+    assert_eq!(&result.return_data[12..], bob.as_bytes());
+}
+
+#[test]
+fn expects_reverts_and_emits() {
+    let sender = Address::from_low_u64_be(1);
+    let reverter = Address::from_low_u64_be(2);
+    let emitter = Address::from_low_u64_be(3);
+
+    let mut evm = Evm::new();
+    evm.enable_cheatcodes();
+
+    // Reverts with its calldata
+    // CALLDATASIZE PUSH1 0 PUSH1 0 CALLDATACOPY CALLDATASIZE PUSH1 0 REVERT
+    evm.set_code(reverter, hex::decode("366000600037366000fd").unwrap());
+
+    // PUSH1 1 PUSH1 0 SSTORE PUSH1 0xaa PUSH1 0 PUSH1 0 LOG1 STOP
+    evm.set_code(emitter, hex::decode("600160005560aa60006000a100").unwrap());
+
+    let mut error = selector("Error(string)").to_vec();
+    error.extend(encode(&[AbiValue::from("nope")]));
+
+    cheatcode(
+        &mut evm,
+        "expectRevert(bytes)",
+        &[AbiValue::from(b"nope".to_vec())],
+    );
+    let result = evm
+        .transact(sender, reverter, U256::zero(), &error)
+        .unwrap();
+    assert!(!result.is_success());
+
+    cheatcode(&mut evm, "expectRevert()", &[]);
+    assert_eq!(
+        evm.transact(sender, emitter, U256::zero(), &[]).err(),
+        Some(EvmError::MissingRevert)
+    );
+    assert_eq!(evm.storage(&emitter, &U256::zero()), U256::zero());
+
+    let log = Log {
+        address: emitter,
+        topics: vec![H256::from_low_u64_be(0xaa)],
+        data: vec![],
+    };
+
+    evm.expect_emit(log.clone());
+    let result = evm.transact(sender, emitter, U256::zero(), &[]).unwrap();
+    assert_eq!(result.logs, vec![log.clone()]);
+
+    evm.expect_emit(Log {
+        topics: vec![H256::from_low_u64_be(0xbb)],
+        ..log
+    });
+    assert_eq!(
+        evm.transact(sender, emitter, U256::zero(), &[]).err(),
+        Some(EvmError::MissingEmit)
+    );
+}
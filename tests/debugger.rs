@@ -0,0 +1,121 @@
+extern crate tiny_evm;
+
+use ethereum_types::U256;
+use std::num::NonZeroUsize;
+use tiny_evm::{
+    run, AnalyzedCode, BlockContext, Breakpoint, Bytecode, CallContext, Debugger, HaltReason,
+    InstructionTable, Opcode,
+};
+
+// PUSH1 5 PUSH1 0 SSTORE PUSH1 7 PUSH1 1 SSTORE PUSH1 0x2a PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+const CODE: &str = "60056000556007600155602a60005260206000f3";
+
+#[test]
+fn steps_forwards_and_backwards() {
+    let code = hex::decode(CODE).unwrap();
+    let call_context = CallContext::default();
+    let block_context = BlockContext::default();
+    let mut debugger = Debugger::new(&code, &call_context, &block_context);
+
+    assert_eq!(debugger.opcode(), Some(Opcode::PUSH1));
+    assert!(!debugger.step_back());
+
+    assert!(debugger.step());
+    assert!(debugger.step_over());
+    assert_eq!(debugger.pc(), 4);
+    assert_eq!(
+        debugger.vm_state().stack.as_slice(),
+        &[U256::from(5), U256::zero()]
+    );
+
+    debugger.add_breakpoint(Breakpoint::StorageWrite(U256::one()));
+    debugger.add_breakpoint(Breakpoint::Opcode(Opcode::MSTORE));
+
+    assert_eq!(
+        debugger.resume(),
+        Some(Breakpoint::StorageWrite(U256::one()))
+    );
+    assert_eq!(debugger.pc(), 9);
+    assert_eq!(debugger.vm_state().storage.len(), 1);
+
+    assert_eq!(debugger.resume(), Some(Breakpoint::Opcode(Opcode::MSTORE)));
+    assert_eq!(debugger.resume(), None);
+    assert_eq!(debugger.outcome(), Some(Ok(HaltReason::Return)));
+    assert!(debugger.memory_dump().starts_with("0000: 00000000"));
+    assert!(!debugger.step());
+
+    // Running backwards stops at the breakpoints too
+    assert!(debugger.remove_breakpoint(Breakpoint::Opcode(Opcode::MSTORE)));
+    assert_eq!(
+        debugger.resume_backwards(),
+        Some(Breakpoint::StorageWrite(U256::one()))
+    );
+    assert!(debugger.outcome().is_none());
+    assert_eq!(debugger.vm_state().return_data, Vec::<u8>::new());
+    assert_eq!(debugger.vm_state().storage.len(), 1);
+
+    assert_eq!(debugger.resume_backwards(), None);
+    assert_eq!(debugger.steps(), 0);
+    assert_eq!(debugger.pc(), 0);
+}
+
+#[test]
+fn finishes_like_run() {
+    let code = hex::decode(CODE).unwrap();
+    let call_context = CallContext::default();
+    let block_context = BlockContext::default();
+    let mut debugger = Debugger::new(&code, &call_context, &block_context);
+
+    assert!(debugger.result().is_none());
+    assert_eq!(debugger.resume(), None);
+
+    let result = debugger.result().unwrap();
+    let expected = run(&Bytecode::new(&code), &call_context, &block_context);
+
+    assert_eq!(result.return_data, expected.return_data);
+    assert_eq!(result.halt_reason, expected.halt_reason);
+    assert_eq!(result.storage, expected.storage);
+    assert_eq!(debugger.steps(), 12);
+
+    let analyzed = AnalyzedCode::new(&code);
+    let mut debugger = Debugger::with_analyzed_code(
+        &analyzed,
+        InstructionTable::default_table(),
+        &call_context,
+        &block_context,
+    );
+
+    assert_eq!(debugger.resume(), None);
+    assert_eq!(debugger.result().unwrap().return_data, expected.return_data);
+}
+
+#[test]
+fn keeps_a_bounded_history() {
+    let code = hex::decode(CODE).unwrap();
+    let call_context = CallContext::default();
+    let block_context = BlockContext::default();
+    let mut debugger = Debugger::new(&code, &call_context, &block_context);
+
+    // A state is recorded every 2 instructions, keeping the last 3 of them
+    debugger.set_history(NonZeroUsize::new(2).unwrap(), NonZeroUsize::new(3).unwrap());
+    debugger.add_breakpoint(Breakpoint::Pc(7));
+
+    assert_eq!(debugger.resume(), Some(Breakpoint::Pc(7)));
+    assert_eq!(debugger.resume(), None);
+    assert_eq!(debugger.steps(), 12);
+
+    // Stepping back replays from the closest recorded state
+    assert!(debugger.step_back());
+    assert_eq!(debugger.steps(), 11);
+    assert_eq!(debugger.opcode(), Some(Opcode::RETURN));
+    assert_eq!(debugger.vm_state().stack.as_slice().len(), 2);
+
+    // The states after 6, 8 and 10 instructions are recorded, so the breakpoint at pc 7, which is
+    // reached after 4, can't be
+    assert_eq!(debugger.resume_backwards(), None);
+    assert_eq!(debugger.steps(), 6);
+    assert!(!debugger.step_back());
+
+    assert_eq!(debugger.resume(), None);
+    assert_eq!(debugger.outcome(), Some(Ok(HaltReason::Return)));
+}
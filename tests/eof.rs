@@ -0,0 +1,188 @@
+extern crate tiny_evm;
+
+use tiny_evm::{run_eof, BlockContext, CallContext, EofContainer, EofError};
+
+// (inputs, outputs, max_stack_height, code)
+type Section<'a> = (u8, u8, u16, &'a [u8]);
+
+fn encode_container(sections: &[Section], data: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![0xef, 0x00, 0x01];
+
+    bytes.push(0x01);
+    bytes.extend_from_slice(&((sections.len() * 4) as u16).to_be_bytes());
+
+    bytes.push(0x02);
+    bytes.extend_from_slice(&(sections.len() as u16).to_be_bytes());
+    for (_, _, _, code) in sections {
+        bytes.extend_from_slice(&(code.len() as u16).to_be_bytes());
+    }
+
+    bytes.push(0xff);
+    bytes.extend_from_slice(&(data.len() as u16).to_be_bytes());
+    bytes.push(0x00);
+
+    for (inputs, outputs, max_stack_height, _) in sections {
+        bytes.push(*inputs);
+        bytes.push(*outputs);
+        bytes.extend_from_slice(&max_stack_height.to_be_bytes());
+    }
+
+    for (_, _, _, code) in sections {
+        bytes.extend_from_slice(code);
+    }
+
+    bytes.extend_from_slice(data);
+
+    bytes
+}
+
+fn execute(sections: &[Section], data: &[u8]) -> Vec<u8> {
+    let container = EofContainer::parse(&encode_container(sections, data)).unwrap();
+    let result = run_eof(
+        &container,
+        &CallContext::default(),
+        &BlockContext::default(),
+    );
+
+    assert_eq!(result.error, None);
+
+    result.return_data
+}
+
+fn validate(sections: &[Section], data: &[u8]) -> Result<(), EofError> {
+    EofContainer::parse(&encode_container(sections, data)).map(|_| ())
+}
+
+// PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN, which returns the value on top of the stack
+const RETURN_TOP: [u8; 8] = [0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3];
+
+fn word(value: u8) -> Vec<u8> {
+    let mut word = vec![0; 32];
+    word[31] = value;
+    word
+}
+
+#[test]
+fn calls_and_returns_from_sections() {
+    let mut main = vec![0xe3, 0x00, 0x01];
+    main.extend_from_slice(&RETURN_TOP);
+
+    // PUSH1 7 RETF
+    let returns_seven = [0x60, 0x07, 0xe4];
+
+    assert_eq!(
+        execute(&[(0, 0x80, 2, &main), (0, 1, 1, &returns_seven)], &[]),
+        word(7)
+    );
+}
+
+#[test]
+fn loads_from_the_data_section() {
+    let mut data = vec![0; 32];
+    data.extend_from_slice(&word(0x2a));
+
+    // DATALOADN 32
+    let mut main = vec![0xd1, 0x00, 0x20];
+    main.extend_from_slice(&RETURN_TOP);
+
+    assert_eq!(execute(&[(0, 0x80, 2, &main)], &data), word(0x2a));
+}
+
+#[test]
+fn runs_relative_jumps() {
+    // PUSH1 3 | loop: PUSH1 1 SWAP1 SUB DUP1 RJUMPI loop | <return top>
+    let mut main = vec![0x60, 0x03, 0x60, 0x01, 0x90, 0x03, 0x80, 0xe1, 0xff, 0xf8];
+    main.extend_from_slice(&RETURN_TOP);
+
+    assert_eq!(execute(&[(0, 0x80, 2, &main)], &[]), word(0));
+}
+
+#[test]
+fn rejects_invalid_containers() {
+    assert_eq!(
+        EofContainer::parse(&[0xef, 0x01]).unwrap_err(),
+        EofError::InvalidMagic
+    );
+
+    let mut truncated = encode_container(&[(0, 0x80, 0, &[0x00])], &[]);
+    truncated.pop();
+    assert_eq!(
+        EofContainer::parse(&truncated).unwrap_err(),
+        EofError::InvalidContainerSize
+    );
+
+    // PUSH1 without its immediate
+    assert_eq!(
+        validate(&[(0, 0x80, 0, &[0x60])], &[]),
+        Err(EofError::TruncatedImmediate { section: 0, pc: 0 })
+    );
+
+    // JUMP is deprecated in EOF code
+    assert_eq!(
+        validate(&[(0, 0x80, 1, &[0x60, 0x00, 0x56])], &[]),
+        Err(EofError::UndefinedInstruction { section: 0, pc: 2 })
+    );
+
+    // RJUMP into a PUSH1 immediate
+    assert_eq!(
+        validate(&[(0, 0x80, 1, &[0x60, 0x00, 0xe0, 0xff, 0xfc])], &[]),
+        Err(EofError::InvalidRelativeJump { section: 0, pc: 2 })
+    );
+
+    // DATALOADN reading past the end of the data
+    assert_eq!(
+        validate(&[(0, 0x80, 1, &[0xd1, 0x00, 0x01, 0x00])], &[0; 32]),
+        Err(EofError::InvalidDataOffset { section: 0, pc: 0 })
+    );
+}
+
+#[test]
+fn validates_stack_heights() {
+    assert_eq!(
+        validate(&[(0, 0x80, 0, &[0x01, 0x00])], &[]),
+        Err(EofError::StackUnderflow { section: 0, pc: 0 })
+    );
+
+    assert_eq!(
+        validate(&[(0, 0x80, 2, &[0x60, 0x01, 0x00])], &[]),
+        Err(EofError::InvalidMaxStackHeight { section: 0 })
+    );
+
+    assert_eq!(
+        validate(&[(0, 0x80, 1, &[0x60, 0x01])], &[]),
+        Err(EofError::NoTerminatingInstruction { section: 0 })
+    );
+
+    // A loop that pushes an item in every iteration
+    assert_eq!(
+        validate(&[(0, 0x80, 1, &[0x60, 0x01, 0xe0, 0xff, 0xfb])], &[]),
+        Err(EofError::InconsistentStackHeight { section: 0, pc: 2 })
+    );
+
+    assert_eq!(
+        validate(&[(0, 0x80, 0, &[0x00]), (0, 0x80, 0, &[0x00])], &[]),
+        Err(EofError::UnreachableCodeSection { section: 1 })
+    );
+
+    // A section whose inputs don't fit in its max stack height, called with CALLF
+    assert_eq!(
+        validate(
+            &[(0, 0x80, 0, &[0xe3, 0x00, 0x01, 0x00]), (2, 0, 0, &[0xe4])],
+            &[]
+        ),
+        Err(EofError::InvalidTypeSection)
+    );
+
+    // JUMPF to a section that returns more outputs than the jumping one
+    assert_eq!(
+        validate(
+            &[
+                (0, 0x80, 0, &[0xe3, 0x00, 0x01, 0x00]),
+                (0, 0, 0, &[0xe5, 0x00, 0x02]),
+                (0, 1, 1, &[0x5f, 0xe4]),
+            ],
+            &[]
+        ),
+        Err(EofError::InconsistentStackHeight { section: 1, pc: 0 })
+    );
+}
@@ -0,0 +1,55 @@
+extern crate tiny_evm;
+
+use ethereum_types::U256;
+use tiny_evm::{run, BlockContext, Bytecode, CallContext, ExecutionError, HaltReason, Opcode};
+
+fn run_code(code: &[u8]) -> tiny_evm::ExecutionResult {
+    run(
+        &Bytecode::new(code),
+        &CallContext::default(),
+        &BlockContext::default(),
+    )
+}
+
+#[test]
+fn reports_where_and_why_the_execution_failed() {
+    // PUSH1 0 PUSH1 7 JUMP
+    let result = run_code(&[0x60, 0x00, 0x60, 0x07, 0x56]);
+    assert_eq!(
+        result.error,
+        Some(ExecutionError::InvalidJump {
+            pc: 4,
+            opcode: Opcode::JUMP,
+            destination: U256::from(7),
+        })
+    );
+    assert_eq!(result.halt_reason, None);
+
+    // PUSH4 0xffffffe0 MLOAD
+    let result = run_code(&[0x63, 0xff, 0xff, 0xff, 0xe0, 0x51]);
+    assert_eq!(
+        result.error,
+        Some(ExecutionError::OutOfGas {
+            pc: 5,
+            opcode: Opcode::MLOAD,
+            requested_memory_size: U256::from(0x100000000u64),
+        })
+    );
+}
+
+#[test]
+fn distinguishes_halt_reasons() {
+    assert_eq!(run_code(&[0x60, 0x01]).halt_reason, Some(HaltReason::Stop));
+    assert_eq!(run_code(&[0x00]).halt_reason, Some(HaltReason::Stop));
+
+    // PUSH1 0 DUP1 RETURN
+    let result = run_code(&[0x60, 0x00, 0x80, 0xf3]);
+    assert!(result.is_success());
+    assert_eq!(result.halt_reason, Some(HaltReason::Return));
+
+    // PUSH1 0 SELFDESTRUCT
+    assert_eq!(
+        run_code(&[0x60, 0x00, 0xff]).halt_reason,
+        Some(HaltReason::SelfDestruct)
+    );
+}
@@ -0,0 +1,128 @@
+extern crate tiny_evm;
+
+use ethereum_types::{Address, U256};
+use std::sync::Arc;
+use tiny_evm::abi::AbiValue;
+use tiny_evm::{create_address, CodeCache, Evm, EvmError, ExecutionError, Opcode};
+
+// Stores 5 in slot 0, and deploys a counter that increments it and returns its new value
+fn counter_init_code() -> Vec<u8> {
+    // PUSH1 5 PUSH1 0 SSTORE PUSH1 18 PUSH1 17 PUSH1 0 CODECOPY PUSH1 18 PUSH1 0 RETURN
+    let mut code = hex::decode("60056000556012601160003960126000f3").unwrap();
+    // PUSH1 0 SLOAD PUSH1 1 ADD DUP1 PUSH1 0 SSTORE PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+    code.extend(hex::decode("6000546001018060005560005260206000f3").unwrap());
+    code
+}
+
+#[test]
+fn deploys_and_interacts_with_contracts() {
+    let deployer = Address::from_low_u64_be(1);
+    let code_cache = Arc::new(CodeCache::default());
+    let mut evm = Evm::new();
+    evm.set_code_cache(Arc::clone(&code_cache));
+
+    let deployment = evm
+        .deploy(deployer, &counter_init_code(), U256::zero())
+        .unwrap();
+    assert!(deployment.result.is_success());
+
+    let counter = deployment.address;
+    assert_eq!(counter, create_address(&deployer, 0));
+    assert_eq!(evm.code(&counter).len(), 18);
+    assert_eq!(evm.nonce(&counter), 1);
+    assert_eq!(evm.nonce(&deployer), 1);
+    assert_eq!(evm.storage(&counter, &U256::zero()), U256::from(5));
+
+    // Calls don't change the state
+    for _ in 0..2 {
+        assert_eq!(
+            evm.call_function(deployer, counter, "increment() returns (uint256)", &[]),
+            Ok(vec![AbiValue::from(6u64)])
+        );
+    }
+
+    let result = evm.transact(deployer, counter, U256::zero(), &[]).unwrap();
+    assert_eq!(U256::from(result.return_data.as_slice()), U256::from(6));
+
+    let result = evm.transact(deployer, counter, U256::zero(), &[]).unwrap();
+    assert_eq!(U256::from(result.return_data.as_slice()), U256::from(7));
+    assert_eq!(evm.storage(&counter, &U256::zero()), U256::from(7));
+
+    // The init code and the counter's code are only analyzed once
+    assert_eq!(code_cache.len(), 2);
+}
+
+#[test]
+fn transfers_value_and_discards_failed_transactions() {
+    let alice = Address::from_low_u64_be(1);
+    let bob = Address::from_low_u64_be(2);
+
+    let mut evm = Evm::new();
+    evm.set_balance(alice, U256::from(100));
+
+    evm.transact(alice, bob, U256::from(30), &[]).unwrap();
+    assert_eq!(evm.balance(&alice), U256::from(70));
+    assert_eq!(evm.balance(&bob), U256::from(30));
+
+    assert_eq!(
+        evm.transact(alice, bob, U256::from(1000), &[]).err(),
+        Some(EvmError::InsufficientBalance)
+    );
+
+    // PUSH1 1 PUSH1 0 SSTORE PUSH1 0 DUP1 REVERT
+    evm.set_code(bob, hex::decode("6001600055600080fd").unwrap());
+
+    let result = evm.transact(alice, bob, U256::from(10), &[]).unwrap();
+    assert_eq!(
+        result.error,
+        Some(ExecutionError::Revert {
+            pc: 8,
+            opcode: Opcode::REVERT
+        })
+    );
+    assert_eq!(evm.balance(&alice), U256::from(70));
+    assert_eq!(evm.balance(&bob), U256::from(30));
+    assert_eq!(evm.storage(&bob, &U256::zero()), U256::zero());
+    assert_eq!(evm.nonce(&alice), 2);
+}
+
+#[test]
+fn reverts_to_snapshots() {
+    let deployer = Address::from_low_u64_be(1);
+    let mut evm = Evm::new();
+    evm.set_balance(deployer, U256::from(100));
+
+    let counter = evm
+        .deploy(deployer, &counter_init_code(), U256::zero())
+        .unwrap()
+        .address;
+
+    let fixture = evm.snapshot();
+
+    evm.transact(deployer, counter, U256::from(40), &[])
+        .unwrap();
+    evm.block.number = 10;
+
+    let nested = evm.snapshot();
+    let other = evm
+        .deploy(deployer, &counter_init_code(), U256::zero())
+        .unwrap()
+        .address;
+    evm.set_storage(counter, U256::one(), U256::from(9));
+
+    assert!(evm.revert_to(nested));
+    assert!(evm.account(&other).is_none());
+    assert_eq!(evm.storage(&counter, &U256::one()), U256::zero());
+    assert_eq!(evm.storage(&counter, &U256::zero()), U256::from(6));
+    assert_eq!(evm.block.number, 10);
+
+    assert!(evm.revert_to(fixture));
+    assert_eq!(evm.storage(&counter, &U256::zero()), U256::from(5));
+    assert_eq!(evm.balance(&deployer), U256::from(100));
+    assert_eq!(evm.balance(&counter), U256::zero());
+    assert_eq!(evm.nonce(&deployer), 1);
+    assert_eq!(evm.block.number, 0);
+
+    // Reverting invalidates the snapshot
+    assert!(!evm.revert_to(fixture));
+}
@@ -0,0 +1,113 @@
+extern crate tiny_evm;
+
+use ethereum_types::{Address, H256, U256};
+use sha3::{Digest, Keccak256};
+use tiny_evm::{run, BlockContext, Bytecode, CallContext, Host};
+
+struct TestHost;
+
+impl Host for TestHost {
+    fn balance(&self, address: &Address) -> U256 {
+        U256::from(address.to_low_u64_be() * 10)
+    }
+
+    fn code(&self, _address: &Address) -> Vec<u8> {
+        vec![0x60, 0x00]
+    }
+
+    fn storage(&self, address: &Address, key: &U256) -> U256 {
+        key + 100 + address.to_low_u64_be() * 1000
+    }
+
+    fn block_hash(&self, number: u32) -> H256 {
+        H256::from_low_u64_be(number as u64 + 1000)
+    }
+}
+
+fn run_and_return_top(code: &[u8]) -> U256 {
+    run_in_contract_and_return_top(code, Address::zero())
+}
+
+fn run_in_contract_and_return_top(code: &[u8], contract_address: Address) -> U256 {
+    // Appends PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+    let mut code = code.to_vec();
+    code.extend_from_slice(&[0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3]);
+
+    let call_context = CallContext {
+        host: &TestHost,
+        contract_address,
+        ..CallContext::default()
+    };
+    let block_context = BlockContext {
+        number: 300,
+        ..BlockContext::default()
+    };
+
+    let result = run(&Bytecode::new(&code), &call_context, &block_context);
+    assert_eq!(result.error, None);
+
+    U256::from_big_endian(&result.return_data)
+}
+
+#[test]
+fn reads_state_from_the_host() {
+    // PUSH1 7 BALANCE
+    assert_eq!(run_and_return_top(&[0x60, 0x07, 0x31]), U256::from(70));
+    // PUSH1 7 EXTCODESIZE
+    assert_eq!(run_and_return_top(&[0x60, 0x07, 0x3b]), U256::from(2));
+    // PUSH1 1 SLOAD
+    assert_eq!(run_and_return_top(&[0x60, 0x01, 0x54]), U256::from(101));
+    // PUSH1 2 PUSH1 1 SSTORE PUSH1 1 SLOAD
+    assert_eq!(
+        run_and_return_top(&[0x60, 0x02, 0x60, 0x01, 0x55, 0x60, 0x01, 0x54]),
+        U256::from(2)
+    );
+    // Unwritten slots are read from the storage of the running contract
+    assert_eq!(
+        run_in_contract_and_return_top(&[0x60, 0x01, 0x54], Address::from_low_u64_be(5)),
+        U256::from(5101)
+    );
+
+    // PUSH1 2 PUSH1 0 PUSH1 30 PUSH1 7 EXTCODECOPY PUSH1 0 MLOAD
+    assert_eq!(
+        run_and_return_top(&[
+            0x60, 0x02, 0x60, 0x00, 0x60, 0x1e, 0x60, 0x07, 0x3c, 0x60, 0x00, 0x51
+        ]),
+        U256::from(0x6000)
+    );
+    // PUSH1 7 EXTCODEHASH
+    assert_eq!(
+        run_and_return_top(&[0x60, 0x07, 0x3f]),
+        U256::from_big_endian(&Keccak256::digest(&[0x60, 0x00]))
+    );
+}
+
+#[test]
+fn hashes_non_existent_accounts_as_zero() {
+    // PUSH1 7 EXTCODEHASH PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN, with the default empty host
+    let code = [
+        0x60, 0x07, 0x3f, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3,
+    ];
+    let result = run(
+        &Bytecode::new(&code),
+        &CallContext::default(),
+        &BlockContext::default(),
+    );
+
+    assert_eq!(result.return_data, vec![0; 32]);
+}
+
+#[test]
+fn only_returns_recent_block_hashes() {
+    // PUSH2 299 BLOCKHASH
+    assert_eq!(
+        run_and_return_top(&[0x61, 0x01, 0x2b, 0x40]),
+        U256::from(1299)
+    );
+    // PUSH1 44 BLOCKHASH
+    assert_eq!(run_and_return_top(&[0x60, 0x2c, 0x40]), U256::from(1044));
+    // PUSH1 43 BLOCKHASH
+    assert_eq!(run_and_return_top(&[0x60, 0x2b, 0x40]), U256::zero());
+    // PUSH2 300 BLOCKHASH
+    assert_eq!(run_and_return_top(&[0x61, 0x01, 0x2c, 0x40]), U256::zero());
+}
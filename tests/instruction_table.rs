@@ -0,0 +1,214 @@
+extern crate tiny_evm;
+
+use ethereum_types::{Address, H256, U256};
+use tiny_evm::{
+    disassemble, run, run_with_table, BlockContext, Bytecode, CallContext, CustomOpcode,
+    ExecutionError, ExecutionStatus, Host, InstructionTable, InstructionTableError, Opcode,
+    StepResult, VmState,
+};
+
+// PUSH1 2 PUSH1 3 <opcode> PUSH1 0 MSTORE PUSH1 1 PUSH1 31 RETURN
+fn code_with(opcode: u8) -> Vec<u8> {
+    vec![
+        0x60, 0x02, 0x60, 0x03, opcode, 0x60, 0x00, 0x52, 0x60, 0x01, 0x60, 0x1f, 0xf3,
+    ]
+}
+
+fn push_answer(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    vm_state.stack.pop()?;
+    vm_state.stack.pop()?;
+    vm_state.stack.push(U256::from(42))?;
+
+    Ok(ExecutionStatus::Running)
+}
+
+fn run_code(code: &[u8], table: &InstructionTable) -> (Vec<u8>, Option<ExecutionError>) {
+    let bytecode = Bytecode::new(code);
+    let result = run_with_table(
+        &bytecode,
+        table,
+        &CallContext::default(),
+        &BlockContext::default(),
+    );
+
+    (result.return_data, result.error)
+}
+
+#[test]
+fn uses_the_default_handlers() {
+    assert_eq!(
+        run_code(&code_with(0x01), &InstructionTable::new()),
+        (vec![5], None)
+    );
+
+    let code = code_with(0x0c);
+    let result = run(
+        &Bytecode::new(&code),
+        &CallContext::default(),
+        &BlockContext::default(),
+    );
+    assert_eq!(
+        result.error,
+        Some(ExecutionError::InvalidOpcode {
+            pc: 4,
+            opcode: Opcode::UNRECOGNIZED0C
+        })
+    );
+}
+
+#[test]
+fn overrides_handlers() {
+    let mut table = InstructionTable::new();
+    table.set(Opcode::ADD, push_answer);
+    table.set(Opcode::UNRECOGNIZED0C, push_answer);
+
+    assert_eq!(run_code(&code_with(0x01), &table), (vec![42], None));
+    assert_eq!(run_code(&code_with(0x0c), &table), (vec![42], None));
+
+    // The default table isn't affected
+    assert_eq!(
+        run_code(&code_with(0x01), InstructionTable::default_table()),
+        (vec![5], None)
+    );
+}
+
+fn answer_opcode() -> CustomOpcode {
+    CustomOpcode {
+        name: "ANSWER",
+        inputs: 2,
+        outputs: 1,
+        handler: push_answer,
+    }
+}
+
+#[test]
+fn registers_custom_opcodes() {
+    let mut table = InstructionTable::new();
+    table
+        .register_custom_opcode(Opcode::UNRECOGNIZED0C, answer_opcode())
+        .unwrap();
+
+    assert_eq!(run_code(&code_with(0x0c), &table), (vec![42], None));
+    assert_eq!(
+        table.custom_opcode(Opcode::UNRECOGNIZED0C).unwrap().name,
+        "ANSWER"
+    );
+
+    assert_eq!(
+        table.register_custom_opcode(Opcode::UNRECOGNIZED0C, answer_opcode()),
+        Err(InstructionTableError::OpcodeAlreadyRegistered(
+            Opcode::UNRECOGNIZED0C
+        ))
+    );
+
+    assert_eq!(
+        table.register_custom_opcode(Opcode::ADD, answer_opcode()),
+        Err(InstructionTableError::OpcodeAlreadyAssigned(Opcode::ADD))
+    );
+}
+
+#[test]
+fn checks_the_stack_arity_of_custom_opcodes() {
+    let mut table = InstructionTable::new();
+    table
+        .register_custom_opcode(Opcode::UNRECOGNIZED0C, answer_opcode())
+        .unwrap();
+
+    // PUSH1 1 <custom>
+    assert_eq!(
+        run_code(&[0x60, 0x01, 0x0c], &table),
+        (
+            vec![],
+            Some(ExecutionError::StackUnderflow {
+                pc: 2,
+                opcode: Opcode::UNRECOGNIZED0C
+            })
+        )
+    );
+}
+
+// Pushes the balance of the contract, read from the host
+fn self_balance(
+    _opcode: Opcode,
+    vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    let balance = call_context.host.balance(&call_context.contract_address);
+    vm_state.stack.push(balance)?;
+
+    Ok(ExecutionStatus::Running)
+}
+
+struct RichHost;
+
+impl Host for RichHost {
+    fn balance(&self, address: &Address) -> U256 {
+        U256::from(address.to_low_u64_be() + 100)
+    }
+
+    fn code(&self, _address: &Address) -> Vec<u8> {
+        Vec::new()
+    }
+
+    fn storage(&self, _address: &Address, _key: &U256) -> U256 {
+        U256::zero()
+    }
+
+    fn block_hash(&self, _number: u32) -> H256 {
+        H256::zero()
+    }
+}
+
+#[test]
+fn gives_custom_opcodes_access_to_the_host() {
+    let mut table = InstructionTable::new();
+    table
+        .register_custom_opcode(
+            Opcode::UNRECOGNIZED0C,
+            CustomOpcode {
+                name: "SELFBALANCE",
+                inputs: 0,
+                outputs: 1,
+                handler: self_balance,
+            },
+        )
+        .unwrap();
+
+    let call_context = CallContext {
+        contract_address: Address::from_low_u64_be(5),
+        host: &RichHost,
+        ..CallContext::default()
+    };
+
+    // <custom> PUSH1 0 MSTORE8 PUSH1 1 PUSH1 0 RETURN
+    let code = [0x0c, 0x60, 0x00, 0x53, 0x60, 0x01, 0x60, 0x00, 0xf3];
+    let result = run_with_table(
+        &Bytecode::new(&code),
+        &table,
+        &call_context,
+        &BlockContext::default(),
+    );
+
+    assert_eq!(result.return_data, vec![105]);
+}
+
+#[test]
+fn disassembles_custom_opcodes_by_name() {
+    let mut table = InstructionTable::new();
+    table
+        .register_custom_opcode(Opcode::UNRECOGNIZED0C, answer_opcode())
+        .unwrap();
+
+    assert_eq!(
+        disassemble(&[0x61, 0x01, 0x02, 0x0c, 0x0d, 0x60], &table),
+        "0000: PUSH2 0x0102\n0003: ANSWER\n0004: UNRECOGNIZED0D\n0005: PUSH1"
+    );
+}
@@ -0,0 +1,29 @@
+extern crate tiny_evm;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use tiny_evm::{run, BlockContext, Bytecode, CallContext, JumpdestMap};
+
+#[test]
+fn ignores_jumpdests_in_push_data() {
+    // PUSH2 0x5b5b JUMPDEST PUSH1 0x5b JUMPDEST
+    let map = JumpdestMap::analyze(&[0x61, 0x5b, 0x5b, 0x5b, 0x60, 0x5b, 0x5b]);
+
+    let jumpdests: Vec<usize> = (0..10).filter(|pc| map.contains(*pc)).collect();
+    assert_eq!(jumpdests, vec![3, 6]);
+}
+
+#[test]
+fn reuses_cached_analysis() {
+    // PUSH1 4 JUMP INVALID JUMPDEST STOP
+    let code = [0x60, 0x04, 0x56, 0xfe, 0x5b, 0x00];
+
+    let mut cache = HashMap::new();
+    let analyzed = Bytecode::new(&code);
+    cache.insert(analyzed.code_hash(), analyzed.jumpdests().clone());
+
+    let bytecode = Bytecode::with_jumpdests(&code, Arc::clone(&cache[&analyzed.code_hash()]));
+    let result = run(&bytecode, &CallContext::default(), &BlockContext::default());
+
+    assert_eq!(result.error, None);
+}
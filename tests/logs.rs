@@ -0,0 +1,59 @@
+extern crate tiny_evm;
+
+use ethereum_types::{Address, H256, U256};
+use tiny_evm::{run, BlockContext, Bytecode, CallContext, ExecutionError, Log, Opcode};
+
+#[test]
+fn emits_logs_from_the_contract_address() {
+    // PUSH1 0xaa PUSH1 0 MSTORE8 PUSH1 2 PUSH1 1 PUSH1 1 PUSH1 0 LOG2 PUSH1 7 PUSH1 3 SSTORE
+    let code = [
+        0x60, 0xaa, 0x60, 0x00, 0x53, 0x60, 0x02, 0x60, 0x01, 0x60, 0x01, 0x60, 0x00, 0xa2, 0x60,
+        0x07, 0x60, 0x03, 0x55,
+    ];
+
+    let call_context = CallContext {
+        contract_address: Address::repeat_byte(0x11),
+        ..CallContext::default()
+    };
+
+    let result = run(
+        &Bytecode::new(&code),
+        &call_context,
+        &BlockContext::default(),
+    );
+
+    assert_eq!(result.error, None);
+    assert_eq!(
+        result.logs,
+        vec![Log {
+            address: Address::repeat_byte(0x11),
+            topics: vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)],
+            data: vec![0xaa],
+        }]
+    );
+    assert_eq!(result.storage.get(&U256::from(3)), Some(&U256::from(7)));
+}
+
+#[test]
+fn discards_logs_and_storage_on_revert() {
+    // PUSH1 7 PUSH1 3 SSTORE PUSH1 0 PUSH1 0 LOG0 PUSH1 0 PUSH1 0 REVERT
+    let code = [
+        0x60, 0x07, 0x60, 0x03, 0x55, 0x60, 0x00, 0x60, 0x00, 0xa0, 0x60, 0x00, 0x60, 0x00, 0xfd,
+    ];
+
+    let result = run(
+        &Bytecode::new(&code),
+        &CallContext::default(),
+        &BlockContext::default(),
+    );
+
+    assert_eq!(
+        result.error,
+        Some(ExecutionError::Revert {
+            pc: 14,
+            opcode: Opcode::REVERT
+        })
+    );
+    assert!(result.logs.is_empty());
+    assert!(result.storage.is_empty());
+}
@@ -304,6 +304,7 @@ fn run_test_file(test_file: &TestFile, passed: &mut u32, skipped: &mut u32) {
             caller_address: test.exec.caller,
             origin_address: test.exec.origin,
             gas_price: test.exec.gas_price,
+            ..CallContext::default()
         };
 
         let bytecode = Bytecode::new(test.exec.code.as_slice());
@@ -0,0 +1,83 @@
+extern crate tiny_evm;
+
+use ethereum_types::U256;
+use tiny_evm::{run, BlockContext, Bytecode, CallContext, RevertDecoder, RevertReason};
+
+// Returns code that reverts with the given data
+fn revert_with(data: &[u8]) -> Vec<u8> {
+    let mut code = Vec::new();
+
+    for (i, chunk) in data.chunks(32).enumerate() {
+        let mut word = [0; 32];
+        word[..chunk.len()].copy_from_slice(chunk);
+
+        // PUSH32 word PUSH1 offset MSTORE
+        code.push(0x7f);
+        code.extend_from_slice(&word);
+        code.extend_from_slice(&[0x60, (i * 32) as u8, 0x52]);
+    }
+
+    // PUSH1 length PUSH1 0 REVERT
+    code.extend_from_slice(&[0x60, data.len() as u8, 0x60, 0x00, 0xfd]);
+    code
+}
+
+fn word(value: u64) -> [u8; 32] {
+    let mut word = [0; 32];
+    U256::from(value).to_big_endian(&mut word);
+    word
+}
+
+#[test]
+fn decodes_errors_and_panics() {
+    let mut data = hex::decode("08c379a0").unwrap();
+    data.extend_from_slice(&word(32));
+    data.extend_from_slice(&word(18));
+    data.extend_from_slice(b"Not enough balance");
+
+    let code = revert_with(&data);
+    let result = run(
+        &Bytecode::new(&code),
+        &CallContext::default(),
+        &BlockContext::default(),
+    );
+    assert_eq!(
+        result.revert_reason(),
+        Some(RevertReason::Error("Not enough balance".to_string()))
+    );
+
+    let mut data = hex::decode("4e487b71").unwrap();
+    data.extend_from_slice(&word(0x11));
+
+    let code = revert_with(&data);
+    let result = run(
+        &Bytecode::new(&code),
+        &CallContext::default(),
+        &BlockContext::default(),
+    );
+    let reason = result.revert_reason().unwrap();
+    assert_eq!(reason, RevertReason::Panic(U256::from(0x11)));
+    assert_eq!(
+        reason.to_string(),
+        "Panic(0x11): arithmetic overflow or underflow"
+    );
+}
+
+#[test]
+fn decodes_custom_errors_when_known() {
+    // keccak256("Unauthorized(address)")[..4]
+    let mut data = hex::decode("8e4a23d6").unwrap();
+    data.extend_from_slice(&word(0xaa));
+
+    let mut decoder = RevertDecoder::new();
+    assert_eq!(decoder.decode(&data), None);
+
+    decoder.add_custom_error("Unauthorized(address)");
+    assert_eq!(
+        decoder.decode(&data),
+        Some(RevertReason::Custom {
+            signature: "Unauthorized(address)".to_string(),
+            arguments: word(0xaa).to_vec(),
+        })
+    );
+}
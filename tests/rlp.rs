@@ -0,0 +1,112 @@
+extern crate tiny_evm;
+
+use tiny_evm::create_address;
+use tiny_evm::rlp::{self, DecoderError, Rlp, RlpStream};
+
+use ethereum_types::{Address, U256};
+
+fn address(hex_str: &str) -> Address {
+    Address::from_slice(&hex::decode(hex_str).unwrap())
+}
+
+#[test]
+fn encodes_canonically() {
+    assert_eq!(rlp::encode(&b"dog"[..]), hex::decode("83646f67").unwrap());
+    assert_eq!(rlp::encode(&b""[..]), vec![0x80]);
+    assert_eq!(rlp::encode(&vec![0x0f_u8]), vec![0x0f]);
+    assert_eq!(rlp::encode(&U256::zero()), vec![0x80]);
+    assert_eq!(rlp::encode(&U256::from(15)), vec![0x0f]);
+    assert_eq!(rlp::encode(&1024u64), vec![0x82, 0x04, 0x00]);
+
+    let list = rlp::encode_list(&[b"cat".to_vec(), b"dog".to_vec()]);
+    assert_eq!(list, hex::decode("c88363617483646f67").unwrap());
+    assert_eq!(RlpStream::new().out(), vec![0xc0]);
+
+    let long_string = b"Lorem ipsum dolor sit amet, consectetur adipisicing elit".to_vec();
+    let encoded = rlp::encode(&long_string);
+    assert_eq!(encoded[..2], [0xb8, 0x38]);
+    assert_eq!(encoded[2..], long_string[..]);
+}
+
+#[test]
+fn roundtrips() {
+    let value = U256::from_dec_str("123456789012345678901234567890").unwrap();
+    assert_eq!(rlp::decode::<U256>(&rlp::encode(&value)), Ok(value));
+
+    let long_string = vec![0xaa; 1024];
+    assert_eq!(
+        rlp::decode::<Vec<u8>>(&rlp::encode(&long_string)),
+        Ok(long_string)
+    );
+
+    let sender = address("6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0");
+    let encoded = RlpStream::new().append(&sender).append(&7u64).out();
+    let items = Rlp::new(&encoded).unwrap().items().unwrap();
+
+    assert_eq!(items[0].as_val::<Address>(), Ok(sender));
+    assert_eq!(items[1].as_val::<u64>(), Ok(7));
+
+    // Lists of anything but bytes are encoded as RLP lists
+    let nested = vec![vec![1u64, 2], vec![], vec![1024]];
+    let encoded = rlp::encode(&nested);
+    assert_eq!(encoded, hex::decode("c8c20102c0c3820400").unwrap());
+    assert_eq!(rlp::decode::<Vec<Vec<u64>>>(&encoded), Ok(nested));
+    assert_eq!(
+        rlp::decode_list::<U256>(&[0xc1, 0x80]),
+        Ok(vec![U256::zero()])
+    );
+    assert_eq!(
+        rlp::decode::<Vec<u64>>(&[0x80]),
+        Err(DecoderError::ExpectedList)
+    );
+}
+
+#[test]
+fn rejects_non_canonical_input() {
+    assert_eq!(
+        rlp::decode::<Vec<u8>>(&[0x81, 0x05]),
+        Err(DecoderError::NonCanonicalSingleByte)
+    );
+    assert_eq!(
+        rlp::decode::<Vec<u8>>(&[0xb8, 0x05, 1, 2, 3, 4, 5]),
+        Err(DecoderError::NonCanonicalSize)
+    );
+    assert_eq!(
+        rlp::decode::<U256>(&[0x82, 0x00, 0x01]),
+        Err(DecoderError::IntegerWithLeadingZeros)
+    );
+    assert_eq!(
+        rlp::decode::<u64>(&[0x89, 1, 2, 3, 4, 5, 6, 7, 8, 9]),
+        Err(DecoderError::IntegerOverflow)
+    );
+    assert_eq!(
+        rlp::decode::<Vec<u8>>(&[0x83, 0x01]),
+        Err(DecoderError::InputTooShort)
+    );
+    assert_eq!(
+        rlp::decode::<Vec<u8>>(&[0x01, 0x02]),
+        Err(DecoderError::TrailingBytes)
+    );
+    assert_eq!(
+        rlp::decode::<Address>(&[0x82, 0x01, 0x02]),
+        Err(DecoderError::InvalidLength)
+    );
+    assert_eq!(
+        rlp::decode::<Vec<u8>>(&[0xc0]),
+        Err(DecoderError::ExpectedBytes)
+    );
+}
+
+#[test]
+fn derives_create_addresses() {
+    let sender = address("6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0");
+
+    assert_eq!(
+        create_address(&sender, 0),
+        address("cd234a471b72ba2f1ccf0a70fcaba648a5eecd8d")
+    );
+    assert_eq!(
+        create_address(&sender, 1),
+        address("343c43a37d37dff08ae8c4a11544c718abb4fcf8")
+    );
+}
@@ -0,0 +1,120 @@
+extern crate tiny_evm;
+
+use tiny_evm::{
+    run_with_table_and_tracer, run_with_tracer, BlockContext, Bytecode, CallContext, CustomOpcode,
+    ExecutionError, ExecutionStatus, InstructionTable, Opcode, StepResult, Tracer, TracerAction,
+    VmState,
+};
+
+#[derive(Default)]
+struct StepRecorder {
+    steps: Vec<(usize, String, usize)>,
+    failed_steps: usize,
+    // Aborts the execution after this many steps
+    max_steps: Option<usize>,
+}
+
+impl Tracer for StepRecorder {
+    fn step(
+        &mut self,
+        opcode: Opcode,
+        vm_state: &VmState,
+        instruction_table: &InstructionTable,
+    ) -> TracerAction {
+        if self.max_steps == Some(self.steps.len()) {
+            return TracerAction::Abort;
+        }
+
+        self.steps.push((
+            vm_state.pc,
+            instruction_table.opcode_name(opcode),
+            vm_state.stack.len(),
+        ));
+
+        TracerAction::Continue
+    }
+
+    fn step_end(&mut self, _opcode: Opcode, _vm_state: &VmState, result: &StepResult) {
+        if result.is_err() {
+            self.failed_steps += 1;
+        }
+    }
+}
+
+fn noop(
+    _opcode: Opcode,
+    _vm_state: &mut VmState,
+    _bytecode: &Bytecode,
+    _call_context: &CallContext,
+    _block_context: &BlockContext,
+) -> StepResult {
+    Ok(ExecutionStatus::Running)
+}
+
+#[test]
+fn traces_every_step() {
+    // PUSH1 1 PUSH2 0x0203 ADD POP
+    let code = [0x60, 0x01, 0x61, 0x02, 0x03, 0x01, 0x50, 0x50];
+    let mut tracer = StepRecorder::default();
+
+    let result = run_with_tracer(
+        &Bytecode::new(&code),
+        &mut tracer,
+        &CallContext::default(),
+        &BlockContext::default(),
+    );
+
+    assert!(result.error.is_some());
+    assert_eq!(
+        tracer.steps,
+        vec![
+            (0, "PUSH1".to_string(), 0),
+            (2, "PUSH2".to_string(), 1),
+            (5, "ADD".to_string(), 2),
+            (6, "POP".to_string(), 1),
+            (7, "POP".to_string(), 0),
+        ]
+    );
+    assert_eq!(tracer.failed_steps, 1);
+}
+
+#[test]
+fn traces_custom_opcodes_and_aborts() {
+    let mut table = InstructionTable::new();
+    table
+        .register_custom_opcode(
+            Opcode::UNRECOGNIZED0C,
+            CustomOpcode {
+                name: "NOOP",
+                inputs: 0,
+                outputs: 0,
+                handler: noop,
+            },
+        )
+        .unwrap();
+
+    // NOOP JUMPDEST PUSH1 1 JUMP, which loops forever
+    let code = [0x0c, 0x5b, 0x60, 0x01, 0x56];
+    let mut tracer = StepRecorder {
+        max_steps: Some(100),
+        ..StepRecorder::default()
+    };
+
+    let result = run_with_table_and_tracer(
+        &Bytecode::new(&code),
+        &table,
+        &mut tracer,
+        &CallContext::default(),
+        &BlockContext::default(),
+    );
+
+    assert_eq!(tracer.steps[0], (0, "NOOP".to_string(), 0));
+    assert_eq!(tracer.steps.len(), 100);
+    assert_eq!(
+        result.error,
+        Some(ExecutionError::Aborted {
+            pc: 1,
+            opcode: Opcode::JUMPDEST
+        })
+    );
+}
@@ -0,0 +1,204 @@
+extern crate tiny_evm;
+
+use tiny_evm::rlp::RlpStream;
+use tiny_evm::{
+    apply_authorizations, delegated_address, delegation_designator, resolve_code, Account,
+    Transaction, TransactionError, TransactionType,
+};
+
+use ethereum_types::{Address, U256};
+use k256::ecdsa::SigningKey;
+use sha3::{Digest, Keccak256};
+use std::collections::HashMap;
+
+fn address(hex_str: &str) -> Address {
+    Address::from_slice(&hex::decode(hex_str).unwrap())
+}
+
+fn signing_key() -> SigningKey {
+    SigningKey::from_slice(&[0x46; 32]).unwrap()
+}
+
+// The address of the 0x4646...46 private key
+fn signer_address() -> Address {
+    address("9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f")
+}
+
+fn sign_typed(tx_type: u8, unsigned_fields: &RlpStream) -> Vec<u8> {
+    let mut payload = vec![tx_type];
+    payload.extend_from_slice(&unsigned_fields.out());
+
+    let hash = Keccak256::digest(&payload);
+    let (signature, recovery_id) = signing_key().sign_prehash_recoverable(&hash).unwrap();
+
+    let mut stream = unsigned_fields.clone();
+    stream
+        .append(&(recovery_id.to_byte() as u64))
+        .append(&U256::from_big_endian(&signature.r().to_bytes()))
+        .append(&U256::from_big_endian(&signature.s().to_bytes()));
+
+    let mut raw = vec![tx_type];
+    raw.extend_from_slice(&stream.out());
+
+    raw
+}
+
+#[test]
+fn decodes_eip155_transaction() {
+    let raw = hex::decode("f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83").unwrap();
+
+    let tx = Transaction::decode(&raw).unwrap();
+
+    assert_eq!(tx.tx_type, TransactionType::Legacy);
+    assert_eq!(tx.chain_id, Some(1));
+    assert_eq!(tx.nonce, 9);
+    assert_eq!(tx.gas_limit, 21000);
+    assert_eq!(tx.max_fee_per_gas, U256::from(20_000_000_000u64));
+    assert_eq!(
+        tx.to,
+        Some(address("3535353535353535353535353535353535353535"))
+    );
+    assert_eq!(tx.value, U256::from(1_000_000_000_000_000_000u64));
+    assert_eq!(tx.sender, signer_address());
+    assert_eq!(tx.hash.as_bytes(), &Keccak256::digest(&raw)[..]);
+
+    assert_eq!(tx.validate_chain_id(1), Ok(()));
+    assert_eq!(
+        tx.validate_chain_id(5),
+        Err(TransactionError::ChainIdMismatch {
+            expected: 5,
+            actual: 1
+        })
+    );
+}
+
+#[test]
+fn decodes_dynamic_fee_transaction() {
+    let mut fields = RlpStream::new();
+    fields
+        .append(&1u64)
+        .append(&3u64)
+        .append(&U256::from(2))
+        .append(&U256::from(100))
+        .append(&50000u64)
+        .append(&address("3535353535353535353535353535353535353535"))
+        .append(&U256::from(7))
+        .append(&vec![0xaa_u8, 0xbb])
+        .append_raw(&RlpStream::new().out());
+
+    let raw = sign_typed(2, &fields);
+    let tx = Transaction::decode(&raw).unwrap();
+
+    assert_eq!(tx.tx_type, TransactionType::DynamicFee);
+    assert_eq!(tx.sender, signer_address());
+    assert_eq!(tx.nonce, 3);
+    assert_eq!(tx.data, vec![0xaa, 0xbb]);
+    assert_eq!(tx.effective_gas_price(U256::from(10)), U256::from(12));
+    assert_eq!(tx.effective_gas_price(U256::from(99)), U256::from(100));
+
+    let call_context = tx.call_context(U256::from(10));
+    assert_eq!(call_context.caller_address, signer_address());
+    assert_eq!(call_context.calldata, &[0xaa, 0xbb]);
+}
+
+#[test]
+fn rejects_invalid_transactions() {
+    assert_eq!(Transaction::decode(&[]), Err(TransactionError::EmptyInput));
+    assert_eq!(
+        Transaction::decode(&[0x05, 0xc0]),
+        Err(TransactionError::UnsupportedType(5))
+    );
+    assert_eq!(
+        Transaction::decode(&[0x02, 0xc0]),
+        Err(TransactionError::InvalidFieldCount)
+    );
+
+    // A set code transaction that creates a contract
+    let mut fields = RlpStream::new();
+    fields
+        .append(&1u64)
+        .append(&0u64)
+        .append(&U256::from(2))
+        .append(&U256::from(100))
+        .append(&50000u64)
+        .append(&b""[..])
+        .append(&U256::zero())
+        .append(&b""[..])
+        .append_raw(&RlpStream::new().out())
+        .append_raw(&RlpStream::new().out());
+
+    assert_eq!(
+        Transaction::decode(&sign_typed(4, &fields)),
+        Err(TransactionError::MissingRecipient)
+    );
+}
+
+fn sign_authorization(chain_id: u64, delegate: &Address, nonce: u64) -> Vec<u8> {
+    let mut message = vec![0x05];
+    message.extend_from_slice(
+        &RlpStream::new()
+            .append(&U256::from(chain_id))
+            .append(delegate)
+            .append(&nonce)
+            .out(),
+    );
+
+    let hash = Keccak256::digest(&message);
+    let (signature, recovery_id) = signing_key().sign_prehash_recoverable(&hash).unwrap();
+
+    RlpStream::new()
+        .append(&U256::from(chain_id))
+        .append(delegate)
+        .append(&nonce)
+        .append(&(recovery_id.to_byte() as u64))
+        .append(&U256::from_big_endian(&signature.r().to_bytes()))
+        .append(&U256::from_big_endian(&signature.s().to_bytes()))
+        .out()
+}
+
+#[test]
+fn applies_authorizations() {
+    let delegate = address("1111111111111111111111111111111111111111");
+
+    let mut authorization_list = RlpStream::new();
+    authorization_list
+        .append_raw(&sign_authorization(1, &delegate, 0))
+        // Skipped, as the nonce was already used by the previous authorization
+        .append_raw(&sign_authorization(1, &Address::zero(), 0))
+        // Skipped, as it's for another chain
+        .append_raw(&sign_authorization(5, &Address::zero(), 1));
+
+    let mut fields = RlpStream::new();
+    fields
+        .append(&1u64)
+        .append(&0u64)
+        .append(&U256::from(2))
+        .append(&U256::from(100))
+        .append(&50000u64)
+        .append(&delegate)
+        .append(&U256::zero())
+        .append(&b""[..])
+        .append_raw(&RlpStream::new().out())
+        .append_raw(&authorization_list.out());
+
+    let tx = Transaction::decode(&sign_typed(4, &fields)).unwrap();
+    assert_eq!(tx.authorization_list[0].authority(), Some(signer_address()));
+
+    let mut accounts = HashMap::new();
+    accounts.insert(
+        delegate,
+        Account {
+            code: vec![0x60, 0x01],
+            ..Account::default()
+        },
+    );
+
+    let authorities = apply_authorizations(&mut accounts, &tx, 1);
+    assert_eq!(authorities, vec![signer_address()]);
+
+    let authority = &accounts[&signer_address()];
+    assert_eq!(authority.nonce, 1);
+    assert_eq!(authority.code, delegation_designator(&delegate));
+    assert_eq!(delegated_address(&authority.code), Some(delegate));
+    assert_eq!(resolve_code(&accounts, &signer_address()), &[0x60, 0x01]);
+}
@@ -0,0 +1,115 @@
+extern crate tiny_evm;
+
+use tiny_evm::{state_root, storage_root, Trie};
+
+use ethereum_types::{H256, U256};
+use std::collections::HashMap;
+
+const EMPTY_TRIE_ROOT: &str = "56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421";
+
+fn h256(hex_str: &str) -> H256 {
+    H256::from_slice(&hex::decode(hex_str).unwrap())
+}
+
+fn build_trie(entries: &[(&str, &str)]) -> Trie {
+    let mut trie = Trie::new();
+
+    for (key, value) in entries {
+        trie.insert(key.as_bytes(), value.as_bytes().to_vec());
+    }
+
+    trie
+}
+
+#[test]
+fn empty_trie_root() {
+    assert_eq!(Trie::new().root_hash(), h256(EMPTY_TRIE_ROOT));
+    assert_eq!(storage_root(&HashMap::new()), h256(EMPTY_TRIE_ROOT));
+    assert_eq!(state_root(&HashMap::new()), h256(EMPTY_TRIE_ROOT));
+}
+
+#[test]
+fn known_roots() {
+    let trie = build_trie(&[
+        ("do", "verb"),
+        ("dog", "puppy"),
+        ("doge", "coin"),
+        ("horse", "stallion"),
+    ]);
+    assert_eq!(
+        trie.root_hash(),
+        h256("5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84")
+    );
+
+    let trie = build_trie(&[
+        ("doe", "reindeer"),
+        ("dog", "puppy"),
+        ("dogglesworth", "cat"),
+    ]);
+    assert_eq!(
+        trie.root_hash(),
+        h256("8aad789dff2f538bca5d8ea56e8abe10f4c7ba3a5dea95fea4cd6e7c3a1168d3")
+    );
+}
+
+#[test]
+fn insertion_order_doesnt_matter() {
+    let entries = [
+        ("do", "verb"),
+        ("dog", "puppy"),
+        ("doge", "coin"),
+        ("horse", "stallion"),
+    ];
+
+    let mut reversed = entries;
+    reversed.reverse();
+
+    assert_eq!(
+        build_trie(&entries).root_hash(),
+        build_trie(&reversed).root_hash()
+    );
+}
+
+#[test]
+fn get_and_delete() {
+    let mut trie = build_trie(&[
+        ("do", "verb"),
+        ("dog", "puppy"),
+        ("doge", "coin"),
+        ("horse", "stallion"),
+    ]);
+
+    assert_eq!(trie.get(b"dog"), Some(&b"puppy"[..]));
+    assert_eq!(trie.get(b"doge"), Some(&b"coin"[..]));
+    assert_eq!(trie.get(b"d"), None);
+    assert_eq!(trie.get(b"dogecoin"), None);
+
+    trie.delete(b"doge");
+    trie.delete(b"horse");
+    trie.delete(b"not-present");
+
+    assert_eq!(trie.get(b"doge"), None);
+    assert_eq!(
+        trie.root_hash(),
+        build_trie(&[("do", "verb"), ("dog", "puppy")]).root_hash()
+    );
+
+    trie.delete(b"do");
+    trie.insert(b"dog", Vec::new());
+
+    assert!(trie.is_empty());
+    assert_eq!(trie.root_hash(), h256(EMPTY_TRIE_ROOT));
+}
+
+#[test]
+fn zero_storage_values_are_ignored() {
+    let mut storage = HashMap::new();
+    storage.insert(U256::from(1), U256::from(0x1234));
+
+    let root = storage_root(&storage);
+
+    storage.insert(U256::from(2), U256::zero());
+
+    assert_eq!(storage_root(&storage), root);
+    assert_ne!(root, h256(EMPTY_TRIE_ROOT));
+}